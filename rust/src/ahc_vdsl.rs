@@ -1,10 +1,13 @@
 pub mod ahc_vdsl {
     #[cfg(feature = "vis")]
     mod vis_enabled {
+        use std::collections::BTreeMap;
         use std::fmt::{Display, Write};
         use std::fs::File;
         use std::io::Write as IoWrite;
+        use std::ops::Range;
         use std::path::PathBuf;
+        use std::rc::Rc;
 
         use rustc_hash::{FxHashMap, FxHashSet};
 
@@ -97,9 +100,14 @@ pub mod ahc_vdsl {
             }
         }
 
+        #[allow(clippy::large_enum_variant)]
         pub enum VisItem {
             Grid(VisGrid),
             Plane(Vis2DPlane),
+            HexGrid(VisHexGrid),
+            TriGrid(VisTriGrid),
+            Graph(VisGraph),
+            Tree(VisTree),
         }
 
         impl VisItem {
@@ -107,6 +115,10 @@ pub mod ahc_vdsl {
                 match self {
                     VisItem::Grid(grid) => grid.to_vis_string(mode),
                     VisItem::Plane(plane) => plane.to_vis_string(mode),
+                    VisItem::HexGrid(grid) => grid.to_vis_string(mode),
+                    VisItem::TriGrid(grid) => grid.to_vis_string(mode),
+                    VisItem::Graph(graph) => graph.to_vis_string(mode),
+                    VisItem::Tree(tree) => tree.to_vis_string(mode),
                 }
             }
         }
@@ -144,6 +156,46 @@ pub mod ahc_vdsl {
                 self.fill_color = Some(color);
                 self
             }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+                let height = self.height.unwrap_or(200);
+                let text_color = self.text_color.as_deref().unwrap_or("#000000");
+                let fill_color = self.fill_color.as_deref().unwrap_or("#ffffff");
+
+                // 改行を含む場合は行数を指定した複数行形式で出力
+                if self.text.contains('\n') {
+                    let text_lines: Vec<&str> = self.text.split('\n').collect();
+                    writeln!(
+                        &mut s,
+                        "$v({}) TEXTAREA_LINES {} {} {} {} {}",
+                        mode,
+                        self.title,
+                        height,
+                        text_color,
+                        fill_color,
+                        text_lines.len()
+                    )
+                    .unwrap();
+                    for line in text_lines {
+                        writeln!(&mut s, "{}", line.trim_end_matches('\r')).unwrap();
+                    }
+                    return s;
+                }
+
+                let text_output = if self.text.is_empty() {
+                    "\"\"".to_string()
+                } else {
+                    self.text.clone()
+                };
+                writeln!(
+                    &mut s,
+                    "$v({}) TEXTAREA {} {} {} {} {}",
+                    mode, self.title, height, text_color, fill_color, text_output
+                )
+                .unwrap();
+                s
+            }
         }
 
         pub struct VisFrame {
@@ -152,6 +204,9 @@ pub mod ahc_vdsl {
             score: String,
             textarea: Vec<VisTextArea>,
             bar_graphs: Vec<VisBarGraph>,
+            line_charts: Vec<VisLineChart>,
+            histograms: Vec<VisHistogram>,
+            tables: Vec<VisTable>,
             with_debug: bool,
         }
 
@@ -163,6 +218,9 @@ pub mod ahc_vdsl {
                     score: String::new(),
                     textarea: Vec::new(),
                     bar_graphs: Vec::new(),
+                    line_charts: Vec::new(),
+                    histograms: Vec::new(),
+                    tables: Vec::new(),
                     with_debug: false,
                 }
             }
@@ -182,6 +240,26 @@ pub mod ahc_vdsl {
                 self
             }
 
+            pub fn add_hex_grid(mut self, grid: VisHexGrid) -> Self {
+                self.items.push(VisItem::HexGrid(grid));
+                self
+            }
+
+            pub fn add_tri_grid(mut self, grid: VisTriGrid) -> Self {
+                self.items.push(VisItem::TriGrid(grid));
+                self
+            }
+
+            pub fn add_graph(mut self, graph: VisGraph) -> Self {
+                self.items.push(VisItem::Graph(graph));
+                self
+            }
+
+            pub fn add_tree(mut self, tree: VisTree) -> Self {
+                self.items.push(VisItem::Tree(tree));
+                self
+            }
+
             pub fn add_item(mut self, item: VisItem) -> Self {
                 self.items.push(item);
                 self
@@ -202,6 +280,21 @@ pub mod ahc_vdsl {
                 self
             }
 
+            pub fn add_line_chart(mut self, line_chart: VisLineChart) -> Self {
+                self.line_charts.push(line_chart);
+                self
+            }
+
+            pub fn add_histogram(mut self, histogram: VisHistogram) -> Self {
+                self.histograms.push(histogram);
+                self
+            }
+
+            pub fn add_table(mut self, table: VisTable) -> Self {
+                self.tables.push(table);
+                self
+            }
+
             pub fn enable_debug(mut self) -> Self {
                 self.with_debug = true;
                 self
@@ -232,20 +325,7 @@ pub mod ahc_vdsl {
 
                 // Output textarea
                 for textarea in &self.textarea {
-                    let height = textarea.height.unwrap_or(200);
-                    let text_color = textarea.text_color.as_deref().unwrap_or("#000000");
-                    let fill_color = textarea.fill_color.as_deref().unwrap_or("#ffffff");
-                    let text_output = if textarea.text.is_empty() {
-                        "\"\"".to_string()
-                    } else {
-                        textarea.text.clone()
-                    };
-                    writeln!(
-                        &mut output,
-                        "$v({}) TEXTAREA {} {} {} {} {}",
-                        mode, textarea.title, height, text_color, fill_color, text_output
-                    )
-                    .unwrap();
+                    output.push_str(&textarea.to_vis_string(mode));
                 }
 
                 // Output bar graphs
@@ -253,6 +333,21 @@ pub mod ahc_vdsl {
                     output.push_str(&bar_graph.to_vis_string(mode));
                 }
 
+                // Output line charts
+                for line_chart in &self.line_charts {
+                    output.push_str(&line_chart.to_vis_string(mode));
+                }
+
+                // Output histograms
+                for histogram in &self.histograms {
+                    output.push_str(&histogram.to_vis_string(mode));
+                }
+
+                // Output tables
+                for table in &self.tables {
+                    output.push_str(&table.to_vis_string(mode));
+                }
+
                 // Output debug flag
                 if self.with_debug {
                     writeln!(&mut output, "$v({mode}) DEBUG").unwrap();
@@ -271,6 +366,7 @@ pub mod ahc_vdsl {
             }
         }
 
+        #[derive(Clone, Copy)]
         pub struct ItemBounds {
             pub left: f64,
             pub top: f64,
@@ -292,23 +388,158 @@ pub mod ahc_vdsl {
         pub struct Vis2DPlane {
             h: f64,
             w: f64,
-            circle_groups: FxHashMap<(Color, Color), Vec<Circle>>,
-            line_groups: FxHashMap<(Color, u64), (f64, Vec<((f64, f64), (f64, f64))>)>,
-            polygon_groups: Vec<PolygonGroup>,
-            text_groups: FxHashMap<(Color, u64), (f64, Vec<TextItem>)>,
+            // z の小さいレイヤから順に描画する。図形は current_layer に追加される
+            layers: BTreeMap<i32, PlaneLayer>,
+            current_layer: i32,
+            // 描画する座標範囲 (min_x, max_x, min_y, max_y)。既定は [0, w] x [0, h]
+            view: (f64, f64, f64, f64),
+            y_up: bool,
+            keep_aspect: bool,
             bounds: Option<ItemBounds>,
         }
 
+        // 1つのレイヤ内の図形。グループは最初に追加された順に出力する
+        #[derive(Default)]
+        struct PlaneLayer {
+            // 円・線は色と線の太さ・線種ごとにまとめる
+            circle_group_index: FxHashMap<CircleGroupKey, usize>,
+            circle_groups: Vec<PlaneCircleGroup>,
+            line_group_index: FxHashMap<(Color, u64, LineStyle), usize>,
+            line_groups: Vec<PlaneLineGroup>,
+            polylines: Vec<PlanePolyline>,
+            // 楕円・円弧・ベジェ曲線も色と線の太さ・線種ごとにまとめる
+            ellipse_group_index: FxHashMap<CircleGroupKey, usize>,
+            ellipse_groups: Vec<PlaneEllipseGroup>,
+            arc_group_index: FxHashMap<(Color, Option<Color>, u64, LineStyle), usize>,
+            arc_groups: Vec<PlaneArcGroup>,
+            curve_group_index: FxHashMap<(Color, u64, LineStyle, bool), usize>,
+            curve_groups: Vec<PlaneCurveGroup>,
+            // 矢印は色・太さ・矢じりの大きさごとにまとめる
+            arrow_group_index: FxHashMap<(Color, u64, u64), usize>,
+            arrow_groups: Vec<PlaneArrowGroup>,
+            polygon_groups: Vec<PolygonGroup>,
+            rect_group_index: FxHashMap<(Color, Color), usize>,
+            rect_groups: Vec<PlaneRectGroup>,
+            // 点は形状・色・大きさごとにまとめる
+            point_group_index: FxHashMap<(MarkerShape, Color, u64), usize>,
+            point_groups: Vec<PlanePointGroup>,
+            text_group_index: FxHashMap<TextGroupKey, usize>,
+            text_groups: Vec<PlaneTextGroup>,
+        }
+
+        type Segment = ((f64, f64), (f64, f64));
+        // (枠線の色, 塗りつぶしの色, 枠線の太さ, 線種)
+        type CircleGroupKey = (Color, Color, u64, LineStyle);
+
+        // (色, フォントサイズ, 位置, 回転角, 背景色, 余白, dx, dy)
+        type TextGroupKey = (Color, u64, TextAnchor, u64, Option<Color>, u64, u64, u64);
+
+        // 円・多角形の枠線の既定の太さ
+        const DEFAULT_STROKE_WIDTH: f64 = 2.0;
+
+        // key のグループの位置を返す。無ければ new で作って末尾に追加する
+        fn group_index<K: std::hash::Hash + Eq, G>(
+            index: &mut FxHashMap<K, usize>,
+            groups: &mut Vec<G>,
+            key: K,
+            new: impl FnOnce() -> G,
+        ) -> usize {
+            *index.entry(key).or_insert_with(|| {
+                groups.push(new());
+                groups.len() - 1
+            })
+        }
+
+        struct PlaneCircleGroup {
+            stroke_color: Color,
+            fill_color: Color,
+            stroke_width: f64,
+            style: LineStyle,
+            circles: Vec<Circle>,
+        }
+
+        struct PlaneLineGroup {
+            color: Color,
+            width: f64,
+            style: LineStyle,
+            lines: Vec<Segment>,
+        }
+
+        // (cx, cy, rx, ry, angle)
+        struct PlaneEllipseGroup {
+            stroke_color: Color,
+            fill_color: Color,
+            stroke_width: f64,
+            style: LineStyle,
+            ellipses: Vec<(f64, f64, f64, f64, f64)>,
+        }
+
+        // (cx, cy, r, start, end)。fill が None なら円弧、Some なら扇形
+        struct PlaneArcGroup {
+            color: Color,
+            fill: Option<Color>,
+            width: f64,
+            style: LineStyle,
+            arcs: Vec<(f64, f64, f64, f64, f64)>,
+        }
+
+        // 2次なら 3 点、3次なら 4 点ずつ制御点を並べる
+        struct PlaneCurveGroup {
+            color: Color,
+            width: f64,
+            style: LineStyle,
+            cubic: bool,
+            points: Vec<(f64, f64)>,
+        }
+
+        struct PlaneArrowGroup {
+            color: Color,
+            width: f64,
+            head_size: f64,
+            arrows: Vec<Segment>,
+        }
+
+        struct PlaneRectGroup {
+            stroke_color: Color,
+            fill_color: Color,
+            rects: Vec<Rect>,
+        }
+
+        struct PlanePointGroup {
+            shape: MarkerShape,
+            color: Color,
+            size: f64,
+            points: Vec<(f64, f64)>,
+        }
+
+        struct PlaneTextGroup {
+            color: Color,
+            font_size: f64,
+            style: TextStyle,
+            texts: Vec<TextItem>,
+        }
+
+        struct PlanePolyline {
+            color: Color,
+            width: f64,
+            style: LineStyle,
+            points: Vec<(f64, f64)>,
+        }
+
         pub struct Circle {
             x: f64,
             y: f64,
             r: f64,
+            tooltip: String,
         }
 
         pub struct PolygonGroup {
             stroke_color: Color,
             fill_color: Color,
+            stroke_width: f64,
+            style: LineStyle,
             vertices: Vec<(f64, f64)>,
+            tooltip: String,
         }
 
         pub struct TextItem {
@@ -317,227 +548,169 @@ pub mod ahc_vdsl {
             text: String,
         }
 
-        impl Vis2DPlane {
-            pub fn new(h: f64, w: f64, bounds: Option<ItemBounds>) -> Self {
+        impl Circle {
+            pub fn new(x: f64, y: f64, r: f64) -> Self {
                 Self {
-                    h,
-                    w,
-                    circle_groups: FxHashMap::default(),
-                    line_groups: FxHashMap::default(),
-                    polygon_groups: Vec::new(),
-                    text_groups: FxHashMap::default(),
-                    bounds,
+                    x,
+                    y,
+                    r,
+                    tooltip: String::new(),
                 }
             }
 
-            pub fn set_bounds(mut self, bounds: ItemBounds) -> Self {
-                self.bounds = Some(bounds);
+            pub fn with_tooltip(mut self, tooltip: String) -> Self {
+                self.tooltip = tooltip;
                 self
             }
+        }
 
-            pub fn add_circle_group(
-                mut self,
-                stroke_color: Color,
-                fill_color: Color,
-                circles: Vec<Circle>,
-            ) -> Self {
-                self.circle_groups
-                    .entry((stroke_color, fill_color))
-                    .or_default()
-                    .extend(circles);
-                self
+        // (x, y, r)
+        impl From<(f64, f64, f64)> for Circle {
+            fn from((x, y, r): (f64, f64, f64)) -> Self {
+                Circle::new(x, y, r)
             }
+        }
 
-            pub fn add_circle(
-                mut self,
-                stroke_color: Color,
-                fill_color: Color,
-                x: f64,
-                y: f64,
-                r: f64,
-            ) -> Self {
-                self.circle_groups
-                    .entry((stroke_color, fill_color))
-                    .or_default()
-                    .push(Circle { x, y, r });
-                self
+        impl PolygonGroup {
+            pub fn new(stroke_color: Color, fill_color: Color, vertices: Vec<(f64, f64)>) -> Self {
+                Self {
+                    stroke_color,
+                    fill_color,
+                    stroke_width: DEFAULT_STROKE_WIDTH,
+                    style: LineStyle::Solid,
+                    vertices,
+                    tooltip: String::new(),
+                }
             }
 
-            pub fn add_line_group(
-                mut self,
-                color: Color,
-                width: f64,
-                points: Vec<(f64, f64)>,
-            ) -> Self {
-                let width_key = width.to_bits();
-                // pointsをペアに変換 (2点で一つの線)
-                let lines: Vec<((f64, f64), (f64, f64))> = points
-                    .chunks_exact(2)
-                    .map(|chunk| (chunk[0], chunk[1]))
-                    .collect();
-                self.line_groups
-                    .entry((color, width_key))
-                    .or_insert_with(|| (width, Vec::new()))
-                    .1
-                    .extend(lines);
+            pub fn with_stroke(mut self, width: f64, style: LineStyle) -> Self {
+                self.stroke_width = width;
+                self.style = style;
                 self
             }
 
-            pub fn add_line(
-                mut self,
-                color: Color,
-                width: f64,
-                ax: f64,
-                ay: f64,
-                bx: f64,
-                by: f64,
-            ) -> Self {
-                let width_key = width.to_bits();
-                let entry = self
-                    .line_groups
-                    .entry((color, width_key))
-                    .or_insert_with(|| (width, Vec::new()));
-                entry.1.push(((ax, ay), (bx, by)));
+            pub fn with_tooltip(mut self, tooltip: String) -> Self {
+                self.tooltip = tooltip;
                 self
             }
+        }
 
-            pub fn add_polygon(
-                mut self,
-                stroke_color: Color,
-                fill_color: Color,
-                vertices: Vec<(f64, f64)>,
-            ) -> Self {
-                self.polygon_groups.push(PolygonGroup {
-                    stroke_color,
-                    fill_color,
-                    vertices,
-                });
-                self
+        // 左上 (x, y)、幅 w、高さ h の軸に平行な長方形
+        pub struct Rect {
+            x: f64,
+            y: f64,
+            w: f64,
+            h: f64,
+            label: String,
+        }
+
+        impl Rect {
+            pub fn new(x: f64, y: f64, w: f64, h: f64) -> Self {
+                Self {
+                    x,
+                    y,
+                    w,
+                    h,
+                    label: String::new(),
+                }
             }
 
-            pub fn add_text(
-                mut self,
-                color: Color,
-                font_size: f64,
-                x: f64,
-                y: f64,
-                text: String,
-            ) -> Self {
-                let font_size_key = font_size.to_bits();
-                let entry = self
-                    .text_groups
-                    .entry((color, font_size_key))
-                    .or_insert_with(|| (font_size, Vec::new()));
-                entry.1.push(TextItem { x, y, text });
+            // 長方形の中央に表示するラベル
+            pub fn with_label(mut self, label: String) -> Self {
+                self.label = label;
                 self
             }
+        }
 
-            pub fn to_vis_string(&self, mode: &str) -> String {
-                let mut s = String::new();
+        // (x, y, w, h)
+        impl From<(f64, f64, f64, f64)> for Rect {
+            fn from((x, y, w, h): (f64, f64, f64, f64)) -> Self {
+                Rect::new(x, y, w, h)
+            }
+        }
 
-                // Output 2D_PLANE header with optional bounds
-                if let Some(bounds) = &self.bounds {
-                    writeln!(
-                        &mut s,
-                        "$v({}) 2D_PLANE({}, {}, {}, {}) {} {}",
-                        mode, bounds.left, bounds.top, bounds.right, bounds.bottom, self.h, self.w
-                    )
-                    .unwrap();
-                } else {
-                    writeln!(&mut s, "$v({}) 2D_PLANE {} {}", mode, self.h, self.w).unwrap();
-                }
+        impl TextItem {
+            pub fn new(x: f64, y: f64, text: String) -> Self {
+                Self { x, y, text }
+            }
+        }
 
-                // Output circles
-                if !self.circle_groups.is_empty() {
-                    writeln!(&mut s, "CIRCLES").unwrap();
-                    writeln!(&mut s, "{}", self.circle_groups.len()).unwrap();
-                    for ((stroke_color, fill_color), circles) in &self.circle_groups {
-                        write!(&mut s, "{} {} {}", stroke_color, fill_color, circles.len())
-                            .unwrap();
-                        for circle in circles {
-                            write!(&mut s, " {} {} {}", circle.x, circle.y, circle.r).unwrap();
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
-                }
+        // テキストの配置・回転・背景。既定は (x, y) を中心に、回転・背景なし
+        #[derive(Clone, Copy, PartialEq)]
+        pub struct TextStyle {
+            anchor: TextAnchor,
+            rotation: f64,
+            background: Option<Color>,
+            padding: f64,
+            offset: (f64, f64),
+        }
 
-                // Output lines
-                if !self.line_groups.is_empty() {
-                    writeln!(&mut s, "LINES").unwrap();
-                    writeln!(&mut s, "{}", self.line_groups.len()).unwrap();
-                    for ((color, _width_key), (width, lines)) in &self.line_groups {
-                        write!(&mut s, "{} {} {}", color, width, lines.len()).unwrap();
-                        for ((x1, y1), (x2, y2)) in lines {
-                            write!(&mut s, " {x1} {y1} {x2} {y2}").unwrap();
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
-                }
+        impl Default for TextStyle {
+            fn default() -> Self {
+                TextStyle::new()
+            }
+        }
 
-                // Output polygons
-                if !self.polygon_groups.is_empty() {
-                    writeln!(&mut s, "POLYGONS").unwrap();
-                    writeln!(&mut s, "{}", self.polygon_groups.len()).unwrap();
-                    for group in &self.polygon_groups {
-                        write!(
-                            &mut s,
-                            "{} {} {}",
-                            group.stroke_color,
-                            group.fill_color,
-                            group.vertices.len()
-                        )
-                        .unwrap();
-                        for (x, y) in &group.vertices {
-                            write!(&mut s, " {x} {y}").unwrap();
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
+        impl TextStyle {
+            pub fn new() -> Self {
+                Self {
+                    anchor: TextAnchor::Center,
+                    rotation: 0.0,
+                    background: None,
+                    padding: 0.0,
+                    offset: (0.0, 0.0),
                 }
+            }
 
-                // Output text
-                if !self.text_groups.is_empty() {
-                    writeln!(&mut s, "TEXT").unwrap();
-                    writeln!(&mut s, "{}", self.text_groups.len()).unwrap();
-                    for ((color, _font_size_key), (font_size, texts)) in &self.text_groups {
-                        write!(&mut s, "{} {} {}", color, font_size, texts.len()).unwrap();
-                        for item in texts {
-                            if item.text.contains(' ') || item.text.is_empty() {
-                                write!(&mut s, " {} {} \"{}\"", item.x, item.y, item.text).unwrap();
-                            } else {
-                                write!(&mut s, " {} {} {}", item.x, item.y, item.text).unwrap();
-                            }
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
-                }
+            // テキストのどの位置を (x, y) に合わせるか (TopLeft なら左上の角)
+            pub fn with_anchor(mut self, anchor: TextAnchor) -> Self {
+                self.anchor = anchor;
+                self
+            }
 
-                s
+            // (x, y) を中心とした時計回りの回転角 (度)
+            pub fn with_rotation(mut self, degrees: f64) -> Self {
+                self.rotation = degrees;
+                self
+            }
+
+            // 背景の塗りつぶしと、文字の周りの余白 (ピクセル)
+            pub fn with_background(mut self, color: Color, padding: f64) -> Self {
+                self.background = Some(color);
+                self.padding = padding;
+                self
+            }
+
+            // 画面上でずらす量 (ピクセル、右・下が正)
+            pub fn with_offset(mut self, dx: f64, dy: f64) -> Self {
+                self.offset = (dx, dy);
+                self
             }
         }
 
-        pub struct VisGrid {
-            h: usize,
-            w: usize,
-            conf: VisGridConf,
-            cell_colors: Vec<Vec<Color>>,
-            cell_texts: Vec<Vec<String>>,
-            no_wall_vertical_pos: FxHashSet<(usize, usize)>,
-            no_wall_horizontal_pos: FxHashSet<(usize, usize)>,
-            lines: Vec<(Vec<(usize, usize)>, Color)>,
-            bounds: Option<ItemBounds>,
+        // (x, y, text)
+        impl From<(f64, f64, String)> for TextItem {
+            fn from((x, y, text): (f64, f64, String)) -> Self {
+                TextItem::new(x, y, text)
+            }
         }
 
-        impl VisGrid {
-            pub fn new(h: usize, w: usize, bounds: Option<ItemBounds>) -> Self {
+        impl From<(f64, f64, &str)> for TextItem {
+            fn from((x, y, text): (f64, f64, &str)) -> Self {
+                TextItem::new(x, y, text.to_string())
+            }
+        }
+
+        impl Vis2DPlane {
+            pub fn new(h: f64, w: f64, bounds: Option<ItemBounds>) -> Self {
                 Self {
                     h,
                     w,
-                    conf: Default::default(),
-                    cell_colors: vec![vec![WHITE; w]; h],
-                    cell_texts: vec![vec![String::new(); w]; h],
-                    no_wall_vertical_pos: FxHashSet::default(),
-                    no_wall_horizontal_pos: FxHashSet::default(),
-                    lines: Vec::new(),
+                    layers: BTreeMap::new(),
+                    current_layer: 0,
+                    view: (0.0, w, 0.0, h),
+                    y_up: false,
+                    keep_aspect: false,
                     bounds,
                 }
             }
@@ -547,583 +720,6125 @@ pub mod ahc_vdsl {
                 self
             }
 
-            pub fn update_cell_color(mut self, p: (usize, usize), color: Color) -> Self {
-                self.cell_colors[p.1][p.0] = color;
-                self
-            }
-
-            pub fn update_text(mut self, p: (usize, usize), text: String) -> Self {
-                self.cell_texts[p.1][p.0] = text;
+            // 以降に追加する図形のレイヤを指定する (既定は 0)。z が大きいレイヤほど手前に描画される
+            // 同じレイヤ内は追加順ではなく、多角形・長方形 → 線 → 円 → テキストの順に描画される
+            pub fn set_layer(mut self, z: i32) -> Self {
+                self.current_layer = z;
                 self
             }
 
-            pub fn add_line(mut self, line: Vec<(usize, usize)>, color: Color) -> Self {
-                self.lines.push((line, color));
+            // 描画する座標範囲を [min_x, max_x] x [min_y, max_y] にする (負の座標も使える)
+            // 範囲が空か NaN を含むときは警告して、それまでの範囲のままにする
+            pub fn set_view(mut self, min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Self {
+                if !(min_x < max_x && min_y < max_y) {
+                    eprintln!(
+                        "warning: Vis2DPlane::set_view: empty range x: [{min_x}, {max_x}], y: [{min_y}, {max_y}]; the view is not changed"
+                    );
+                    return self;
+                }
+                self.view = (min_x, max_x, min_y, max_y);
+                self.w = max_x - min_x;
+                self.h = max_y - min_y;
                 self
             }
 
-            pub fn remove_wall_vertical(mut self, p: (usize, usize)) -> Self {
-                self.no_wall_vertical_pos.insert(p);
+            // true なら y 軸を上向きにする (数学の座標系)
+            pub fn set_y_up(mut self, y_up: bool) -> Self {
+                self.y_up = y_up;
                 self
             }
 
-            pub fn add_wall_vertical(mut self, p: (usize, usize)) -> Self {
-                self.no_wall_vertical_pos.remove(&p);
+            // true なら x と y の縮尺をそろえ、余白を残して中央に描画する
+            pub fn set_keep_aspect(mut self, keep_aspect: bool) -> Self {
+                self.keep_aspect = keep_aspect;
                 self
             }
 
-            pub fn remove_wall_horizontal(mut self, p: (usize, usize)) -> Self {
-                self.no_wall_horizontal_pos.insert(p);
-                self
+            fn layer(&mut self) -> &mut PlaneLayer {
+                self.layers.entry(self.current_layer).or_default()
             }
 
-            pub fn add_wall_horizontal(mut self, p: (usize, usize)) -> Self {
-                self.no_wall_horizontal_pos.remove(&p);
-                self
+            // Circle や (x, y, r) をまとめて追加する
+            pub fn add_circle_group<C: Into<Circle>>(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                circles: impl IntoIterator<Item = C>,
+            ) -> Self {
+                self.add_styled_circle_group(
+                    stroke_color,
+                    fill_color,
+                    DEFAULT_STROKE_WIDTH,
+                    LineStyle::Solid,
+                    circles,
+                )
             }
 
-            pub fn to_vis_string(&self, mode_name: &str) -> String {
-                let mut s = String::new();
+            // 枠線の太さと線種を指定して円をまとめて追加する
+            pub fn add_styled_circle_group<C: Into<Circle>>(
+                mut self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                circles: impl IntoIterator<Item = C>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (stroke_color, fill_color, stroke_width.to_bits(), style);
+                let index = group_index(
+                    &mut layer.circle_group_index,
+                    &mut layer.circle_groups,
+                    key,
+                    || PlaneCircleGroup {
+                        stroke_color,
+                        fill_color,
+                        stroke_width,
+                        style,
+                        circles: Vec::new(),
+                    },
+                );
+                layer.circle_groups[index]
+                    .circles
+                    .extend(circles.into_iter().map(Into::into));
+                self
+            }
 
-                // G H W BORDER TEXT を書き込み (with optional bounds)
-                if let Some(bounds) = &self.bounds {
-                    writeln!(
-                        &mut s,
-                        "$v({}) GRID({}, {}, {}, {}) {} {} {} {} {}",
-                        mode_name,
-                        bounds.left,
-                        bounds.top,
-                        bounds.right,
-                        bounds.bottom,
-                        self.h,
-                        self.w,
-                        self.conf.border_color,
-                        self.conf.text_color,
-                        self.conf.default_cell_color
-                    )
-                    .unwrap();
-                } else {
-                    writeln!(
-                        &mut s,
-                        "$v({}) GRID {} {} {} {} {}",
-                        mode_name,
-                        self.h,
-                        self.w,
-                        self.conf.border_color,
-                        self.conf.text_color,
-                        self.conf.default_cell_color
-                    )
-                    .unwrap();
-                }
+            pub fn add_circle(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                x: f64,
+                y: f64,
+                r: f64,
+            ) -> Self {
+                self.add_circle_with_tooltip(stroke_color, fill_color, x, y, r, String::new())
+            }
 
-                // 各セルの色の位置を書き込み
-                writeln!(&mut s, "CELL_COLORS_POS").unwrap();
-                let mut color_to_pos = FxHashMap::default();
-                for y in 0..self.h {
-                    for x in 0..self.w {
-                        let color = self.cell_colors[y][x];
-                        if color == self.conf.default_cell_color {
-                            continue;
-                        }
-                        color_to_pos
-                            .entry(color)
-                            .or_insert_with(Vec::new)
-                            .push((x, y));
-                    }
-                }
-                writeln!(&mut s, "{}", color_to_pos.len()).unwrap();
-                for (color, positions) in &color_to_pos {
-                    write!(&mut s, "{} {}", color, positions.len()).unwrap();
-                    for &(x, y) in positions {
-                        write!(&mut s, " {x} {y}").unwrap();
-                    }
-                    writeln!(&mut s).unwrap();
-                }
-                // 各セルのテキストを書き込み（すべて空の場合は省略）
-                let all_texts_empty = self
-                    .cell_texts
-                    .iter()
-                    .all(|row| row.iter().all(|t| t.is_empty()));
-                if !all_texts_empty {
-                    writeln!(&mut s, "CELL_TEXT").unwrap();
-                    for y in 0..self.h {
-                        // その行の末尾側の空セルは省略し、途中の空セルは "" で出力
-                        let last_non_empty = (0..self.w)
-                            .rev()
-                            .find(|&x| !self.cell_texts[y][x].is_empty());
-                        if let Some(last) = last_non_empty {
-                            for x in 0..=last {
-                                if x > 0 {
-                                    write!(&mut s, " ").unwrap();
-                                }
-                                if self.cell_texts[y][x].is_empty() {
-                                    write!(&mut s, "\"\"").unwrap();
-                                } else {
-                                    write!(&mut s, "{}", self.cell_texts[y][x]).unwrap();
-                                }
-                            }
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
-                }
+            // マウスを重ねたときに tooltip を表示する円を追加する
+            pub fn add_circle_with_tooltip(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                x: f64,
+                y: f64,
+                r: f64,
+                tooltip: String,
+            ) -> Self {
+                self.add_circle_group(
+                    stroke_color,
+                    fill_color,
+                    [Circle::new(x, y, r).with_tooltip(tooltip)],
+                )
+            }
 
-                // 線分を書き込み
-                writeln!(&mut s, "LINES").unwrap();
-                writeln!(&mut s, "{}", self.lines.len()).unwrap();
-                for (line, color) in &self.lines {
-                    write!(&mut s, "{} {}", color, line.len()).unwrap();
-                    for p in line {
-                        write!(&mut s, " {} {}", p.0, p.1).unwrap();
-                    }
-                    writeln!(&mut s).unwrap();
-                }
-                // 壁を書き込む
-                if !self.no_wall_horizontal_pos.is_empty() {
-                    writeln!(&mut s, "WALL_HORIZONTAL").unwrap();
-                    for y in 0..=self.h {
-                        for x in 0..self.w {
-                            if self.no_wall_horizontal_pos.contains(&(x, y)) {
-                                write!(&mut s, "N").unwrap();
-                            } else {
-                                write!(&mut s, "Y").unwrap();
-                            }
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
-                }
-                if !self.no_wall_vertical_pos.is_empty() {
-                    writeln!(&mut s, "WALL_VERTICAL").unwrap();
-                    for x in 0..self.h {
-                        for y in 0..=self.w {
-                            if self.no_wall_vertical_pos.contains(&(x, y)) {
-                                write!(&mut s, "N").unwrap();
-                            } else {
-                                write!(&mut s, "Y").unwrap();
-                            }
-                        }
-                        writeln!(&mut s).unwrap();
-                    }
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_styled_circle(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                x: f64,
+                y: f64,
+                r: f64,
+            ) -> Self {
+                self.add_styled_circle_group(
+                    stroke_color,
+                    fill_color,
+                    stroke_width,
+                    style,
+                    [Circle::new(x, y, r)],
+                )
+            }
+
+            pub fn add_line_group(self, color: Color, width: f64, points: Vec<(f64, f64)>) -> Self {
+                // pointsをペアに変換 (2点で一つの線)。つながった線は add_polyline を使う
+                // 奇数個のときは警告して最後の点を捨てる
+                if points.len() % 2 != 0 {
+                    eprintln!(
+                        "warning: Vis2DPlane::add_line_group: points must have an even length, got {}; the last point is ignored",
+                        points.len()
+                    );
                 }
-                s
+                let lines = points.chunks_exact(2).map(|chunk| (chunk[0], chunk[1]));
+                self.add_segments(color, width, LineStyle::Solid, lines)
+            }
+
+            pub fn add_line(
+                self,
+                color: Color,
+                width: f64,
+                ax: f64,
+                ay: f64,
+                bx: f64,
+                by: f64,
+            ) -> Self {
+                self.add_styled_line(color, width, LineStyle::Solid, ax, ay, bx, by)
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_styled_line(
+                self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                ax: f64,
+                ay: f64,
+                bx: f64,
+                by: f64,
+            ) -> Self {
+                self.add_segments(color, width, style, [((ax, ay), (bx, by))])
+            }
+
+            fn add_segments(
+                mut self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                lines: impl IntoIterator<Item = Segment>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (color, width.to_bits(), style);
+                let index = group_index(
+                    &mut layer.line_group_index,
+                    &mut layer.line_groups,
+                    key,
+                    || PlaneLineGroup {
+                        color,
+                        width,
+                        style,
+                        lines: Vec::new(),
+                    },
+                );
+                layer.line_groups[index].lines.extend(lines);
+                self
+            }
+
+            // points を順に結ぶ折れ線を追加する (経路や巡回路の描画用)
+            pub fn add_polyline(
+                mut self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                points: Vec<(f64, f64)>,
+            ) -> Self {
+                self.layer().polylines.push(PlanePolyline {
+                    color,
+                    width,
+                    style,
+                    points,
+                });
+                self
+            }
+
+            // (ax, ay) から (bx, by) への矢印を追加する。head_size は矢じりの大きさ (ピクセル)
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_arrow(
+                self,
+                color: Color,
+                width: f64,
+                head_size: f64,
+                ax: f64,
+                ay: f64,
+                bx: f64,
+                by: f64,
+            ) -> Self {
+                self.add_arrow_group(color, width, head_size, [((ax, ay), (bx, by))])
+            }
+
+            // 同じ色・太さ・矢じりの大きさの矢印を ((ax, ay), (bx, by)) の列でまとめて追加する
+            pub fn add_arrow_group(
+                mut self,
+                color: Color,
+                width: f64,
+                head_size: f64,
+                arrows: impl IntoIterator<Item = ((f64, f64), (f64, f64))>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (color, width.to_bits(), head_size.to_bits());
+                let index = group_index(
+                    &mut layer.arrow_group_index,
+                    &mut layer.arrow_groups,
+                    key,
+                    || PlaneArrowGroup {
+                        color,
+                        width,
+                        head_size,
+                        arrows: Vec::new(),
+                    },
+                );
+                layer.arrow_groups[index].arrows.extend(arrows);
+                self
+            }
+
+            // 中心 (cx, cy)、半径 rx, ry の楕円を追加する。angle は x 軸に対する回転角 (度)
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_ellipse(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                cx: f64,
+                cy: f64,
+                rx: f64,
+                ry: f64,
+                angle: f64,
+            ) -> Self {
+                self.add_ellipse_group(
+                    stroke_color,
+                    fill_color,
+                    stroke_width,
+                    style,
+                    [(cx, cy, rx, ry, angle)],
+                )
+            }
+
+            // (cx, cy, rx, ry, angle) の列でまとめて追加する
+            pub fn add_ellipse_group(
+                mut self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                ellipses: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (stroke_color, fill_color, stroke_width.to_bits(), style);
+                let index = group_index(
+                    &mut layer.ellipse_group_index,
+                    &mut layer.ellipse_groups,
+                    key,
+                    || PlaneEllipseGroup {
+                        stroke_color,
+                        fill_color,
+                        stroke_width,
+                        style,
+                        ellipses: Vec::new(),
+                    },
+                );
+                layer.ellipse_groups[index].ellipses.extend(ellipses);
+                self
+            }
+
+            // 中心 (cx, cy)、半径 r の円弧を角度 start から end (度) まで描く
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_arc(
+                self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                cx: f64,
+                cy: f64,
+                r: f64,
+                start: f64,
+                end: f64,
+            ) -> Self {
+                self.add_arc_group(color, width, style, [(cx, cy, r, start, end)])
+            }
+
+            // (cx, cy, r, start, end) の列でまとめて追加する
+            pub fn add_arc_group(
+                self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                arcs: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                self.add_arcs(color, None, width, style, arcs)
+            }
+
+            // 円弧と中心を結んだ扇形を塗りつぶして描く
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_sector(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                cx: f64,
+                cy: f64,
+                r: f64,
+                start: f64,
+                end: f64,
+            ) -> Self {
+                self.add_sector_group(
+                    stroke_color,
+                    fill_color,
+                    stroke_width,
+                    style,
+                    [(cx, cy, r, start, end)],
+                )
+            }
+
+            pub fn add_sector_group(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                sectors: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                self.add_arcs(stroke_color, Some(fill_color), stroke_width, style, sectors)
+            }
+
+            fn add_arcs(
+                mut self,
+                color: Color,
+                fill: Option<Color>,
+                width: f64,
+                style: LineStyle,
+                arcs: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (color, fill, width.to_bits(), style);
+                let index = group_index(
+                    &mut layer.arc_group_index,
+                    &mut layer.arc_groups,
+                    key,
+                    || PlaneArcGroup {
+                        color,
+                        fill,
+                        width,
+                        style,
+                        arcs: Vec::new(),
+                    },
+                );
+                layer.arc_groups[index].arcs.extend(arcs);
+                self
+            }
+
+            // p0 から p1 への2次ベジェ曲線 (制御点 c)
+            pub fn add_quadratic_bezier(
+                self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                p0: (f64, f64),
+                c: (f64, f64),
+                p1: (f64, f64),
+            ) -> Self {
+                self.add_curve(color, width, style, false, &[p0, c, p1])
+            }
+
+            // p0 から p1 への3次ベジェ曲線 (制御点 c0, c1)
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_cubic_bezier(
+                self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                p0: (f64, f64),
+                c0: (f64, f64),
+                c1: (f64, f64),
+                p1: (f64, f64),
+            ) -> Self {
+                self.add_curve(color, width, style, true, &[p0, c0, c1, p1])
+            }
+
+            fn add_curve(
+                mut self,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                cubic: bool,
+                points: &[(f64, f64)],
+            ) -> Self {
+                let layer = self.layer();
+                let key = (color, width.to_bits(), style, cubic);
+                let index = group_index(
+                    &mut layer.curve_group_index,
+                    &mut layer.curve_groups,
+                    key,
+                    || PlaneCurveGroup {
+                        color,
+                        width,
+                        style,
+                        cubic,
+                        points: Vec::new(),
+                    },
+                );
+                layer.curve_groups[index].points.extend_from_slice(points);
+                self
+            }
+
+            pub fn add_polygon(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                vertices: Vec<(f64, f64)>,
+            ) -> Self {
+                self.add_polygon_with_tooltip(stroke_color, fill_color, vertices, String::new())
+            }
+
+            // マウスを重ねたときに tooltip を表示する多角形を追加する
+            pub fn add_polygon_with_tooltip(
+                mut self,
+                stroke_color: Color,
+                fill_color: Color,
+                vertices: Vec<(f64, f64)>,
+                tooltip: String,
+            ) -> Self {
+                self.layer().polygon_groups.push(
+                    PolygonGroup::new(stroke_color, fill_color, vertices).with_tooltip(tooltip),
+                );
+                self
+            }
+
+            pub fn add_styled_polygon(
+                mut self,
+                stroke_color: Color,
+                fill_color: Color,
+                stroke_width: f64,
+                style: LineStyle,
+                vertices: Vec<(f64, f64)>,
+            ) -> Self {
+                self.layer().polygon_groups.push(
+                    PolygonGroup::new(stroke_color, fill_color, vertices)
+                        .with_stroke(stroke_width, style),
+                );
+                self
+            }
+
+            pub fn add_polygon_groups(
+                mut self,
+                polygons: impl IntoIterator<Item = PolygonGroup>,
+            ) -> Self {
+                self.layer().polygon_groups.extend(polygons);
+                self
+            }
+
+            pub fn add_rect(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                x: f64,
+                y: f64,
+                w: f64,
+                h: f64,
+            ) -> Self {
+                self.add_rect_group(stroke_color, fill_color, [Rect::new(x, y, w, h)])
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_labeled_rect(
+                self,
+                stroke_color: Color,
+                fill_color: Color,
+                x: f64,
+                y: f64,
+                w: f64,
+                h: f64,
+                label: String,
+            ) -> Self {
+                self.add_rect_group(
+                    stroke_color,
+                    fill_color,
+                    [Rect::new(x, y, w, h).with_label(label)],
+                )
+            }
+
+            // Rect や (x, y, w, h) を同じ色でまとめて追加する
+            pub fn add_rect_group<R: Into<Rect>>(
+                mut self,
+                stroke_color: Color,
+                fill_color: Color,
+                rects: impl IntoIterator<Item = R>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (stroke_color, fill_color);
+                let index = group_index(
+                    &mut layer.rect_group_index,
+                    &mut layer.rect_groups,
+                    key,
+                    || PlaneRectGroup {
+                        stroke_color,
+                        fill_color,
+                        rects: Vec::new(),
+                    },
+                );
+                layer.rect_groups[index]
+                    .rects
+                    .extend(rects.into_iter().map(Into::into));
+                self
+            }
+
+            // (x, y) に点を描画する。size は画面上の半径 (ピクセル) で、拡大縮小しても変わらない
+            pub fn add_point(
+                self,
+                shape: MarkerShape,
+                color: Color,
+                size: f64,
+                x: f64,
+                y: f64,
+            ) -> Self {
+                self.add_point_group(shape, color, size, [(x, y)])
+            }
+
+            // 同じ形状・色・大きさの点をまとめて追加する
+            pub fn add_point_group(
+                mut self,
+                shape: MarkerShape,
+                color: Color,
+                size: f64,
+                points: impl IntoIterator<Item = (f64, f64)>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (shape, color, size.to_bits());
+                let index = group_index(
+                    &mut layer.point_group_index,
+                    &mut layer.point_groups,
+                    key,
+                    || PlanePointGroup {
+                        shape,
+                        color,
+                        size,
+                        points: Vec::new(),
+                    },
+                );
+                layer.point_groups[index].points.extend(points);
+                self
+            }
+
+            pub fn add_text(
+                self,
+                color: Color,
+                font_size: f64,
+                x: f64,
+                y: f64,
+                text: String,
+            ) -> Self {
+                self.add_text_group(color, font_size, [TextItem::new(x, y, text)])
+            }
+
+            // TextItem や (x, y, text) を同じ色・フォントサイズでまとめて追加する
+            pub fn add_text_group<T: Into<TextItem>>(
+                self,
+                color: Color,
+                font_size: f64,
+                texts: impl IntoIterator<Item = T>,
+            ) -> Self {
+                self.add_styled_text_group(color, font_size, TextStyle::new(), texts)
+            }
+
+            pub fn add_styled_text(
+                self,
+                color: Color,
+                font_size: f64,
+                style: TextStyle,
+                x: f64,
+                y: f64,
+                text: String,
+            ) -> Self {
+                self.add_styled_text_group(color, font_size, style, [TextItem::new(x, y, text)])
+            }
+
+            // 配置・回転・背景が同じテキストをまとめて追加する
+            pub fn add_styled_text_group<T: Into<TextItem>>(
+                mut self,
+                color: Color,
+                font_size: f64,
+                style: TextStyle,
+                texts: impl IntoIterator<Item = T>,
+            ) -> Self {
+                let layer = self.layer();
+                let key = (
+                    color,
+                    font_size.to_bits(),
+                    style.anchor,
+                    style.rotation.to_bits(),
+                    style.background,
+                    style.padding.to_bits(),
+                    style.offset.0.to_bits(),
+                    style.offset.1.to_bits(),
+                );
+                let index = group_index(
+                    &mut layer.text_group_index,
+                    &mut layer.text_groups,
+                    key,
+                    || PlaneTextGroup {
+                        color,
+                        font_size,
+                        style,
+                        texts: Vec::new(),
+                    },
+                );
+                layer.text_groups[index]
+                    .texts
+                    .extend(texts.into_iter().map(Into::into));
+                self
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+
+                // Output 2D_PLANE header with optional bounds
+                if let Some(bounds) = &self.bounds {
+                    writeln!(
+                        &mut s,
+                        "$v({}) 2D_PLANE({}, {}, {}, {}) {} {}",
+                        mode, bounds.left, bounds.top, bounds.right, bounds.bottom, self.h, self.w
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(&mut s, "$v({}) 2D_PLANE {} {}", mode, self.h, self.w).unwrap();
+                }
+
+                // 座標系が既定と異なるときだけ VIEW を出力する
+                let (min_x, max_x, min_y, max_y) = self.view;
+                if self.view != (0.0, self.w, 0.0, self.h) || self.y_up || self.keep_aspect {
+                    writeln!(&mut s, "VIEW").unwrap();
+                    writeln!(
+                        &mut s,
+                        "{min_x} {max_x} {min_y} {max_y} {} {}",
+                        if self.y_up { "Y_UP" } else { "Y_DOWN" },
+                        if self.keep_aspect {
+                            "KEEP_ASPECT"
+                        } else {
+                            "STRETCH"
+                        }
+                    )
+                    .unwrap();
+                }
+
+                // レイヤが複数あるときは、各レイヤの前に LAYER 行を出力する
+                for (z, layer) in &self.layers {
+                    if self.layers.len() > 1 {
+                        writeln!(&mut s, "LAYER {z}").unwrap();
+                    }
+                    layer.write_sections(&mut s);
+                }
+
+                // Output tooltips (円はグループ番号とグループ内の番号、多角形は番号で指定)
+                // 番号はレイヤをまたいだ出力順で数える
+                let mut tooltips = Vec::new();
+                let circle_groups = self.layers.values().flat_map(|layer| &layer.circle_groups);
+                for (g, group) in circle_groups.enumerate() {
+                    for (i, circle) in group.circles.iter().enumerate() {
+                        if !circle.tooltip.is_empty() {
+                            tooltips.push(format!("C {g} {i} {}", escape_tooltip(&circle.tooltip)));
+                        }
+                    }
+                }
+                let polygons = self.layers.values().flat_map(|layer| &layer.polygon_groups);
+                for (i, polygon) in polygons.enumerate() {
+                    if !polygon.tooltip.is_empty() {
+                        tooltips.push(format!("P {i} {}", escape_tooltip(&polygon.tooltip)));
+                    }
+                }
+                if !tooltips.is_empty() {
+                    writeln!(&mut s, "TOOLTIPS").unwrap();
+                    writeln!(&mut s, "{}", tooltips.len()).unwrap();
+                    for line in &tooltips {
+                        writeln!(&mut s, "{line}").unwrap();
+                    }
+                }
+
+                s
+            }
+        }
+
+        impl PlaneLayer {
+            fn write_sections(&self, s: &mut String) {
+                // Output circles
+                if !self.circle_groups.is_empty() {
+                    writeln!(s, "CIRCLES").unwrap();
+                    writeln!(s, "{}", self.circle_groups.len()).unwrap();
+                    for group in &self.circle_groups {
+                        write!(
+                            s,
+                            "{} {} {}",
+                            group.stroke_color,
+                            group.fill_color,
+                            group.circles.len()
+                        )
+                        .unwrap();
+                        for circle in &group.circles {
+                            write!(s, " {} {} {}", circle.x, circle.y, circle.r).unwrap();
+                        }
+                        write_stroke_suffix(s, group.stroke_width, group.style);
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output ellipses
+                if !self.ellipse_groups.is_empty() {
+                    writeln!(s, "ELLIPSES").unwrap();
+                    writeln!(s, "{}", self.ellipse_groups.len()).unwrap();
+                    for group in &self.ellipse_groups {
+                        write!(
+                            s,
+                            "{} {} {} {} {}",
+                            group.stroke_color,
+                            group.fill_color,
+                            group.stroke_width,
+                            group.style,
+                            group.ellipses.len()
+                        )
+                        .unwrap();
+                        for (cx, cy, rx, ry, angle) in &group.ellipses {
+                            write!(s, " {cx} {cy} {rx} {ry} {angle}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output arcs and sectors
+                if !self.arc_groups.is_empty() {
+                    writeln!(s, "ARCS").unwrap();
+                    writeln!(s, "{}", self.arc_groups.len()).unwrap();
+                    for group in &self.arc_groups {
+                        match group.fill {
+                            Some(fill) => write!(s, "SECTOR {} {fill}", group.color).unwrap(),
+                            None => write!(s, "ARC {}", group.color).unwrap(),
+                        }
+                        write!(s, " {} {} {}", group.width, group.style, group.arcs.len()).unwrap();
+                        for (cx, cy, r, start, end) in &group.arcs {
+                            write!(s, " {cx} {cy} {r} {start} {end}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output lines
+                if !self.line_groups.is_empty() {
+                    writeln!(s, "LINES").unwrap();
+                    writeln!(s, "{}", self.line_groups.len()).unwrap();
+                    for group in &self.line_groups {
+                        write!(s, "{} {} {}", group.color, group.width, group.lines.len()).unwrap();
+                        for ((x1, y1), (x2, y2)) in &group.lines {
+                            write!(s, " {x1} {y1} {x2} {y2}").unwrap();
+                        }
+                        // 実線以外のときだけ末尾に線種を付ける
+                        if group.style != LineStyle::Solid {
+                            write!(s, " {}", group.style).unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output polylines
+                if !self.polylines.is_empty() {
+                    writeln!(s, "POLYLINES").unwrap();
+                    writeln!(s, "{}", self.polylines.len()).unwrap();
+                    for line in &self.polylines {
+                        write!(
+                            s,
+                            "{} {} {} {}",
+                            line.color,
+                            line.width,
+                            line.style,
+                            line.points.len()
+                        )
+                        .unwrap();
+                        for (x, y) in &line.points {
+                            write!(s, " {x} {y}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output Bezier curves
+                if !self.curve_groups.is_empty() {
+                    writeln!(s, "CURVES").unwrap();
+                    writeln!(s, "{}", self.curve_groups.len()).unwrap();
+                    for group in &self.curve_groups {
+                        let (kind, n) = if group.cubic { ("C", 4) } else { ("Q", 3) };
+                        write!(
+                            s,
+                            "{kind} {} {} {} {}",
+                            group.color,
+                            group.width,
+                            group.style,
+                            group.points.len() / n
+                        )
+                        .unwrap();
+                        for (x, y) in &group.points {
+                            write!(s, " {x} {y}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output arrows
+                if !self.arrow_groups.is_empty() {
+                    writeln!(s, "ARROWS").unwrap();
+                    writeln!(s, "{}", self.arrow_groups.len()).unwrap();
+                    for group in &self.arrow_groups {
+                        write!(
+                            s,
+                            "{} {} {} {}",
+                            group.color,
+                            group.width,
+                            group.head_size,
+                            group.arrows.len()
+                        )
+                        .unwrap();
+                        for ((ax, ay), (bx, by)) in &group.arrows {
+                            write!(s, " {ax} {ay} {bx} {by}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output polygons
+                if !self.polygon_groups.is_empty() {
+                    writeln!(s, "POLYGONS").unwrap();
+                    writeln!(s, "{}", self.polygon_groups.len()).unwrap();
+                    for group in &self.polygon_groups {
+                        write!(
+                            s,
+                            "{} {} {}",
+                            group.stroke_color,
+                            group.fill_color,
+                            group.vertices.len()
+                        )
+                        .unwrap();
+                        for (x, y) in &group.vertices {
+                            write!(s, " {x} {y}").unwrap();
+                        }
+                        write_stroke_suffix(s, group.stroke_width, group.style);
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output rects
+                if !self.rect_groups.is_empty() {
+                    writeln!(s, "RECTS").unwrap();
+                    writeln!(s, "{}", self.rect_groups.len()).unwrap();
+                    for group in &self.rect_groups {
+                        // ラベルを持つ長方形が無いグループはラベル欄ごと省略する
+                        let labeled = group.rects.iter().any(|rect| !rect.label.is_empty());
+                        write!(
+                            s,
+                            "{} {} {} {}",
+                            group.stroke_color,
+                            group.fill_color,
+                            group.rects.len(),
+                            if labeled { 'L' } else { 'N' }
+                        )
+                        .unwrap();
+                        for rect in &group.rects {
+                            write!(s, " {} {} {} {}", rect.x, rect.y, rect.w, rect.h).unwrap();
+                            if !labeled {
+                                continue;
+                            }
+                            if rect.label.contains(' ') || rect.label.is_empty() {
+                                write!(s, " \"{}\"", rect.label).unwrap();
+                            } else {
+                                write!(s, " {}", rect.label).unwrap();
+                            }
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output points
+                if !self.point_groups.is_empty() {
+                    writeln!(s, "POINTS").unwrap();
+                    writeln!(s, "{}", self.point_groups.len()).unwrap();
+                    for group in &self.point_groups {
+                        write!(
+                            s,
+                            "{} {} {} {}",
+                            group.shape,
+                            group.color,
+                            group.size,
+                            group.points.len()
+                        )
+                        .unwrap();
+                        for (x, y) in &group.points {
+                            write!(s, " {x} {y}").unwrap();
+                        }
+                        writeln!(s).unwrap();
+                    }
+                }
+
+                // Output text
+                if !self.text_groups.is_empty() {
+                    writeln!(s, "TEXT").unwrap();
+                    writeln!(s, "{}", self.text_groups.len()).unwrap();
+                    for group in &self.text_groups {
+                        write!(
+                            s,
+                            "{} {} {}",
+                            group.color,
+                            group.font_size,
+                            group.texts.len()
+                        )
+                        .unwrap();
+                        for item in &group.texts {
+                            if item.text.contains(' ') || item.text.is_empty() {
+                                write!(s, " {} {} \"{}\"", item.x, item.y, item.text).unwrap();
+                            } else {
+                                write!(s, " {} {} {}", item.x, item.y, item.text).unwrap();
+                            }
+                        }
+                        write_text_style_suffix(s, &group.style);
+                        writeln!(s).unwrap();
+                    }
+                }
+            }
+        }
+
+        // テキストが既定 (中央・回転なし・背景なし) 以外のときだけ、行末に配置・回転・背景を付ける
+        fn write_text_style_suffix(s: &mut String, style: &TextStyle) {
+            if *style != TextStyle::default() {
+                let background = style.background.map_or("-".to_string(), |c| c.to_string());
+                write!(
+                    s,
+                    " {} {} {} {} {} {}",
+                    style.anchor,
+                    style.rotation,
+                    background,
+                    style.padding,
+                    style.offset.0,
+                    style.offset.1
+                )
+                .unwrap();
+            }
+        }
+
+        // 円・多角形の枠線が既定 (太さ2の実線) と異なるときだけ、行末に太さと線種を付ける
+        fn write_stroke_suffix(s: &mut String, width: f64, style: LineStyle) {
+            if width != DEFAULT_STROKE_WIDTH || style != LineStyle::Solid {
+                write!(s, " {width} {style}").unwrap();
+            }
+        }
+
+        // tooltip を1行に収めるため、改行を \n、バックスラッシュを \\ に置き換える
+        fn escape_tooltip(text: &str) -> String {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                match c {
+                    '\\' => escaped.push_str("\\\\"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => {}
+                    _ => escaped.push(c),
+                }
+            }
+            escaped
+        }
+
+        pub struct VisGrid {
+            h: usize,
+            w: usize,
+            conf: VisGridConf,
+            // 既定色と異なるセルの色・空でないテキストだけを (x, y) をキーに保持する
+            // テキストは同じ文字列を共有するよう text_pool の添字で持つ
+            cell_colors: FxHashMap<(usize, usize), Color>,
+            cell_texts: FxHashMap<(usize, usize), u32>,
+            text_pool: TextPool,
+            cell_text_styles: FxHashMap<(usize, usize), (Color, f64)>,
+            cell_text_groups: FxHashMap<(Color, u64, TextAnchor), (f64, Vec<CellText>)>,
+            tooltips: FxHashMap<(usize, usize), String>,
+            no_walls: FxHashSet<WallPos>,
+            wall_styles: FxHashMap<WallPos, (Color, f64)>,
+            lines: Vec<(Vec<(usize, usize)>, Color)>,
+            polylines: Vec<GridPolyline>,
+            arrow_groups: FxHashMap<(Color, u64, LineStyle), (f64, Vec<GridArrow>)>,
+            marker_group_index: FxHashMap<(MarkerShape, Color, Color, u64), usize>,
+            marker_groups: Vec<MarkerGroup>,
+            clip_out_of_range: bool,
+            // 軸ラベル: axis_label_step ごとの番号、または行・列ごとに指定した文字列
+            axis_label_step: usize,
+            row_labels: Vec<String>,
+            col_labels: Vec<String>,
+            // 表示する範囲 (行の範囲, 列の範囲)。None なら盤面全体
+            viewport: Option<(Range<usize>, Range<usize>)>,
+            bounds: Option<ItemBounds>,
+        }
+
+        struct GridPolyline {
+            points: Vec<(usize, usize)>,
+            color: Color,
+            width: f64,
+            style: LineStyle,
+        }
+
+        struct GridArrow {
+            from: (usize, usize),
+            to: (usize, usize),
+            label: String,
+        }
+
+        // 追加順に描画されるよう、グループは Vec で保持する
+        struct MarkerGroup {
+            shape: MarkerShape,
+            stroke_color: Color,
+            fill_color: Color,
+            size: f64,
+            positions: Vec<(usize, usize)>,
+        }
+
+        // 同じ文字列を1つにまとめて保持する (数字や記号など、同じテキストのセルが多い盤面向け)
+        #[derive(Default)]
+        struct TextPool {
+            texts: Vec<Rc<str>>,
+            index: FxHashMap<Rc<str>, u32>,
+        }
+
+        impl TextPool {
+            fn intern(&mut self, text: &str) -> u32 {
+                if let Some(&id) = self.index.get(text) {
+                    return id;
+                }
+                let id = self.texts.len() as u32;
+                let text: Rc<str> = Rc::from(text);
+                self.texts.push(Rc::clone(&text));
+                self.index.insert(text, id);
+                id
+            }
+
+            fn get(&self, id: u32) -> &str {
+                &self.texts[id as usize]
+            }
+        }
+
+        type StyledCellTexts<'a> =
+            FxHashMap<(Color, u64, TextAnchor), (f64, Vec<(usize, usize, &'a str)>)>;
+
+        struct CellText {
+            x: usize,
+            y: usize,
+            text: String,
+        }
+
+        impl VisGrid {
+            pub fn new(h: usize, w: usize, bounds: Option<ItemBounds>) -> Self {
+                Self {
+                    h,
+                    w,
+                    conf: Default::default(),
+                    cell_colors: FxHashMap::default(),
+                    cell_texts: FxHashMap::default(),
+                    text_pool: TextPool::default(),
+                    cell_text_styles: FxHashMap::default(),
+                    cell_text_groups: FxHashMap::default(),
+                    tooltips: FxHashMap::default(),
+                    no_walls: FxHashSet::default(),
+                    wall_styles: FxHashMap::default(),
+                    lines: Vec::new(),
+                    polylines: Vec::new(),
+                    arrow_groups: FxHashMap::default(),
+                    marker_group_index: FxHashMap::default(),
+                    marker_groups: Vec::new(),
+                    clip_out_of_range: false,
+                    axis_label_step: 0,
+                    row_labels: Vec::new(),
+                    col_labels: Vec::new(),
+                    viewport: None,
+                    bounds,
+                }
+            }
+
+            pub fn set_bounds(mut self, bounds: ItemBounds) -> Self {
+                self.bounds = Some(bounds);
+                self
+            }
+
+            // true にすると、範囲外の位置への操作を panic させずに警告を出して無視する
+            pub fn clip_out_of_range(mut self, enabled: bool) -> Self {
+                self.clip_out_of_range = enabled;
+                self
+            }
+
+            fn check_pos(&self, p: GridPos) -> Result<(), GridPosError> {
+                if p.row < self.h && p.col < self.w {
+                    Ok(())
+                } else {
+                    Err(GridPosError {
+                        pos: p,
+                        h: self.h,
+                        w: self.w,
+                    })
+                }
+            }
+
+            // 範囲外なら、clip モードでは警告して false を返し、そうでなければ panic する
+            fn accept_pos(&self, p: GridPos, method: &str) -> bool {
+                match self.check_pos(p) {
+                    Ok(()) => true,
+                    Err(e) if self.clip_out_of_range => {
+                        eprintln!("warning: VisGrid::{method}: {e}");
+                        false
+                    }
+                    Err(e) => panic!("VisGrid::{method}: {e}"),
+                }
+            }
+
+            pub fn update_cell_color(mut self, p: impl Into<GridPos>, color: Color) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "update_cell_color") {
+                    if color == self.conf.default_cell_color {
+                        self.cell_colors.remove(&(p.col, p.row));
+                    } else {
+                        self.cell_colors.insert((p.col, p.row), color);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_cell_color(
+                self,
+                p: impl Into<GridPos>,
+                color: Color,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_cell_color(p, color))
+            }
+
+            pub fn update_text(mut self, p: impl Into<GridPos>, text: String) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "update_text") {
+                    if text.is_empty() {
+                        self.cell_texts.remove(&(p.col, p.row));
+                    } else {
+                        let id = self.text_pool.intern(&text);
+                        self.cell_texts.insert((p.col, p.row), id);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_text(
+                self,
+                p: impl Into<GridPos>,
+                text: String,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_text(p, text))
+            }
+
+            // update_text で設定したテキストの色とフォントサイズをセルごとに上書きする
+            pub fn update_text_style(
+                mut self,
+                p: impl Into<GridPos>,
+                color: Color,
+                font_size: f64,
+            ) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "update_text_style") {
+                    self.cell_text_styles
+                        .insert((p.col, p.row), (color, font_size));
+                }
+                self
+            }
+
+            pub fn try_update_text_style(
+                self,
+                p: impl Into<GridPos>,
+                color: Color,
+                font_size: f64,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_text_style(p, color, font_size))
+            }
+
+            // セル内の指定位置にテキストを追加する (同じセルに複数追加可能)
+            pub fn add_cell_text(
+                mut self,
+                p: impl Into<GridPos>,
+                anchor: TextAnchor,
+                color: Color,
+                font_size: f64,
+                text: String,
+            ) -> Self {
+                let p = p.into();
+                if !self.accept_pos(p, "add_cell_text") {
+                    return self;
+                }
+                let font_size_key = font_size.to_bits();
+                self.cell_text_groups
+                    .entry((color, font_size_key, anchor))
+                    .or_insert_with(|| (font_size, Vec::new()))
+                    .1
+                    .push(CellText {
+                        x: p.col,
+                        y: p.row,
+                        text,
+                    });
+                self
+            }
+
+            pub fn try_add_cell_text(
+                self,
+                p: impl Into<GridPos>,
+                anchor: TextAnchor,
+                color: Color,
+                font_size: f64,
+                text: String,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.add_cell_text(p, anchor, color, font_size, text))
+            }
+
+            // マウスを重ねたときに表示する文字列を設定する (改行を含んでもよい)
+            pub fn set_tooltip(mut self, p: impl Into<GridPos>, tooltip: String) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "set_tooltip") {
+                    if tooltip.is_empty() {
+                        self.tooltips.remove(&(p.col, p.row));
+                    } else {
+                        self.tooltips.insert((p.col, p.row), tooltip);
+                    }
+                }
+                self
+            }
+
+            // 範囲内の点だけを (x, y) の列にする。clip モードでは範囲外の点を飛ばす
+            fn accept_path<P: Into<GridPos>>(
+                &self,
+                points: Vec<P>,
+                method: &str,
+            ) -> Vec<(usize, usize)> {
+                points
+                    .into_iter()
+                    .map(Into::into)
+                    .filter(|&p| self.accept_pos(p, method))
+                    .map(|p| (p.col, p.row))
+                    .collect()
+            }
+
+            pub fn add_line<P: Into<GridPos>>(mut self, line: Vec<P>, color: Color) -> Self {
+                let line = self.accept_path(line, "add_line");
+                self.lines.push((line, color));
+                self
+            }
+
+            pub fn try_add_line<P: Into<GridPos>>(
+                self,
+                line: Vec<P>,
+                color: Color,
+            ) -> Result<Self, GridPosError> {
+                let line: Vec<GridPos> = line.into_iter().map(Into::into).collect();
+                for &p in &line {
+                    self.check_pos(p)?;
+                }
+                Ok(self.add_line(line, color))
+            }
+
+            // 太さと線種を指定してセルの中心を結ぶ折れ線を描画する
+            pub fn add_polyline<P: Into<GridPos>>(
+                mut self,
+                points: Vec<P>,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+            ) -> Self {
+                let points = self.accept_path(points, "add_polyline");
+                self.polylines.push(GridPolyline {
+                    points,
+                    color,
+                    width,
+                    style,
+                });
+                self
+            }
+
+            // start から移動文字列 (例: "RRDDL") に従って進む経路を描画する。盤面の端を越える移動は端で止める
+            pub fn add_move_path(
+                self,
+                start: impl Into<GridPos>,
+                moves: &str,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+            ) -> Self {
+                let start = start.into();
+                if !self.accept_pos(start, "add_move_path") {
+                    return self;
+                }
+                let points =
+                    walk_moves((start.col, start.row), moves, &MOVES_UDLR, (self.w, self.h));
+                self.add_polyline(points, color, width, style)
+            }
+
+            pub fn add_arrow(
+                self,
+                from: impl Into<GridPos>,
+                to: impl Into<GridPos>,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+            ) -> Self {
+                self.add_labeled_arrow(from, to, color, width, style, String::new())
+            }
+
+            // 始点か終点が範囲外なら、clip モードでは矢印ごと飛ばす
+            pub fn add_labeled_arrow(
+                mut self,
+                from: impl Into<GridPos>,
+                to: impl Into<GridPos>,
+                color: Color,
+                width: f64,
+                style: LineStyle,
+                label: String,
+            ) -> Self {
+                let (from, to) = (from.into(), to.into());
+                if !self.accept_pos(from, "add_labeled_arrow")
+                    || !self.accept_pos(to, "add_labeled_arrow")
+                {
+                    return self;
+                }
+                self.arrow_groups
+                    .entry((color, width.to_bits(), style))
+                    .or_insert_with(|| (width, Vec::new()))
+                    .1
+                    .push(GridArrow {
+                        from: (from.col, from.row),
+                        to: (to.col, to.row),
+                        label,
+                    });
+                self
+            }
+
+            // セル内にマーカーを描画する。size はセルの大きさに対する比率 (1.0 でセルいっぱい)
+            pub fn add_marker(
+                mut self,
+                p: impl Into<GridPos>,
+                shape: MarkerShape,
+                stroke_color: Color,
+                fill_color: Color,
+                size: f64,
+            ) -> Self {
+                let p = p.into();
+                if !self.accept_pos(p, "add_marker") {
+                    return self;
+                }
+                let key = (shape, stroke_color, fill_color, size.to_bits());
+                let index = *self.marker_group_index.entry(key).or_insert_with(|| {
+                    self.marker_groups.push(MarkerGroup {
+                        shape,
+                        stroke_color,
+                        fill_color,
+                        size,
+                        positions: Vec::new(),
+                    });
+                    self.marker_groups.len() - 1
+                });
+                self.marker_groups[index].positions.push((p.col, p.row));
+                self
+            }
+
+            pub fn try_add_marker(
+                self,
+                p: impl Into<GridPos>,
+                shape: MarkerShape,
+                stroke_color: Color,
+                fill_color: Color,
+                size: f64,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.add_marker(p, shape, stroke_color, fill_color, size))
+            }
+
+            pub fn add_markers<P: Into<GridPos>>(
+                mut self,
+                positions: Vec<P>,
+                shape: MarkerShape,
+                stroke_color: Color,
+                fill_color: Color,
+                size: f64,
+            ) -> Self {
+                for p in positions {
+                    self = self.add_marker(p, shape, stroke_color, fill_color, size);
+                }
+                self
+            }
+
+            pub fn remove_wall(mut self, wall: WallPos) -> Self {
+                self.no_walls.insert(wall);
+                self
+            }
+
+            pub fn add_wall(mut self, wall: WallPos) -> Self {
+                self.no_walls.remove(&wall);
+                self
+            }
+
+            // 壁ごとに色と太さを指定する (壁が無い位置の指定は無視される)
+            pub fn set_wall_style(mut self, wall: WallPos, color: Color, width: f64) -> Self {
+                self.wall_styles.insert(wall, (color, width));
+                self
+            }
+
+            // 以下の4つは互換性のためにタプルの順序が縦と横で異なる。
+            // 新しいコードでは remove_wall(WallPos::left_of(p)) / remove_wall(WallPos::above(p)) を使う
+            // p = (row, col): row 行目のセルの、col 列目の左側の壁
+            pub fn remove_wall_vertical(self, p: (usize, usize)) -> Self {
+                self.remove_wall(WallPos::Vertical { row: p.0, col: p.1 })
+            }
+
+            // p = (row, col): remove_wall_vertical と同じく (row, col) の順
+            pub fn add_wall_vertical(self, p: (usize, usize)) -> Self {
+                self.add_wall(WallPos::Vertical { row: p.0, col: p.1 })
+            }
+
+            // p = (x, y): y 行目の上側の、x 列目のセルにかかる壁
+            pub fn remove_wall_horizontal(self, p: (usize, usize)) -> Self {
+                self.remove_wall(WallPos::Horizontal { row: p.1, col: p.0 })
+            }
+
+            // p = (x, y): remove_wall_horizontal と同じく (x, y) の順
+            pub fn add_wall_horizontal(self, p: (usize, usize)) -> Self {
+                self.add_wall(WallPos::Horizontal { row: p.1, col: p.0 })
+            }
+
+            // セル間の縦向きの壁をまとめて設定する。walls[r][c] が true なら
+            // (r, c) と (r, c + 1) の間に壁がある (H 行 x (W - 1) 列)
+            pub fn set_vertical_walls(mut self, walls: &[Vec<bool>]) -> Self {
+                for (row, line) in walls.iter().enumerate() {
+                    for (c, &exists) in line.iter().enumerate() {
+                        let wall = WallPos::Vertical { row, col: c + 1 };
+                        if exists {
+                            self.no_walls.remove(&wall);
+                        } else {
+                            self.no_walls.insert(wall);
+                        }
+                    }
+                }
+                self
+            }
+
+            // セル間の横向きの壁をまとめて設定する。walls[r][c] が true なら
+            // (r, c) と (r + 1, c) の間に壁がある ((H - 1) 行 x W 列)
+            pub fn set_horizontal_walls(mut self, walls: &[Vec<bool>]) -> Self {
+                for (r, line) in walls.iter().enumerate() {
+                    for (col, &exists) in line.iter().enumerate() {
+                        let wall = WallPos::Horizontal { row: r + 1, col };
+                        if exists {
+                            self.no_walls.remove(&wall);
+                        } else {
+                            self.no_walls.insert(wall);
+                        }
+                    }
+                }
+                self
+            }
+
+            // AHC の入力形式の '0'/'1' 文字列から縦向きの壁を設定する ('1' が壁あり)
+            pub fn set_vertical_walls_from_strs<S: AsRef<str>>(self, walls: &[S]) -> Self {
+                self.set_vertical_walls(&walls_from_strs(walls))
+            }
+
+            // AHC の入力形式の '0'/'1' 文字列から横向きの壁を設定する ('1' が壁あり)
+            pub fn set_horizontal_walls_from_strs<S: AsRef<str>>(self, walls: &[S]) -> Self {
+                self.set_horizontal_walls(&walls_from_strs(walls))
+            }
+
+            // every 行・列ごとに番号の軸ラベルを表示する (0 で非表示)
+            pub fn show_axis_labels(mut self, every: usize) -> Self {
+                self.axis_label_step = every;
+                self
+            }
+
+            // 行ごとの軸ラベルを指定する (空文字列の行は表示しない)
+            pub fn set_row_labels(mut self, labels: Vec<String>) -> Self {
+                self.row_labels = labels;
+                self
+            }
+
+            // 列ごとの軸ラベルを指定する (空文字列の列は表示しない)
+            pub fn set_col_labels(mut self, labels: Vec<String>) -> Self {
+                self.col_labels = labels;
+                self
+            }
+
+            // 元の番号 i の行 (列) のラベル。指定された文字列があればそれを、なければ番号を使う
+            fn axis_label(&self, i: usize, custom: &[String]) -> String {
+                if !custom.is_empty() {
+                    custom.get(i).cloned().unwrap_or_default()
+                } else if self.axis_label_step > 0 && i % self.axis_label_step == 0 {
+                    i.to_string()
+                } else {
+                    String::new()
+                }
+            }
+
+            // rows 行目から cols 列目までの範囲だけを出力する (座標の表示は元の番号のまま)
+            // 範囲が空になるときは警告して、盤面内の 1 行 (1 列) 以上に広げる
+            pub fn set_viewport(mut self, rows: Range<usize>, cols: Range<usize>) -> Self {
+                let fit = |range: Range<usize>, size: usize| {
+                    let start = range.start.min(size.saturating_sub(1));
+                    let end = range.end.min(size).max(start + 1);
+                    if range.start >= range.end || range.start >= size {
+                        eprintln!(
+                            "warning: VisGrid::set_viewport: empty range {range:?} for size {size}; using {start}..{end}"
+                        );
+                    }
+                    start..end
+                };
+                let rows = fit(rows, self.h);
+                let cols = fit(cols, self.w);
+                self.viewport = Some((rows, cols));
+                self
+            }
+
+            // center が中央付近に来るように、rows 行 x cols 列の範囲を表示する
+            // 盤面の端では範囲が盤面からはみ出さないようにずらす
+            pub fn follow(self, center: impl Into<GridPos>, rows: usize, cols: usize) -> Self {
+                let center = center.into();
+                let start = |c: usize, len: usize, size: usize| {
+                    c.saturating_sub(len / 2).min(size.saturating_sub(len))
+                };
+                let r0 = start(center.row, rows, self.h);
+                let c0 = start(center.col, cols, self.w);
+                self.set_viewport(r0..r0 + rows, c0..c0 + cols)
+            }
+
+            // viewport の範囲だけを切り出し、座標を範囲の左上基準に変換したグリッドを作る
+            fn crop(&self, rows: &Range<usize>, cols: &Range<usize>) -> VisGrid {
+                let (r0, c0) = (rows.start, cols.start);
+                let inside = |&(x, y): &(usize, usize)| rows.contains(&y) && cols.contains(&x);
+                let local = |(x, y): (usize, usize)| (x - c0, y - r0);
+                // 範囲内の点が連続する部分ごとに分割する
+                let split_path = |points: &[(usize, usize)]| -> Vec<Vec<(usize, usize)>> {
+                    points
+                        .split(|p| !inside(p))
+                        .filter(|run| run.len() >= 2)
+                        .map(|run| run.iter().map(|&p| local(p)).collect())
+                        .collect()
+                };
+
+                let mut grid = VisGrid::new(rows.len(), cols.len(), None);
+                grid.conf = VisGridConf::new(
+                    self.conf.border_color,
+                    self.conf.text_color,
+                    self.conf.default_cell_color,
+                );
+                grid.bounds = self
+                    .bounds
+                    .as_ref()
+                    .map(|b| ItemBounds::new(b.left, b.top, b.right, b.bottom));
+                grid.cell_colors = self
+                    .cell_colors
+                    .iter()
+                    .filter(|(p, _)| inside(p))
+                    .map(|(&p, &color)| (local(p), color))
+                    .collect();
+                for (&p, &id) in &self.cell_texts {
+                    if inside(&p) {
+                        let id = grid.text_pool.intern(self.text_pool.get(id));
+                        grid.cell_texts.insert(local(p), id);
+                    }
+                }
+                grid.tooltips = self
+                    .tooltips
+                    .iter()
+                    .filter(|(p, _)| inside(p))
+                    .map(|(&p, tooltip)| (local(p), tooltip.clone()))
+                    .collect();
+                grid.cell_text_styles = self
+                    .cell_text_styles
+                    .iter()
+                    .filter(|(p, _)| inside(p))
+                    .map(|(&p, &style)| (local(p), style))
+                    .collect();
+                for (&key, (font_size, texts)) in &self.cell_text_groups {
+                    let texts: Vec<CellText> = texts
+                        .iter()
+                        .filter(|t| inside(&(t.x, t.y)))
+                        .map(|t| CellText {
+                            x: t.x - c0,
+                            y: t.y - r0,
+                            text: t.text.clone(),
+                        })
+                        .collect();
+                    if !texts.is_empty() {
+                        grid.cell_text_groups.insert(key, (*font_size, texts));
+                    }
+                }
+                for group in &self.marker_groups {
+                    let positions: Vec<(usize, usize)> = group
+                        .positions
+                        .iter()
+                        .filter(|p| inside(p))
+                        .map(|&p| local(p))
+                        .collect();
+                    if !positions.is_empty() {
+                        grid.marker_groups.push(MarkerGroup {
+                            shape: group.shape,
+                            stroke_color: group.stroke_color,
+                            fill_color: group.fill_color,
+                            size: group.size,
+                            positions,
+                        });
+                    }
+                }
+                for (points, color) in &self.lines {
+                    for run in split_path(points) {
+                        grid.lines.push((run, *color));
+                    }
+                }
+                for polyline in &self.polylines {
+                    for run in split_path(&polyline.points) {
+                        grid.polylines.push(GridPolyline {
+                            points: run,
+                            color: polyline.color,
+                            width: polyline.width,
+                            style: polyline.style,
+                        });
+                    }
+                }
+                for (&key, (width, arrows)) in &self.arrow_groups {
+                    let arrows: Vec<GridArrow> = arrows
+                        .iter()
+                        .filter(|a| inside(&a.from) && inside(&a.to))
+                        .map(|a| GridArrow {
+                            from: local(a.from),
+                            to: local(a.to),
+                            label: a.label.clone(),
+                        })
+                        .collect();
+                    if !arrows.is_empty() {
+                        grid.arrow_groups.insert(key, (*width, arrows));
+                    }
+                }
+                // 壁は範囲の外周も含めて切り出す
+                let local_wall = |wall: WallPos| match wall {
+                    WallPos::Horizontal { row, col }
+                        if (r0..=rows.end).contains(&row) && cols.contains(&col) =>
+                    {
+                        Some(WallPos::Horizontal {
+                            row: row - r0,
+                            col: col - c0,
+                        })
+                    }
+                    WallPos::Vertical { row, col }
+                        if rows.contains(&row) && (c0..=cols.end).contains(&col) =>
+                    {
+                        Some(WallPos::Vertical {
+                            row: row - r0,
+                            col: col - c0,
+                        })
+                    }
+                    _ => None,
+                };
+                grid.no_walls = self
+                    .no_walls
+                    .iter()
+                    .filter_map(|&w| local_wall(w))
+                    .collect();
+                grid.wall_styles = self
+                    .wall_styles
+                    .iter()
+                    .filter_map(|(&w, &style)| local_wall(w).map(|w| (w, style)))
+                    .collect();
+                // 軸ラベルは元の番号で作ってから切り出す (表示しない設定なら出力しない)
+                if self.axis_label_step > 0
+                    || !self.row_labels.is_empty()
+                    || !self.col_labels.is_empty()
+                {
+                    grid.row_labels = rows
+                        .clone()
+                        .map(|r| self.axis_label(r, &self.row_labels))
+                        .collect();
+                    grid.col_labels = cols
+                        .clone()
+                        .map(|c| self.axis_label(c, &self.col_labels))
+                        .collect();
+                }
+                grid
+            }
+
+            pub fn to_vis_string(&self, mode_name: &str) -> String {
+                if let Some((rows, cols)) = &self.viewport {
+                    let mut s = self.crop(rows, cols).to_vis_string(mode_name);
+                    // 切り出した範囲の左上の元の座標
+                    writeln!(&mut s, "OFFSET").unwrap();
+                    writeln!(&mut s, "{} {}", rows.start, cols.start).unwrap();
+                    return s;
+                }
+
+                let mut s = String::new();
+
+                // G H W BORDER TEXT を書き込み (with optional bounds)
+                if let Some(bounds) = &self.bounds {
+                    writeln!(
+                        &mut s,
+                        "$v({}) GRID({}, {}, {}, {}) {} {} {} {} {}",
+                        mode_name,
+                        bounds.left,
+                        bounds.top,
+                        bounds.right,
+                        bounds.bottom,
+                        self.h,
+                        self.w,
+                        self.conf.border_color,
+                        self.conf.text_color,
+                        self.conf.default_cell_color
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(
+                        &mut s,
+                        "$v({}) GRID {} {} {} {} {}",
+                        mode_name,
+                        self.h,
+                        self.w,
+                        self.conf.border_color,
+                        self.conf.text_color,
+                        self.conf.default_cell_color
+                    )
+                    .unwrap();
+                }
+
+                // 各セルの色を書き込み
+                // 部分指定 (CELL_COLORS_POS) と全指定 (CELL_COLORS) のうち短い方を使う
+                let mut color_to_pos: FxHashMap<Color, Vec<(usize, usize)>> = FxHashMap::default();
+                for (&(x, y), &color) in &self.cell_colors {
+                    if y >= self.h || x >= self.w {
+                        continue;
+                    }
+                    color_to_pos.entry(color).or_default().push((x, y));
+                }
+                let mut sparse = String::new();
+                writeln!(&mut sparse, "CELL_COLORS_POS").unwrap();
+                writeln!(&mut sparse, "{}", color_to_pos.len()).unwrap();
+                for (color, positions) in &mut color_to_pos {
+                    positions.sort_unstable_by_key(|&(x, y)| (y, x));
+                    write!(&mut sparse, "{} {}", color, positions.len()).unwrap();
+                    for &(x, y) in positions.iter() {
+                        write!(&mut sparse, " {x} {y}").unwrap();
+                    }
+                    writeln!(&mut sparse).unwrap();
+                }
+                // 全指定は 1 セルあたり "#RRGGBB" と区切り文字の 8 文字
+                let dense_len = "CELL_COLORS\n".len() + self.h * self.w * 8;
+                if sparse.len() <= dense_len {
+                    s.push_str(&sparse);
+                } else {
+                    writeln!(&mut s, "CELL_COLORS").unwrap();
+                    for y in 0..self.h {
+                        for x in 0..self.w {
+                            if x > 0 {
+                                write!(&mut s, " ").unwrap();
+                            }
+                            let color = self
+                                .cell_colors
+                                .get(&(x, y))
+                                .unwrap_or(&self.conf.default_cell_color);
+                            write!(&mut s, "{color}").unwrap();
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+                // 各セルのテキストを書き込み（すべて空の場合は省略）
+                // スタイル指定のあるセルは CELL_TEXT_EX 側で出力する
+                let plain_text = |x: usize, y: usize| -> &str {
+                    if self.cell_text_styles.contains_key(&(x, y)) {
+                        ""
+                    } else {
+                        self.cell_texts
+                            .get(&(x, y))
+                            .map_or("", |&id| self.text_pool.get(id))
+                    }
+                };
+                // 各行の最後の空でないセルの列
+                let mut last_non_empty = vec![None; self.h];
+                for &(x, y) in self.cell_texts.keys() {
+                    if y >= self.h || x >= self.w || plain_text(x, y).is_empty() {
+                        continue;
+                    }
+                    let last: &mut Option<usize> = &mut last_non_empty[y];
+                    *last = Some(last.map_or(x, |l| l.max(x)));
+                }
+                if last_non_empty.iter().any(|l| l.is_some()) {
+                    writeln!(&mut s, "CELL_TEXT").unwrap();
+                    for (y, &last_non_empty) in last_non_empty.iter().enumerate() {
+                        // その行の末尾側の空セルは省略し、途中の空セルは "" で出力
+                        if let Some(last) = last_non_empty {
+                            for x in 0..=last {
+                                if x > 0 {
+                                    write!(&mut s, " ").unwrap();
+                                }
+                                let text = plain_text(x, y);
+                                if text.is_empty() {
+                                    write!(&mut s, "\"\"").unwrap();
+                                } else {
+                                    write!(&mut s, "{text}").unwrap();
+                                }
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // スタイル付きのテキストを書き込み
+                let mut styled_groups: StyledCellTexts = FxHashMap::default();
+                for (&(x, y), &(color, font_size)) in &self.cell_text_styles {
+                    if y >= self.h || x >= self.w {
+                        continue;
+                    }
+                    let Some(&id) = self.cell_texts.get(&(x, y)) else {
+                        continue;
+                    };
+                    let text = self.text_pool.get(id);
+                    styled_groups
+                        .entry((color, font_size.to_bits(), TextAnchor::Center))
+                        .or_insert_with(|| (font_size, Vec::new()))
+                        .1
+                        .push((x, y, text));
+                }
+                for (&key, (font_size, texts)) in &self.cell_text_groups {
+                    styled_groups
+                        .entry(key)
+                        .or_insert_with(|| (*font_size, Vec::new()))
+                        .1
+                        .extend(texts.iter().map(|t| (t.x, t.y, t.text.as_str())));
+                }
+                if !styled_groups.is_empty() {
+                    writeln!(&mut s, "CELL_TEXT_EX").unwrap();
+                    writeln!(&mut s, "{}", styled_groups.len()).unwrap();
+                    for ((color, _font_size_key, anchor), (font_size, texts)) in &styled_groups {
+                        write!(&mut s, "{} {} {} {}", color, font_size, anchor, texts.len())
+                            .unwrap();
+                        for (x, y, text) in texts {
+                            if text.contains(' ') || text.is_empty() {
+                                write!(&mut s, " {x} {y} \"{text}\"").unwrap();
+                            } else {
+                                write!(&mut s, " {x} {y} {text}").unwrap();
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // ツールチップを書き込み
+                let mut tooltips: Vec<_> = self
+                    .tooltips
+                    .iter()
+                    .filter(|&(&(x, y), _)| x < self.w && y < self.h)
+                    .collect();
+                if !tooltips.is_empty() {
+                    tooltips.sort_unstable_by_key(|&(&(x, y), _)| (y, x));
+                    writeln!(&mut s, "TOOLTIPS").unwrap();
+                    writeln!(&mut s, "{}", tooltips.len()).unwrap();
+                    for ((x, y), tooltip) in tooltips {
+                        writeln!(&mut s, "{x} {y} {}", escape_tooltip(tooltip)).unwrap();
+                    }
+                }
+
+                // 軸ラベルを書き込み
+                if self.axis_label_step > 0
+                    || !self.row_labels.is_empty()
+                    || !self.col_labels.is_empty()
+                {
+                    writeln!(&mut s, "AXIS_LABELS").unwrap();
+                    for (n, custom) in [(self.h, &self.row_labels), (self.w, &self.col_labels)] {
+                        let labels: Vec<(usize, String)> = (0..n)
+                            .map(|i| (i, self.axis_label(i, custom)))
+                            .filter(|(_, label)| !label.is_empty())
+                            .collect();
+                        write!(&mut s, "{}", labels.len()).unwrap();
+                        for (i, label) in &labels {
+                            if label.contains(' ') {
+                                write!(&mut s, " {i} \"{label}\"").unwrap();
+                            } else {
+                                write!(&mut s, " {i} {label}").unwrap();
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // マーカーを書き込み
+                if !self.marker_groups.is_empty() {
+                    writeln!(&mut s, "CELL_MARKERS").unwrap();
+                    writeln!(&mut s, "{}", self.marker_groups.len()).unwrap();
+                    for group in &self.marker_groups {
+                        write!(
+                            &mut s,
+                            "{} {} {} {} {}",
+                            group.shape,
+                            group.stroke_color,
+                            group.fill_color,
+                            group.size,
+                            group.positions.len()
+                        )
+                        .unwrap();
+                        for (x, y) in &group.positions {
+                            write!(&mut s, " {x} {y}").unwrap();
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // 線分を書き込み
+                writeln!(&mut s, "LINES").unwrap();
+                writeln!(&mut s, "{}", self.lines.len()).unwrap();
+                for (line, color) in &self.lines {
+                    write!(&mut s, "{} {}", color, line.len()).unwrap();
+                    for p in line {
+                        write!(&mut s, " {} {}", p.0, p.1).unwrap();
+                    }
+                    writeln!(&mut s).unwrap();
+                }
+                // 太さ・線種付きの折れ線を書き込み
+                if !self.polylines.is_empty() {
+                    writeln!(&mut s, "POLYLINES").unwrap();
+                    writeln!(&mut s, "{}", self.polylines.len()).unwrap();
+                    for line in &self.polylines {
+                        write!(
+                            &mut s,
+                            "{} {} {} {}",
+                            line.color,
+                            line.width,
+                            line.style,
+                            line.points.len()
+                        )
+                        .unwrap();
+                        for (x, y) in &line.points {
+                            write!(&mut s, " {x} {y}").unwrap();
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // 矢印を書き込み
+                if !self.arrow_groups.is_empty() {
+                    writeln!(&mut s, "ARROWS").unwrap();
+                    writeln!(&mut s, "{}", self.arrow_groups.len()).unwrap();
+                    for ((color, _width_key, style), (width, arrows)) in &self.arrow_groups {
+                        write!(&mut s, "{} {} {} {}", color, width, style, arrows.len()).unwrap();
+                        for arrow in arrows {
+                            write!(
+                                &mut s,
+                                " {} {} {} {}",
+                                arrow.from.0, arrow.from.1, arrow.to.0, arrow.to.1
+                            )
+                            .unwrap();
+                            if arrow.label.contains(' ') || arrow.label.is_empty() {
+                                write!(&mut s, " \"{}\"", arrow.label).unwrap();
+                            } else {
+                                write!(&mut s, " {}", arrow.label).unwrap();
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // 壁を書き込む
+                let has_no_wall = |wall: WallPos| self.no_walls.contains(&wall);
+                if self
+                    .no_walls
+                    .iter()
+                    .any(|w| matches!(w, WallPos::Horizontal { .. }))
+                {
+                    writeln!(&mut s, "WALL_HORIZONTAL").unwrap();
+                    for row in 0..=self.h {
+                        for col in 0..self.w {
+                            if has_no_wall(WallPos::Horizontal { row, col }) {
+                                write!(&mut s, "N").unwrap();
+                            } else {
+                                write!(&mut s, "Y").unwrap();
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+                if self
+                    .no_walls
+                    .iter()
+                    .any(|w| matches!(w, WallPos::Vertical { .. }))
+                {
+                    writeln!(&mut s, "WALL_VERTICAL").unwrap();
+                    for row in 0..self.h {
+                        for col in 0..=self.w {
+                            if has_no_wall(WallPos::Vertical { row, col }) {
+                                write!(&mut s, "N").unwrap();
+                            } else {
+                                write!(&mut s, "Y").unwrap();
+                            }
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+
+                // 壁の色と太さを書き込む
+                let mut wall_style_groups: FxHashMap<(Color, u64), (f64, Vec<WallPos>)> =
+                    FxHashMap::default();
+                for (&wall, &(color, width)) in &self.wall_styles {
+                    if has_no_wall(wall) {
+                        continue;
+                    }
+                    wall_style_groups
+                        .entry((color, width.to_bits()))
+                        .or_insert_with(|| (width, Vec::new()))
+                        .1
+                        .push(wall);
+                }
+                if !wall_style_groups.is_empty() {
+                    writeln!(&mut s, "WALL_STYLES").unwrap();
+                    writeln!(&mut s, "{}", wall_style_groups.len()).unwrap();
+                    for ((color, _width_key), (width, walls)) in &wall_style_groups {
+                        write!(&mut s, "{} {} {}", color, width, walls.len()).unwrap();
+                        for wall in walls {
+                            write!(&mut s, " {wall}").unwrap();
+                        }
+                        writeln!(&mut s).unwrap();
+                    }
+                }
+                s
+            }
+        }
+
+        // 六角形グリッドの向き。Pointy は頂点が上 (奇数行が右にずれる)、
+        // Flat は辺が上 (奇数列が下にずれる)
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum HexOrientation {
+            Pointy,
+            Flat,
+        }
+
+        impl Display for HexOrientation {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    HexOrientation::Pointy => write!(f, "POINTY"),
+                    HexOrientation::Flat => write!(f, "FLAT"),
+                }
+            }
+        }
+
+        // 六角形グリッドのセルの位置。オフセット座標 (行・列) または axial 座標 (q, r) で指定する
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum HexPos {
+            Offset { row: usize, col: usize },
+            Axial { q: isize, r: isize },
+        }
+
+        impl From<GridPos> for HexPos {
+            fn from(p: GridPos) -> Self {
+                HexPos::Offset {
+                    row: p.row,
+                    col: p.col,
+                }
+            }
+        }
+
+        impl From<(usize, usize)> for HexPos {
+            // VisGrid と同じく (x, y) として解釈する
+            fn from(p: (usize, usize)) -> Self {
+                GridPos::from(p).into()
+            }
+        }
+
+        // axial 座標での隣接方向。方向 d の辺は方向 (d + 3) % 6 の辺と向かい合う
+        // Pointy: 東, 北東, 北西, 西, 南西, 南東 / Flat: 南東, 北東, 北, 北西, 南西, 南
+        pub const HEX_DIRECTIONS: [(isize, isize); 6] =
+            [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub struct HexPosError {
+            pub pos: HexPos,
+            pub h: usize,
+            pub w: usize,
+        }
+
+        impl Display for HexPosError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "position {:?} is out of range for {}x{} grid (H x W)",
+                    self.pos, self.h, self.w
+                )
+            }
+        }
+
+        impl std::error::Error for HexPosError {}
+
+        pub struct VisHexGrid {
+            h: usize,
+            w: usize,
+            orientation: HexOrientation,
+            conf: VisGridConf,
+            cell_colors: FxHashMap<(usize, usize), Color>,
+            cell_texts: FxHashMap<(usize, usize), String>,
+            lines: Vec<(Vec<(usize, usize)>, Color)>,
+            // 非表示にする辺 (セルの (x, y), 方向)
+            hidden_edges: FxHashSet<((usize, usize), usize)>,
+            bounds: Option<ItemBounds>,
+            clip_out_of_range: bool,
+        }
+
+        impl VisHexGrid {
+            pub fn new(
+                h: usize,
+                w: usize,
+                orientation: HexOrientation,
+                bounds: Option<ItemBounds>,
+            ) -> Self {
+                Self {
+                    h,
+                    w,
+                    orientation,
+                    conf: Default::default(),
+                    cell_colors: FxHashMap::default(),
+                    cell_texts: FxHashMap::default(),
+                    lines: Vec::new(),
+                    hidden_edges: FxHashSet::default(),
+                    bounds,
+                    clip_out_of_range: false,
+                }
+            }
+
+            pub fn set_bounds(mut self, bounds: ItemBounds) -> Self {
+                self.bounds = Some(bounds);
+                self
+            }
+
+            // true にすると、範囲外の位置の指定で panic せず、警告を出して無視する
+            pub fn clip_out_of_range(mut self, enabled: bool) -> Self {
+                self.clip_out_of_range = enabled;
+                self
+            }
+
+            fn axial(&self, x: usize, y: usize) -> (isize, isize) {
+                let (x, y) = (x as isize, y as isize);
+                match self.orientation {
+                    HexOrientation::Pointy => (x - (y - (y & 1)) / 2, y),
+                    HexOrientation::Flat => (x, y - (x - (x & 1)) / 2),
+                }
+            }
+
+            // 盤面内のセルなら (x, y) を返す
+            fn offset(&self, p: HexPos) -> Option<(usize, usize)> {
+                let (x, y) = match p {
+                    HexPos::Offset { row, col } => (col as isize, row as isize),
+                    HexPos::Axial { q, r } => match self.orientation {
+                        HexOrientation::Pointy => (q + (r - (r & 1)) / 2, r),
+                        HexOrientation::Flat => (q, r + (q - (q & 1)) / 2),
+                    },
+                };
+                if (0..self.w as isize).contains(&x) && (0..self.h as isize).contains(&y) {
+                    Some((x as usize, y as usize))
+                } else {
+                    None
+                }
+            }
+
+            fn check_pos(&self, p: HexPos) -> Result<(usize, usize), HexPosError> {
+                self.offset(p).ok_or(HexPosError {
+                    pos: p,
+                    h: self.h,
+                    w: self.w,
+                })
+            }
+
+            // 範囲外なら、clip モードでは警告して None を返し、そうでなければ panic する
+            fn cell(&self, p: HexPos, method: &str) -> Option<(usize, usize)> {
+                match self.check_pos(p) {
+                    Ok(cell) => Some(cell),
+                    Err(e) if self.clip_out_of_range => {
+                        eprintln!("warning: VisHexGrid::{method}: {e}");
+                        None
+                    }
+                    Err(e) => panic!("VisHexGrid::{method}: {e}"),
+                }
+            }
+
+            // p の方向 dir (HEX_DIRECTIONS の添字) に隣接するセル
+            pub fn neighbor(&self, p: impl Into<HexPos>, dir: usize) -> Option<GridPos> {
+                let (x, y) = self.offset(p.into())?;
+                let (q, r) = self.axial(x, y);
+                let (dq, dr) = HEX_DIRECTIONS[dir % 6];
+                self.offset(HexPos::Axial {
+                    q: q + dq,
+                    r: r + dr,
+                })
+                .map(|(x, y)| GridPos::xy(x, y))
+            }
+
+            pub fn update_cell_color(mut self, p: impl Into<HexPos>, color: Color) -> Self {
+                if let Some(cell) = self.cell(p.into(), "update_cell_color") {
+                    if color == self.conf.default_cell_color {
+                        self.cell_colors.remove(&cell);
+                    } else {
+                        self.cell_colors.insert(cell, color);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_cell_color(
+                self,
+                p: impl Into<HexPos>,
+                color: Color,
+            ) -> Result<Self, HexPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_cell_color(p, color))
+            }
+
+            pub fn update_text(mut self, p: impl Into<HexPos>, text: String) -> Self {
+                if let Some(cell) = self.cell(p.into(), "update_text") {
+                    if text.is_empty() {
+                        self.cell_texts.remove(&cell);
+                    } else {
+                        self.cell_texts.insert(cell, text);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_text(
+                self,
+                p: impl Into<HexPos>,
+                text: String,
+            ) -> Result<Self, HexPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_text(p, text))
+            }
+
+            // セルの中心を結ぶ折れ線を描画する。clip モードでは範囲外の点を飛ばす
+            pub fn add_line<P: Into<HexPos>>(mut self, line: Vec<P>, color: Color) -> Self {
+                let line = line
+                    .into_iter()
+                    .filter_map(|p| self.cell(p.into(), "add_line"))
+                    .collect();
+                self.lines.push((line, color));
+                self
+            }
+
+            pub fn try_add_line<P: Into<HexPos>>(
+                self,
+                line: Vec<P>,
+                color: Color,
+            ) -> Result<Self, HexPosError> {
+                let line: Vec<HexPos> = line.into_iter().map(Into::into).collect();
+                for &p in &line {
+                    self.check_pos(p)?;
+                }
+                Ok(self.add_line(line, color))
+            }
+
+            // セル p の方向 dir の辺を非表示にする (盤面の外周の辺も指定可能)
+            pub fn remove_edge(mut self, p: impl Into<HexPos>, dir: usize) -> Self {
+                if let Some(cell) = self.cell(p.into(), "remove_edge") {
+                    self.hidden_edges.insert((cell, dir % 6));
+                }
+                self
+            }
+
+            pub fn try_remove_edge(
+                self,
+                p: impl Into<HexPos>,
+                dir: usize,
+            ) -> Result<Self, HexPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.remove_edge(p, dir))
+            }
+
+            // 隣接する2つのセルの間の辺を非表示にする。隣接していない場合は何もしない
+            pub fn remove_edge_between(self, a: impl Into<HexPos>, b: impl Into<HexPos>) -> Self {
+                let a = a.into();
+                if self.cell(a, "remove_edge_between").is_none() {
+                    return self;
+                }
+                let Some(b) = self.cell(b.into(), "remove_edge_between") else {
+                    return self;
+                };
+                match (0..6).find(|&dir| self.neighbor(a, dir) == Some(GridPos::xy(b.0, b.1))) {
+                    Some(dir) => self.remove_edge(a, dir),
+                    None => self,
+                }
+            }
+
+            pub fn to_vis_string(&self, mode_name: &str) -> String {
+                let mut s = String::new();
+
+                let bounds = match &self.bounds {
+                    Some(b) => format!("({}, {}, {}, {})", b.left, b.top, b.right, b.bottom),
+                    None => String::new(),
+                };
+                writeln!(
+                    &mut s,
+                    "$v({}) HEX_GRID{} {} {} {} {} {} {}",
+                    mode_name,
+                    bounds,
+                    self.h,
+                    self.w,
+                    self.orientation,
+                    self.conf.border_color,
+                    self.conf.text_color,
+                    self.conf.default_cell_color
+                )
+                .unwrap();
+
+                write_lattice_sections(
+                    &mut s,
+                    self.h,
+                    self.w,
+                    &self.cell_colors,
+                    &self.cell_texts,
+                    &self.lines,
+                    &self.hidden_edges,
+                );
+
+                s
+            }
+        }
+
+        // VisHexGrid / VisTriGrid で共通のセクション (セルの色, テキスト, 線, 非表示の辺) を書き込む
+        fn write_lattice_sections(
+            s: &mut String,
+            h: usize,
+            w: usize,
+            cell_colors: &FxHashMap<(usize, usize), Color>,
+            cell_texts: &FxHashMap<(usize, usize), String>,
+            lines: &[(Vec<(usize, usize)>, Color)],
+            hidden_edges: &FxHashSet<((usize, usize), usize)>,
+        ) {
+            // 各セルの色の位置を書き込み
+            if !cell_colors.is_empty() {
+                let mut color_to_pos: FxHashMap<Color, Vec<(usize, usize)>> = FxHashMap::default();
+                for (&p, &color) in cell_colors {
+                    color_to_pos.entry(color).or_default().push(p);
+                }
+                writeln!(s, "CELL_COLORS_POS").unwrap();
+                writeln!(s, "{}", color_to_pos.len()).unwrap();
+                for (color, positions) in &mut color_to_pos {
+                    positions.sort_unstable_by_key(|&(x, y)| (y, x));
+                    write!(s, "{} {}", color, positions.len()).unwrap();
+                    for &(x, y) in positions.iter() {
+                        write!(s, " {x} {y}").unwrap();
+                    }
+                    writeln!(s).unwrap();
+                }
+            }
+
+            // 各セルのテキストを書き込み (VisGrid の CELL_TEXT と同じ形式)
+            // スペースを含むテキストと空のテキストはダブルクォートで囲む
+            let quote = |text: &str| {
+                if text.contains(' ') || text.is_empty() {
+                    format!("\"{text}\"")
+                } else {
+                    text.to_string()
+                }
+            };
+            if !cell_texts.is_empty() {
+                writeln!(s, "CELL_TEXT").unwrap();
+                for y in 0..h {
+                    let last_non_empty = (0..w).rev().find(|&x| cell_texts.contains_key(&(x, y)));
+                    if let Some(last) = last_non_empty {
+                        for x in 0..=last {
+                            if x > 0 {
+                                write!(s, " ").unwrap();
+                            }
+                            let text = cell_texts.get(&(x, y)).map_or("", |t| t.as_str());
+                            write!(s, "{}", quote(text)).unwrap();
+                        }
+                    }
+                    writeln!(s).unwrap();
+                }
+            }
+
+            // 線を書き込み
+            if !lines.is_empty() {
+                writeln!(s, "LINES").unwrap();
+                writeln!(s, "{}", lines.len()).unwrap();
+                for (line, color) in lines {
+                    write!(s, "{} {}", color, line.len()).unwrap();
+                    for &(x, y) in line {
+                        write!(s, " {x} {y}").unwrap();
+                    }
+                    writeln!(s).unwrap();
+                }
+            }
+
+            // 非表示の辺を書き込み
+            if !hidden_edges.is_empty() {
+                let mut edges: Vec<_> = hidden_edges.iter().collect();
+                edges.sort_unstable_by_key(|&&((x, y), dir)| (y, x, dir));
+                writeln!(s, "HIDDEN_EDGES").unwrap();
+                write!(s, "{}", edges.len()).unwrap();
+                for ((x, y), dir) in edges {
+                    write!(s, " {x} {y} {dir}").unwrap();
+                }
+                writeln!(s).unwrap();
+            }
+        }
+
+        // 三角形グリッド。各行に W 個の三角形が上向き・下向き交互に並び、
+        // (x + y) が偶数のセルが上向きになる
+        // 辺の方向 0: 水平 (上向きなら下辺、下向きなら上辺), 1: 左の斜辺, 2: 右の斜辺
+        pub struct VisTriGrid {
+            h: usize,
+            w: usize,
+            conf: VisGridConf,
+            cell_colors: FxHashMap<(usize, usize), Color>,
+            cell_texts: FxHashMap<(usize, usize), String>,
+            lines: Vec<(Vec<(usize, usize)>, Color)>,
+            // 非表示にする辺 (セルの (x, y), 方向)
+            hidden_edges: FxHashSet<((usize, usize), usize)>,
+            bounds: Option<ItemBounds>,
+            clip_out_of_range: bool,
+        }
+
+        impl VisTriGrid {
+            pub fn new(h: usize, w: usize, bounds: Option<ItemBounds>) -> Self {
+                Self {
+                    h,
+                    w,
+                    conf: Default::default(),
+                    cell_colors: FxHashMap::default(),
+                    cell_texts: FxHashMap::default(),
+                    lines: Vec::new(),
+                    hidden_edges: FxHashSet::default(),
+                    bounds,
+                    clip_out_of_range: false,
+                }
+            }
+
+            pub fn set_bounds(mut self, bounds: ItemBounds) -> Self {
+                self.bounds = Some(bounds);
+                self
+            }
+
+            // true にすると、範囲外の位置の指定で panic せず、警告を出して無視する
+            pub fn clip_out_of_range(mut self, enabled: bool) -> Self {
+                self.clip_out_of_range = enabled;
+                self
+            }
+
+            fn check_pos(&self, p: GridPos) -> Result<(), GridPosError> {
+                if p.row < self.h && p.col < self.w {
+                    Ok(())
+                } else {
+                    Err(GridPosError {
+                        pos: p,
+                        h: self.h,
+                        w: self.w,
+                    })
+                }
+            }
+
+            // 範囲外なら、clip モードでは警告して false を返し、そうでなければ panic する
+            fn accept_pos(&self, p: GridPos, method: &str) -> bool {
+                match self.check_pos(p) {
+                    Ok(()) => true,
+                    Err(e) if self.clip_out_of_range => {
+                        eprintln!("warning: VisTriGrid::{method}: {e}");
+                        false
+                    }
+                    Err(e) => panic!("VisTriGrid::{method}: {e}"),
+                }
+            }
+
+            // セル p が上向きの三角形か
+            pub fn is_up(p: impl Into<GridPos>) -> bool {
+                let p = p.into();
+                (p.row + p.col) % 2 == 0
+            }
+
+            // p の方向 dir (0: 水平, 1: 左, 2: 右) の辺を共有するセル
+            pub fn neighbor(&self, p: impl Into<GridPos>, dir: usize) -> Option<GridPos> {
+                let p = p.into();
+                self.check_pos(p).ok()?;
+                let (x, y) = (p.col as isize, p.row as isize);
+                let (nx, ny) = match dir % 3 {
+                    0 if Self::is_up(p) => (x, y + 1),
+                    0 => (x, y - 1),
+                    1 => (x - 1, y),
+                    _ => (x + 1, y),
+                };
+                if (0..self.w as isize).contains(&nx) && (0..self.h as isize).contains(&ny) {
+                    Some(GridPos::xy(nx as usize, ny as usize))
+                } else {
+                    None
+                }
+            }
+
+            pub fn update_cell_color(mut self, p: impl Into<GridPos>, color: Color) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "update_cell_color") {
+                    if color == self.conf.default_cell_color {
+                        self.cell_colors.remove(&(p.col, p.row));
+                    } else {
+                        self.cell_colors.insert((p.col, p.row), color);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_cell_color(
+                self,
+                p: impl Into<GridPos>,
+                color: Color,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_cell_color(p, color))
+            }
+
+            pub fn update_text(mut self, p: impl Into<GridPos>, text: String) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "update_text") {
+                    if text.is_empty() {
+                        self.cell_texts.remove(&(p.col, p.row));
+                    } else {
+                        self.cell_texts.insert((p.col, p.row), text);
+                    }
+                }
+                self
+            }
+
+            pub fn try_update_text(
+                self,
+                p: impl Into<GridPos>,
+                text: String,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.update_text(p, text))
+            }
+
+            // セルの重心を結ぶ折れ線を描画する。clip モードでは範囲外の点を飛ばす
+            pub fn add_line<P: Into<GridPos>>(mut self, line: Vec<P>, color: Color) -> Self {
+                let line = line
+                    .into_iter()
+                    .map(Into::into)
+                    .filter(|&p| self.accept_pos(p, "add_line"))
+                    .map(|p| (p.col, p.row))
+                    .collect();
+                self.lines.push((line, color));
+                self
+            }
+
+            pub fn try_add_line<P: Into<GridPos>>(
+                self,
+                line: Vec<P>,
+                color: Color,
+            ) -> Result<Self, GridPosError> {
+                let line: Vec<GridPos> = line.into_iter().map(Into::into).collect();
+                for &p in &line {
+                    self.check_pos(p)?;
+                }
+                Ok(self.add_line(line, color))
+            }
+
+            // セル p の方向 dir の辺を非表示にする (盤面の外周の辺も指定可能)
+            pub fn remove_edge(mut self, p: impl Into<GridPos>, dir: usize) -> Self {
+                let p = p.into();
+                if self.accept_pos(p, "remove_edge") {
+                    self.hidden_edges.insert(((p.col, p.row), dir % 3));
+                }
+                self
+            }
+
+            pub fn try_remove_edge(
+                self,
+                p: impl Into<GridPos>,
+                dir: usize,
+            ) -> Result<Self, GridPosError> {
+                let p = p.into();
+                self.check_pos(p)?;
+                Ok(self.remove_edge(p, dir))
+            }
+
+            // 隣接する2つのセルの間の辺を非表示にする。隣接していない場合は何もしない
+            pub fn remove_edge_between(self, a: impl Into<GridPos>, b: impl Into<GridPos>) -> Self {
+                let (a, b) = (a.into(), b.into());
+                if !self.accept_pos(a, "remove_edge_between")
+                    || !self.accept_pos(b, "remove_edge_between")
+                {
+                    return self;
+                }
+                match (0..3).find(|&dir| self.neighbor(a, dir) == Some(b)) {
+                    Some(dir) => self.remove_edge(a, dir),
+                    None => self,
+                }
+            }
+
+            pub fn to_vis_string(&self, mode_name: &str) -> String {
+                let mut s = String::new();
+
+                let bounds = match &self.bounds {
+                    Some(b) => format!("({}, {}, {}, {})", b.left, b.top, b.right, b.bottom),
+                    None => String::new(),
+                };
+                writeln!(
+                    &mut s,
+                    "$v({}) TRI_GRID{} {} {} {} {} {}",
+                    mode_name,
+                    bounds,
+                    self.h,
+                    self.w,
+                    self.conf.border_color,
+                    self.conf.text_color,
+                    self.conf.default_cell_color
+                )
+                .unwrap();
+
+                write_lattice_sections(
+                    &mut s,
+                    self.h,
+                    self.w,
+                    &self.cell_colors,
+                    &self.cell_texts,
+                    &self.lines,
+                    &self.hidden_edges,
+                );
+
+                s
+            }
+        }
+
+        // グラフの頂点の配置方法。座標を指定した頂点はその位置に固定する
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum GraphLayout {
+            // 辺をばね、頂点どうしを斥力で結ぶ力学モデル (円周上の配置から始める)
+            ForceDirected,
+            // 追加した順に円周上に並べる
+            Circular,
+            // 根からの BFS の深さごとに上から段に並べる
+            Layered,
+        }
+
+        pub struct GraphNode {
+            id: usize,
+            color: Color,
+            label: String,
+            pos: Option<(f64, f64)>,
+        }
+
+        impl GraphNode {
+            // 既定では白で塗り、id をラベルにする
+            pub fn new(id: usize) -> Self {
+                Self {
+                    id,
+                    color: WHITE,
+                    label: id.to_string(),
+                    pos: None,
+                }
+            }
+
+            pub fn with_color(mut self, color: Color) -> Self {
+                self.color = color;
+                self
+            }
+
+            pub fn with_label(mut self, label: String) -> Self {
+                self.label = label;
+                self
+            }
+
+            // 座標を指定すると、レイアウトによらずその位置に置く
+            pub fn with_pos(mut self, x: f64, y: f64) -> Self {
+                self.pos = Some((x, y));
+                self
+            }
+        }
+
+        impl From<usize> for GraphNode {
+            fn from(id: usize) -> Self {
+                GraphNode::new(id)
+            }
+        }
+
+        pub struct GraphEdge {
+            u: usize,
+            v: usize,
+            color: Color,
+            label: String,
+        }
+
+        impl GraphEdge {
+            pub fn new(u: usize, v: usize) -> Self {
+                Self {
+                    u,
+                    v,
+                    color: GRAY,
+                    label: String::new(),
+                }
+            }
+
+            // 重みは辺の中央にラベルとして表示する
+            pub fn with_weight(mut self, weight: f64) -> Self {
+                self.label = weight.to_string();
+                self
+            }
+
+            pub fn with_color(mut self, color: Color) -> Self {
+                self.color = color;
+                self
+            }
+
+            pub fn with_label(mut self, label: String) -> Self {
+                self.label = label;
+                self
+            }
+        }
+
+        // (u, v)
+        impl From<(usize, usize)> for GraphEdge {
+            fn from((u, v): (usize, usize)) -> Self {
+                GraphEdge::new(u, v)
+            }
+        }
+
+        // (u, v, weight)
+        impl From<(usize, usize, f64)> for GraphEdge {
+            fn from((u, v, weight): (usize, usize, f64)) -> Self {
+                GraphEdge::new(u, v).with_weight(weight)
+            }
+        }
+
+        // 頂点と辺からなるグラフ。頂点を配置して Vis2DPlane の図形として出力する
+        pub struct VisGraph {
+            h: f64,
+            w: f64,
+            bounds: Option<ItemBounds>,
+            layout: GraphLayout,
+            directed: bool,
+            node_radius: f64,
+            // id -> nodes の位置。頂点は追加した順に並べる
+            node_index: FxHashMap<usize, usize>,
+            nodes: Vec<GraphNode>,
+            edges: Vec<GraphEdge>,
+        }
+
+        impl VisGraph {
+            pub fn new(h: f64, w: f64, bounds: Option<ItemBounds>) -> Self {
+                Self {
+                    h,
+                    w,
+                    bounds,
+                    layout: GraphLayout::ForceDirected,
+                    directed: false,
+                    node_radius: h.min(w) / 40.0,
+                    node_index: FxHashMap::default(),
+                    nodes: Vec::new(),
+                    edges: Vec::new(),
+                }
+            }
+
+            pub fn set_layout(mut self, layout: GraphLayout) -> Self {
+                self.layout = layout;
+                self
+            }
+
+            // 有向グラフでは辺を矢印で描く
+            pub fn set_directed(mut self, directed: bool) -> Self {
+                self.directed = directed;
+                self
+            }
+
+            pub fn set_node_radius(mut self, r: f64) -> Self {
+                self.node_radius = r;
+                self
+            }
+
+            // 同じ id の頂点を再び追加すると、色・ラベル・座標を上書きする
+            pub fn add_node<N: Into<GraphNode>>(mut self, node: N) -> Self {
+                let node = node.into();
+                let i = self.node_slot(node.id);
+                self.nodes[i] = node;
+                self
+            }
+
+            pub fn add_nodes<N: Into<GraphNode>>(self, nodes: impl IntoIterator<Item = N>) -> Self {
+                nodes
+                    .into_iter()
+                    .fold(self, |graph, node| graph.add_node(node))
+            }
+
+            // 未追加の端点は既定の頂点として追加する
+            pub fn add_edge<E: Into<GraphEdge>>(mut self, edge: E) -> Self {
+                let edge = edge.into();
+                self.node_slot(edge.u);
+                self.node_slot(edge.v);
+                self.edges.push(edge);
+                self
+            }
+
+            pub fn add_edges<E: Into<GraphEdge>>(self, edges: impl IntoIterator<Item = E>) -> Self {
+                edges
+                    .into_iter()
+                    .fold(self, |graph, edge| graph.add_edge(edge))
+            }
+
+            fn node_slot(&mut self, id: usize) -> usize {
+                group_index(&mut self.node_index, &mut self.nodes, id, || {
+                    GraphNode::new(id)
+                })
+            }
+
+            // 辺の端点を nodes の位置に変換する (自己ループは除く)
+            fn edge_indices(&self) -> Vec<(usize, usize)> {
+                self.edges
+                    .iter()
+                    .map(|e| (self.node_index[&e.u], self.node_index[&e.v]))
+                    .filter(|(u, v)| u != v)
+                    .collect()
+            }
+
+            // 頂点を置く範囲 (min_x, max_x, min_y, max_y)。端の頂点がはみ出さないよう余白を取る
+            fn layout_area(&self) -> (f64, f64, f64, f64) {
+                let margin = (self.node_radius * 2.0).min(self.w / 2.0).min(self.h / 2.0);
+                (margin, self.w - margin, margin, self.h - margin)
+            }
+
+            fn positions(&self) -> Vec<(f64, f64)> {
+                let mut pos = match self.layout {
+                    GraphLayout::ForceDirected => self.force_directed_positions(),
+                    GraphLayout::Circular => self.circular_positions(),
+                    GraphLayout::Layered => self.layered_positions(),
+                };
+                for (p, node) in pos.iter_mut().zip(&self.nodes) {
+                    if let Some(fixed) = node.pos {
+                        *p = fixed;
+                    }
+                }
+                pos
+            }
+
+            fn circular_positions(&self) -> Vec<(f64, f64)> {
+                let (min_x, max_x, min_y, max_y) = self.layout_area();
+                let (cx, cy) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+                let r = (max_x - min_x).min(max_y - min_y) / 2.0;
+                let n = self.nodes.len();
+                if n == 1 {
+                    return vec![(cx, cy)];
+                }
+                // 真上から時計回りに並べる
+                (0..n)
+                    .map(|i| {
+                        let theta = std::f64::consts::TAU * i as f64 / n as f64
+                            - std::f64::consts::FRAC_PI_2;
+                        (cx + r * theta.cos(), cy + r * theta.sin())
+                    })
+                    .collect()
+            }
+
+            fn force_directed_positions(&self) -> Vec<(f64, f64)> {
+                const ITERATIONS: usize = 200;
+                let n = self.nodes.len();
+                let (min_x, max_x, min_y, max_y) = self.layout_area();
+                let mut pos = self.circular_positions();
+                for (p, node) in pos.iter_mut().zip(&self.nodes) {
+                    if let Some(fixed) = node.pos {
+                        *p = fixed;
+                    }
+                }
+                if n <= 1 {
+                    return pos;
+                }
+                let edges = self.edge_indices();
+                // 理想的な辺の長さ
+                let k = ((max_x - min_x) * (max_y - min_y) / n as f64).sqrt();
+                let max_step = (max_x - min_x).min(max_y - min_y) / 10.0;
+                for iter in 0..ITERATIONS {
+                    let temperature = max_step * (1.0 - iter as f64 / ITERATIONS as f64);
+                    let mut disp = vec![(0.0, 0.0); n];
+                    // 全頂点対の斥力 k^2 / d
+                    for i in 0..n {
+                        for j in i + 1..n {
+                            let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                            let d = dx.hypot(dy).max(1e-3);
+                            let f = k * k / d / d;
+                            disp[i].0 += dx * f;
+                            disp[i].1 += dy * f;
+                            disp[j].0 -= dx * f;
+                            disp[j].1 -= dy * f;
+                        }
+                    }
+                    // 辺の引力 d^2 / k
+                    for &(u, v) in &edges {
+                        let (dx, dy) = (pos[u].0 - pos[v].0, pos[u].1 - pos[v].1);
+                        let d = dx.hypot(dy).max(1e-3);
+                        let f = d / k;
+                        disp[u].0 -= dx * f;
+                        disp[u].1 -= dy * f;
+                        disp[v].0 += dx * f;
+                        disp[v].1 += dy * f;
+                    }
+                    // 移動量を温度で制限し、範囲内に収める
+                    for i in 0..n {
+                        if self.nodes[i].pos.is_some() {
+                            continue;
+                        }
+                        let len = disp[i].0.hypot(disp[i].1);
+                        if len > 0.0 {
+                            let step = len.min(temperature) / len;
+                            pos[i].0 = (pos[i].0 + disp[i].0 * step).clamp(min_x, max_x);
+                            pos[i].1 = (pos[i].1 + disp[i].1 * step).clamp(min_y, max_y);
+                        }
+                    }
+                }
+                pos
+            }
+
+            fn layered_positions(&self) -> Vec<(f64, f64)> {
+                let n = self.nodes.len();
+                let (min_x, max_x, min_y, max_y) = self.layout_area();
+                let mut succ = vec![Vec::new(); n];
+                let mut pred = vec![Vec::new(); n];
+                for (u, v) in self.edge_indices() {
+                    succ[u].push(v);
+                    pred[v].push(u);
+                    if !self.directed {
+                        succ[v].push(u);
+                        pred[u].push(v);
+                    }
+                }
+                // 有向グラフは入次数 0 の頂点を、残りは追加順に未訪問の頂点を根にして BFS する
+                let mut depth = vec![usize::MAX; n];
+                let mut queue = std::collections::VecDeque::new();
+                let roots = (0..n).filter(|&v| self.directed && pred[v].is_empty());
+                for root in roots.chain(0..n) {
+                    if depth[root] != usize::MAX {
+                        continue;
+                    }
+                    depth[root] = 0;
+                    queue.push_back(root);
+                    while let Some(u) = queue.pop_front() {
+                        for &v in &succ[u] {
+                            if depth[v] == usize::MAX {
+                                depth[v] = depth[u] + 1;
+                                queue.push_back(v);
+                            }
+                        }
+                    }
+                }
+                let layer_count = depth.iter().max().map_or(0, |d| d + 1);
+                let mut layers = vec![Vec::new(); layer_count];
+                for v in 0..n {
+                    layers[depth[v]].push(v);
+                }
+                // 各段は、1つ上の段の隣接頂点の x 座標の平均順に並べる (交差を減らす)
+                let mut pos = vec![(0.0, 0.0); n];
+                for (d, layer) in layers.iter_mut().enumerate() {
+                    if d > 0 {
+                        let key = |v: usize| {
+                            let xs: Vec<f64> = pred[v]
+                                .iter()
+                                .filter(|&&u| depth[u] + 1 == d)
+                                .map(|&u| pos[u].0)
+                                .collect();
+                            if xs.is_empty() {
+                                f64::INFINITY
+                            } else {
+                                xs.iter().sum::<f64>() / xs.len() as f64
+                            }
+                        };
+                        layer.sort_by(|&a, &b| key(a).total_cmp(&key(b)));
+                    }
+                    let y = min_y + (max_y - min_y) * (d as f64 + 0.5) / layer_count as f64;
+                    for (i, &v) in layer.iter().enumerate() {
+                        let x = min_x + (max_x - min_x) * (i as f64 + 0.5) / layer.len() as f64;
+                        pos[v] = (x, y);
+                    }
+                }
+                pos
+            }
+
+            // 配置した頂点と辺を 2D 平面の図形に変換する
+            pub fn to_plane(&self) -> Vis2DPlane {
+                let pos = self.positions();
+                let r = self.node_radius;
+                let mut plane = Vis2DPlane::new(self.h, self.w, self.bounds);
+                let label_style = TextStyle::new().with_background(WHITE, 1.0);
+                for edge in &self.edges {
+                    let (u, v) = (self.node_index[&edge.u], self.node_index[&edge.v]);
+                    if u == v {
+                        continue;
+                    }
+                    let ((ax, ay), (bx, by)) = (pos[u], pos[v]);
+                    if self.directed {
+                        // 矢じりが頂点の円に隠れないよう、両端を半径分だけ縮める
+                        let len = (bx - ax).hypot(by - ay);
+                        if len > 2.0 * r {
+                            let (ux, uy) = ((bx - ax) / len * r, (by - ay) / len * r);
+                            plane = plane.add_arrow(
+                                edge.color,
+                                1.5,
+                                8.0,
+                                ax + ux,
+                                ay + uy,
+                                bx - ux,
+                                by - uy,
+                            );
+                        }
+                    } else {
+                        plane = plane.add_line(edge.color, 1.5, ax, ay, bx, by);
+                    }
+                    if !edge.label.is_empty() {
+                        plane = plane.add_styled_text(
+                            BLACK,
+                            10.0,
+                            label_style,
+                            (ax + bx) / 2.0,
+                            (ay + by) / 2.0,
+                            edge.label.clone(),
+                        );
+                    }
+                }
+                for (node, &(x, y)) in self.nodes.iter().zip(&pos) {
+                    plane = plane.add_circle_group(
+                        BLACK,
+                        node.color,
+                        [Circle::new(x, y, r).with_tooltip(format!("node {}", node.id))],
+                    );
+                    if !node.label.is_empty() {
+                        plane = plane.add_text(BLACK, 12.0, x, y, node.label.clone());
+                    }
+                }
+                plane
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                self.to_plane().to_vis_string(mode)
+            }
+        }
+
+        // 探索木の頂点の状態。塗りつぶしの色で区別する
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum TreeNodeStatus {
+            // 未展開 (白)
+            Open,
+            // 子を生成済み (シアン)
+            Expanded,
+            // 枝刈りされた (灰)
+            Pruned,
+        }
+
+        impl Display for TreeNodeStatus {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    TreeNodeStatus::Open => "OPEN",
+                    TreeNodeStatus::Expanded => "EXPANDED",
+                    TreeNodeStatus::Pruned => "PRUNED",
+                };
+                write!(f, "{s}")
+            }
+        }
+
+        pub struct TreeNode {
+            id: usize,
+            parent: Option<usize>,
+            score: f64,
+            status: TreeNodeStatus,
+            label: String,
+        }
+
+        impl TreeNode {
+            // parent が None (または未追加の id) の頂点は根になる
+            pub fn new(id: usize, parent: Option<usize>) -> Self {
+                Self {
+                    id,
+                    parent,
+                    score: 0.0,
+                    status: TreeNodeStatus::Open,
+                    label: String::new(),
+                }
+            }
+
+            // スコアはツールチップに表示する
+            pub fn with_score(mut self, score: f64) -> Self {
+                self.score = score;
+                self
+            }
+
+            pub fn with_status(mut self, status: TreeNodeStatus) -> Self {
+                self.status = status;
+                self
+            }
+
+            // 頂点の円の中に表示するラベル
+            pub fn with_label(mut self, label: String) -> Self {
+                self.label = label;
+                self
+            }
+        }
+
+        // (id, parent)
+        impl From<(usize, Option<usize>)> for TreeNode {
+            fn from((id, parent): (usize, Option<usize>)) -> Self {
+                TreeNode::new(id, parent)
+            }
+        }
+
+        // 配置済みの表示する頂点。node が None のものは省略した部分木をまとめた頂点
+        struct TreeItem {
+            node: Option<usize>,
+            hidden: usize,
+            depth: usize,
+            parent: Option<usize>,
+            children: Vec<usize>,
+            highlighted: bool,
+        }
+
+        // ビームサーチや MCTS の探索木。親へのリンクから木を配置して Vis2DPlane の図形として出力する
+        pub struct VisTree {
+            h: f64,
+            w: f64,
+            bounds: Option<ItemBounds>,
+            node_index: FxHashMap<usize, usize>,
+            nodes: Vec<TreeNode>,
+            // 強調表示する経路の末端の id
+            highlighted_leaves: Vec<usize>,
+            max_depth: Option<usize>,
+            max_children: Option<usize>,
+        }
+
+        impl VisTree {
+            pub fn new(h: f64, w: f64, bounds: Option<ItemBounds>) -> Self {
+                Self {
+                    h,
+                    w,
+                    bounds,
+                    node_index: FxHashMap::default(),
+                    nodes: Vec::new(),
+                    highlighted_leaves: Vec::new(),
+                    max_depth: None,
+                    max_children: None,
+                }
+            }
+
+            // 同じ id の頂点を再び追加すると上書きする
+            pub fn add_node<N: Into<TreeNode>>(mut self, node: N) -> Self {
+                let node = node.into();
+                match self.node_index.get(&node.id) {
+                    Some(&i) => self.nodes[i] = node,
+                    None => {
+                        self.node_index.insert(node.id, self.nodes.len());
+                        self.nodes.push(node);
+                    }
+                }
+                self
+            }
+
+            pub fn add_nodes<N: Into<TreeNode>>(self, nodes: impl IntoIterator<Item = N>) -> Self {
+                nodes
+                    .into_iter()
+                    .fold(self, |tree, node| tree.add_node(node))
+            }
+
+            // 根から leaf までの経路を赤で強調する。省略の対象にもならない
+            pub fn highlight_path(mut self, leaf: usize) -> Self {
+                self.highlighted_leaves.push(leaf);
+                self
+            }
+
+            // 深さが max_depth より深い頂点を省略する (根の深さは 0)
+            pub fn set_max_depth(mut self, max_depth: usize) -> Self {
+                self.max_depth = Some(max_depth);
+                self
+            }
+
+            // 各頂点の子を追加した順に max_children 個まで表示し、残りを省略する
+            pub fn set_max_children(mut self, max_children: usize) -> Self {
+                self.max_children = Some(max_children);
+                self
+            }
+
+            fn parent_index(&self, i: usize) -> Option<usize> {
+                let p = *self.node_index.get(&self.nodes[i].parent?)?;
+                (p != i).then_some(p)
+            }
+
+            // 表示する頂点を行きがけ順に並べる。省略した子はまとめて1つの頂点にする
+            fn visible_items(&self) -> Vec<TreeItem> {
+                let n = self.nodes.len();
+                let mut children = vec![Vec::new(); n];
+                let mut roots = Vec::new();
+                for i in 0..n {
+                    match self.parent_index(i) {
+                        Some(p) => children[p].push(i),
+                        None => roots.push(i),
+                    }
+                }
+
+                // 行きがけ順と部分木の大きさ
+                let mut order = Vec::with_capacity(n);
+                let mut stack = roots.clone();
+                while let Some(v) = stack.pop() {
+                    order.push(v);
+                    stack.extend(&children[v]);
+                }
+                let mut size = vec![1; n];
+                for &v in order.iter().rev() {
+                    if let Some(p) = self.parent_index(v) {
+                        size[p] += size[v];
+                    }
+                }
+
+                // 強調する経路上の頂点 (親をたどる回数は n 回までにして閉路を避ける)
+                let mut highlighted = vec![false; n];
+                for leaf in &self.highlighted_leaves {
+                    let mut cur = self.node_index.get(leaf).copied();
+                    for _ in 0..n {
+                        let Some(v) = cur else { break };
+                        highlighted[v] = true;
+                        cur = self.parent_index(v);
+                    }
+                }
+
+                let mut items: Vec<TreeItem> = Vec::new();
+                // (node, 省略した頂点数, 深さ, 親の items の位置)
+                let mut stack: Vec<(Option<usize>, usize, usize, Option<usize>)> =
+                    roots.iter().rev().map(|&r| (Some(r), 0, 0, None)).collect();
+                while let Some((node, hidden, depth, parent)) = stack.pop() {
+                    let it = items.len();
+                    items.push(TreeItem {
+                        node,
+                        hidden,
+                        depth,
+                        parent,
+                        children: Vec::new(),
+                        highlighted: node.is_some_and(|v| highlighted[v]),
+                    });
+                    if let Some(p) = parent {
+                        items[p].children.push(it);
+                    }
+                    let Some(v) = node else { continue };
+                    let mut shown = Vec::new();
+                    let mut kept = 0;
+                    let mut hidden = 0;
+                    for &c in &children[v] {
+                        if highlighted[c] {
+                            shown.push(c);
+                        } else if self.max_depth.is_none_or(|d| depth < d)
+                            && self.max_children.is_none_or(|k| kept < k)
+                        {
+                            shown.push(c);
+                            kept += 1;
+                        } else {
+                            hidden += size[c];
+                        }
+                    }
+                    // 省略した頂点は最後の子として置く
+                    if hidden > 0 {
+                        stack.push((None, hidden, depth + 1, Some(it)));
+                    }
+                    stack.extend(
+                        shown
+                            .iter()
+                            .rev()
+                            .map(|&c| (Some(c), 0, depth + 1, Some(it))),
+                    );
+                }
+                items
+            }
+
+            // 葉を左から等間隔に並べ、親を最初と最後の子の中央に置く
+            pub fn to_plane(&self) -> Vis2DPlane {
+                let items = self.visible_items();
+                let mut plane = Vis2DPlane::new(self.h, self.w, self.bounds);
+                if items.is_empty() {
+                    return plane;
+                }
+                let mut slot = vec![0.0; items.len()];
+                let mut leaves = 0;
+                for (i, item) in items.iter().enumerate() {
+                    if item.children.is_empty() {
+                        slot[i] = leaves as f64;
+                        leaves += 1;
+                    }
+                }
+                for i in (0..items.len()).rev() {
+                    if let (Some(&first), Some(&last)) =
+                        (items[i].children.first(), items[i].children.last())
+                    {
+                        slot[i] = (slot[first] + slot[last]) / 2.0;
+                    }
+                }
+                let depths = items.iter().map(|item| item.depth).max().unwrap() + 1;
+                let (cell_w, cell_h) = (self.w / leaves as f64, self.h / depths as f64);
+                let r = cell_w.min(cell_h) * 0.3;
+                let font_size = (r * 0.8).clamp(6.0, 12.0);
+                let pos = |i: usize| {
+                    (
+                        cell_w * (slot[i] + 0.5),
+                        cell_h * (items[i].depth as f64 + 0.5),
+                    )
+                };
+
+                // 親子を結ぶ辺
+                for (i, item) in items.iter().enumerate() {
+                    let Some(p) = item.parent else { continue };
+                    let ((ax, ay), (bx, by)) = (pos(p), pos(i));
+                    if item.node.is_none() {
+                        plane = plane.add_styled_line(GRAY, 1.0, LineStyle::Dashed, ax, ay, bx, by);
+                    } else if item.highlighted {
+                        plane = plane.add_line(RED, 2.5, ax, ay, bx, by);
+                    } else {
+                        plane = plane.add_line(GRAY, 1.0, ax, ay, bx, by);
+                    }
+                }
+
+                // 頂点
+                for (i, item) in items.iter().enumerate() {
+                    let (x, y) = pos(i);
+                    let Some(v) = item.node else {
+                        let circle = Circle::new(x, y, r)
+                            .with_tooltip(format!("{} nodes collapsed", item.hidden));
+                        plane = plane
+                            .add_styled_circle_group(GRAY, WHITE, 1.0, LineStyle::Dashed, [circle])
+                            .add_text(GRAY, font_size, x, y, format!("+{}", item.hidden));
+                        continue;
+                    };
+                    let node = &self.nodes[v];
+                    let fill = match node.status {
+                        TreeNodeStatus::Open => WHITE,
+                        TreeNodeStatus::Expanded => CYAN,
+                        TreeNodeStatus::Pruned => GRAY,
+                    };
+                    let (stroke, width) = if item.highlighted {
+                        (RED, 2.5)
+                    } else {
+                        (BLACK, 1.0)
+                    };
+                    let circle = Circle::new(x, y, r).with_tooltip(format!(
+                        "node {}\nscore {}\n{}",
+                        node.id, node.score, node.status
+                    ));
+                    plane = plane.add_styled_circle_group(
+                        stroke,
+                        fill,
+                        width,
+                        LineStyle::Solid,
+                        [circle],
+                    );
+                    if !node.label.is_empty() {
+                        plane = plane.add_text(BLACK, font_size, x, y, node.label.clone());
+                    }
+                }
+                plane
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                self.to_plane().to_vis_string(mode)
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct Color {
+            r: u8,
+            g: u8,
+            b: u8,
+        }
+
+        impl Color {
+            pub const fn new(r: u8, g: u8, b: u8) -> Self {
+                Self { r, g, b }
+            }
+        }
+
+        impl Display for Color {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+            }
+        }
+
+        impl From<&String> for Color {
+            fn from(s: &String) -> Self {
+                let s = s.trim_start_matches('#');
+                let r = u8::from_str_radix(&s[0..2], 16).unwrap_or(0);
+                let g = u8::from_str_radix(&s[2..4], 16).unwrap_or(0);
+                let b = u8::from_str_radix(&s[4..6], 16).unwrap_or(0);
+                Color::new(r, g, b)
+            }
+        }
+
+        // グリッド上のセルの位置。(x, y) と (row, col) の取り違えを防ぐため、
+        // row_col / xy のどちらかで明示的に作成する。タプル (x, y) からも変換できる
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub struct GridPos {
+            pub row: usize,
+            pub col: usize,
+        }
+
+        impl GridPos {
+            pub const fn row_col(row: usize, col: usize) -> Self {
+                Self { row, col }
+            }
+
+            pub const fn xy(x: usize, y: usize) -> Self {
+                Self { row: y, col: x }
+            }
+        }
+
+        impl From<(usize, usize)> for GridPos {
+            // 既存の API に合わせて (x, y) として解釈する
+            fn from(p: (usize, usize)) -> Self {
+                Self::xy(p.0, p.1)
+            }
+        }
+
+        impl Display for GridPos {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "(row {}, col {})", self.row, self.col)
+            }
+        }
+
+        // グリッドの範囲外の位置が指定されたときのエラー
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub struct GridPosError {
+            pub pos: GridPos,
+            pub h: usize,
+            pub w: usize,
+        }
+
+        impl Display for GridPosError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "position {} is out of range for {}x{} grid (H x W)",
+                    self.pos, self.h, self.w
+                )
+            }
+        }
+
+        impl std::error::Error for GridPosError {}
+
+        // グリッドの壁の位置 (行・列で指定)
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum WallPos {
+            // row 行目のセルの上側 (row == H なら最下段の下側)、col 列目のセルにかかる横向きの壁
+            Horizontal { row: usize, col: usize },
+            // row 行目のセルの、col 列目の左側 (col == W なら最右列の右側) にある縦向きの壁
+            Vertical { row: usize, col: usize },
+        }
+
+        impl WallPos {
+            // セル p の上側の壁
+            pub fn above(p: impl Into<GridPos>) -> Self {
+                let p = p.into();
+                WallPos::Horizontal {
+                    row: p.row,
+                    col: p.col,
+                }
+            }
+
+            // セル p の左側の壁
+            pub fn left_of(p: impl Into<GridPos>) -> Self {
+                let p = p.into();
+                WallPos::Vertical {
+                    row: p.row,
+                    col: p.col,
+                }
+            }
+
+            // 隣接する2つのセルの間の壁。隣接していない場合は None
+            // (タプルは VisGrid の他の API と同じく (x, y) として解釈する)
+            pub fn between(a: impl Into<GridPos>, b: impl Into<GridPos>) -> Option<Self> {
+                let (a, b) = (a.into(), b.into());
+                if a.col == b.col && a.row.abs_diff(b.row) == 1 {
+                    Some(WallPos::above(GridPos::row_col(a.row.max(b.row), a.col)))
+                } else if a.row == b.row && a.col.abs_diff(b.col) == 1 {
+                    Some(WallPos::left_of(GridPos::row_col(a.row, a.col.max(b.col))))
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl Display for WallPos {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    WallPos::Horizontal { row, col } => write!(f, "H {row} {col}"),
+                    WallPos::Vertical { row, col } => write!(f, "V {row} {col}"),
+                }
+            }
+        }
+
+        fn walls_from_strs<S: AsRef<str>>(walls: &[S]) -> Vec<Vec<bool>> {
+            walls
+                .iter()
+                .map(|line| line.as_ref().chars().map(|c| c == '1').collect())
+                .collect()
+        }
+
+        pub const WHITE: Color = Color::new(255, 255, 255);
+        pub const BLACK: Color = Color::new(0, 0, 0);
+        pub const GRAY: Color = Color::new(128, 128, 128);
+        pub const RED: Color = Color::new(255, 0, 0);
+        pub const BLUE: Color = Color::new(0, 0, 255);
+        pub const GREEN: Color = Color::new(0, 255, 0);
+        pub const YELLOW: Color = Color::new(255, 255, 0);
+        pub const CYAN: Color = Color::new(0, 255, 255);
+        pub const MAGENTA: Color = Color::new(255, 0, 255);
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum LineStyle {
+            Solid,
+            Dashed,
+            Dotted,
+        }
+
+        impl Display for LineStyle {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    LineStyle::Solid => "SOLID",
+                    LineStyle::Dashed => "DASHED",
+                    LineStyle::Dotted => "DOTTED",
+                };
+                write!(f, "{s}")
+            }
+        }
+
+        // 移動文字と (dx, dy) の対応。U は y が減る方向 (上) に進む
+        pub const MOVES_UDLR: [(char, (isize, isize)); 4] =
+            [('U', (0, -1)), ('D', (0, 1)), ('L', (-1, 0)), ('R', (1, 0))];
+
+        // start から moves の各文字に対応する方向に進んだときに通るセルの列を返す。
+        // alphabet に無い文字は無視し、座標が負になる移動は 0 で止める
+        pub fn moves_to_path(
+            start: (usize, usize),
+            moves: &str,
+            alphabet: &[(char, (isize, isize))],
+        ) -> Vec<(usize, usize)> {
+            walk_moves(start, moves, alphabet, (usize::MAX, usize::MAX))
+        }
+
+        // moves_to_path と同じだが、x < w, y < h の範囲を越える移動も端で止める
+        fn walk_moves(
+            start: (usize, usize),
+            moves: &str,
+            alphabet: &[(char, (isize, isize))],
+            (w, h): (usize, usize),
+        ) -> Vec<(usize, usize)> {
+            let mut path = vec![start];
+            let (mut x, mut y) = start;
+            for c in moves.chars() {
+                if let Some(&(_, (dx, dy))) = alphabet.iter().find(|(m, _)| *m == c) {
+                    x = x.saturating_add_signed(dx).min(w - 1);
+                    y = y.saturating_add_signed(dy).min(h - 1);
+                    path.push((x, y));
+                }
+            }
+            path
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum MarkerShape {
+            Circle,
+            Square,
+            Diamond,
+            Triangle,
+            Cross,
+            Star,
+        }
+
+        impl Display for MarkerShape {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    MarkerShape::Circle => "CIRCLE",
+                    MarkerShape::Square => "SQUARE",
+                    MarkerShape::Diamond => "DIAMOND",
+                    MarkerShape::Triangle => "TRIANGLE",
+                    MarkerShape::Cross => "CROSS",
+                    MarkerShape::Star => "STAR",
+                };
+                write!(f, "{s}")
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum TextAnchor {
+            Center,
+            Top,
+            Bottom,
+            Left,
+            Right,
+            TopLeft,
+            TopRight,
+            BottomLeft,
+            BottomRight,
+        }
+
+        impl Display for TextAnchor {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    TextAnchor::Center => "C",
+                    TextAnchor::Top => "T",
+                    TextAnchor::Bottom => "B",
+                    TextAnchor::Left => "L",
+                    TextAnchor::Right => "R",
+                    TextAnchor::TopLeft => "TL",
+                    TextAnchor::TopRight => "TR",
+                    TextAnchor::BottomLeft => "BL",
+                    TextAnchor::BottomRight => "BR",
+                };
+                write!(f, "{s}")
+            }
+        }
+
+        pub struct VisGridConf {
+            border_color: Color,
+            text_color: Color,
+            default_cell_color: Color,
+        }
+
+        impl VisGridConf {
+            pub const fn new(
+                border_color: Color,
+                text_color: Color,
+                default_cell_color: Color,
+            ) -> Self {
+                Self {
+                    border_color,
+                    text_color,
+                    default_cell_color,
+                }
+            }
+        }
+
+        impl Default for VisGridConf {
+            fn default() -> Self {
+                VisGridConf::new(BLACK, BLACK, WHITE)
+            }
+        }
+
+        pub struct BarGraphItem {
+            pub label: String,
+            pub value: f64,
+        }
+
+        impl BarGraphItem {
+            pub fn new(label: String, value: f64) -> Self {
+                Self { label, value }
+            }
+        }
+
+        pub struct VisBarGraph {
+            title: String,
+            fill_color: Color,
+            y_min: f64,
+            y_max: f64,
+            items: Vec<BarGraphItem>,
+        }
+
+        impl VisBarGraph {
+            pub fn new(title: String, fill_color: Color, y_min: f64, y_max: f64) -> Self {
+                Self {
+                    title,
+                    fill_color,
+                    y_min,
+                    y_max,
+                    items: Vec::new(),
+                }
+            }
+
+            pub fn add_item(mut self, label: String, value: f64) -> Self {
+                self.items.push(BarGraphItem::new(label, value));
+                self
+            }
+
+            pub fn add_items(mut self, items: Vec<BarGraphItem>) -> Self {
+                self.items.extend(items);
+                self
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+
+                let title_output = if self.title.contains(' ') || self.title.is_empty() {
+                    format!("\"{}\"", self.title)
+                } else {
+                    self.title.clone()
+                };
+
+                writeln!(
+                    &mut s,
+                    "$v({}) BAR_GRAPH {} {} {} {}",
+                    mode, title_output, self.fill_color, self.y_min, self.y_max
+                )
+                .unwrap();
+
+                // Output items count and data
+                write!(&mut s, "{}", self.items.len()).unwrap();
+                for item in &self.items {
+                    write!(&mut s, " {} {}", item.label, item.value).unwrap();
+                }
+                writeln!(&mut s).unwrap();
+
+                s
+            }
+        }
+
+        struct LineChartSeries {
+            name: String,
+            color: Color,
+            points: Vec<(f64, f64)>,
+        }
+
+        // 名前付きの系列を重ねて描く折れ線グラフ (スコアや温度の推移など)。
+        // 軸の範囲は指定しなければデータから自動で決める
+        pub struct VisLineChart {
+            title: String,
+            x_range: Option<(f64, f64)>,
+            y_range: Option<(f64, f64)>,
+            log_scale: bool,
+            marker: Option<f64>,
+            series: Vec<LineChartSeries>,
+        }
+
+        impl VisLineChart {
+            pub fn new(title: String) -> Self {
+                Self {
+                    title,
+                    x_range: None,
+                    y_range: None,
+                    log_scale: false,
+                    marker: None,
+                    series: Vec::new(),
+                }
+            }
+
+            pub fn set_x_range(mut self, min: f64, max: f64) -> Self {
+                self.x_range = Some((min, max));
+                self
+            }
+
+            pub fn set_y_range(mut self, min: f64, max: f64) -> Self {
+                self.y_range = Some((min, max));
+                self
+            }
+
+            // y 軸を対数目盛にする (0 以下の値は描画しない)
+            pub fn set_log_scale(mut self, log_scale: bool) -> Self {
+                self.log_scale = log_scale;
+                self
+            }
+
+            // 現在のフレームの位置 (x 座標) に縦線を引く
+            pub fn set_marker(mut self, x: f64) -> Self {
+                self.marker = Some(x);
+                self
+            }
+
+            // (x, y) の列を1つの系列として追加する
+            pub fn add_series(
+                mut self,
+                name: String,
+                color: Color,
+                points: impl IntoIterator<Item = (f64, f64)>,
+            ) -> Self {
+                self.series.push(LineChartSeries {
+                    name,
+                    color,
+                    points: points.into_iter().collect(),
+                });
+                self
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+
+                let quote = |text: &str| {
+                    if text.contains(' ') || text.is_empty() {
+                        format!("\"{text}\"")
+                    } else {
+                        text.to_string()
+                    }
+                };
+                // 範囲を指定していない軸は AUTO
+                let range = |range: Option<(f64, f64)>| match range {
+                    Some((min, max)) => format!("{min} {max}"),
+                    None => "AUTO AUTO".to_string(),
+                };
+                let marker = self.marker.map_or("-".to_string(), |x| x.to_string());
+                writeln!(
+                    &mut s,
+                    "$v({}) LINE_CHART {} {} {} {} {}",
+                    mode,
+                    quote(&self.title),
+                    range(self.x_range),
+                    range(self.y_range),
+                    if self.log_scale { "LOG" } else { "LINEAR" },
+                    marker
+                )
+                .unwrap();
+
+                // 系列ごとに 名前 色 点数 x1 y1 x2 y2 ...
+                writeln!(&mut s, "{}", self.series.len()).unwrap();
+                for series in &self.series {
+                    write!(
+                        &mut s,
+                        "{} {} {}",
+                        quote(&series.name),
+                        series.color,
+                        series.points.len()
+                    )
+                    .unwrap();
+                    for (x, y) in &series.points {
+                        write!(&mut s, " {x} {y}").unwrap();
+                    }
+                    writeln!(&mut s).unwrap();
+                }
+
+                s
+            }
+        }
+
+        // ヒストグラムのビンの決め方
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub enum HistogramBins {
+            // 範囲を指定した個数に等分する
+            Count(usize),
+            // 指定した幅のビンを幅の倍数の位置から並べる
+            Width(f64),
+            // Freedman–Diaconis 則 (幅 2 * IQR / n^(1/3))
+            FreedmanDiaconis,
+        }
+
+        // ビンが多すぎると描画できないので上限を設ける
+        const HISTOGRAM_MAX_BINS: usize = 200;
+
+        // 生のサンプルからビンと要約統計量を計算して表示するヒストグラム。
+        // 全サンプルの統計量 (個数, 平均, 標準偏差, 分位点) をグラフの下に表示する
+        pub struct VisHistogram {
+            title: String,
+            color: Color,
+            bins: HistogramBins,
+            range: Option<(f64, f64)>,
+            samples: Vec<f64>,
+        }
+
+        impl VisHistogram {
+            pub fn new(title: String, color: Color) -> Self {
+                Self {
+                    title,
+                    color,
+                    bins: HistogramBins::FreedmanDiaconis,
+                    range: None,
+                    samples: Vec::new(),
+                }
+            }
+
+            // ビンの個数や幅が正でないときは警告して、それまでの設定のままにする
+            pub fn set_bins(mut self, bins: HistogramBins) -> Self {
+                match bins {
+                    HistogramBins::Count(0) => {
+                        eprintln!(
+                            "warning: VisHistogram::set_bins: bin count must be positive; the call is ignored"
+                        );
+                        return self;
+                    }
+                    HistogramBins::Width(w) if !(w > 0.0 && w.is_finite()) => {
+                        eprintln!(
+                            "warning: VisHistogram::set_bins: bin width must be positive, got {w}; the call is ignored"
+                        );
+                        return self;
+                    }
+                    _ => {}
+                }
+                self.bins = bins;
+                self
+            }
+
+            // ビンを作る範囲を固定する (フレーム間で軸を揃えたいとき)。範囲外のサンプルは統計量にだけ使う
+            // 範囲が空のとき (サンプルがすべて等しいときの min, max など) は警告して、それまでの範囲のままにする
+            pub fn set_range(mut self, min: f64, max: f64) -> Self {
+                if !(min < max && min.is_finite() && max.is_finite()) {
+                    eprintln!(
+                        "warning: VisHistogram::set_range: empty range [{min}, {max}]; the call is ignored"
+                    );
+                    return self;
+                }
+                self.range = Some((min, max));
+                self
+            }
+
+            // NaN と ±inf はビンを作れないので無視する
+            pub fn add_sample(mut self, value: f64) -> Self {
+                if value.is_finite() {
+                    self.samples.push(value);
+                }
+                self
+            }
+
+            pub fn add_samples(mut self, values: impl IntoIterator<Item = f64>) -> Self {
+                self.samples
+                    .extend(values.into_iter().filter(|value| value.is_finite()));
+                self
+            }
+
+            fn sorted_samples(&self) -> Vec<f64> {
+                let mut sorted = self.samples.clone();
+                sorted.sort_by(f64::total_cmp);
+                sorted
+            }
+
+            // ソート済みの列の分位点 (線形補間)
+            fn quantile(sorted: &[f64], q: f64) -> f64 {
+                let pos = q * (sorted.len() - 1) as f64;
+                let i = pos.floor() as usize;
+                let j = (i + 1).min(sorted.len() - 1);
+                sorted[i] + (sorted[j] - sorted[i]) * (pos - i as f64)
+            }
+
+            // ビンの境界 (個数 + 1 個) と各ビンの度数。最後のビンだけ右端を含む
+            fn compute_bins(&self, sorted: &[f64]) -> (Vec<f64>, Vec<usize>) {
+                let (lo, hi) = match (self.range, sorted.first(), sorted.last()) {
+                    (Some(range), _, _) => range,
+                    (None, Some(&first), Some(&last)) => (first, last),
+                    _ => return (Vec::new(), Vec::new()),
+                };
+                let span = hi - lo;
+                let (start, width, count) = match self.bins {
+                    HistogramBins::Count(n) if span > 0.0 => (lo, span / n as f64, n),
+                    HistogramBins::Count(_) => (lo - 0.5, 1.0, 1),
+                    HistogramBins::Width(w) => {
+                        let start = (lo / w).floor() * w;
+                        (start, w, ((hi - start) / w).ceil() as usize)
+                    }
+                    HistogramBins::FreedmanDiaconis => {
+                        let n = sorted.len();
+                        let iqr = if n > 0 {
+                            Self::quantile(sorted, 0.75) - Self::quantile(sorted, 0.25)
+                        } else {
+                            0.0
+                        };
+                        let width = 2.0 * iqr / (n as f64).cbrt();
+                        if span <= 0.0 {
+                            (lo - 0.5, 1.0, 1)
+                        } else if width > 0.0 {
+                            (lo, width, (span / width).ceil() as usize)
+                        } else {
+                            // IQR が 0 のときは Sturges 則で個数を決める
+                            let count = (n.max(1) as f64).log2().ceil() as usize + 1;
+                            (lo, span / count as f64, count)
+                        }
+                    }
+                };
+                // 指定されたビンが多すぎるときは幅を広げるので、そのことを警告する
+                if count > HISTOGRAM_MAX_BINS && self.bins != HistogramBins::FreedmanDiaconis {
+                    eprintln!(
+                        "warning: VisHistogram: {count} bins exceed the limit of {HISTOGRAM_MAX_BINS}; the bin width is widened"
+                    );
+                }
+                let count = count.clamp(1, HISTOGRAM_MAX_BINS);
+                let width = width.max((hi - start) / count as f64);
+                let edges: Vec<f64> = (0..=count).map(|i| start + width * i as f64).collect();
+
+                let mut counts = vec![0; count];
+                for &value in sorted {
+                    if value < lo || value > hi {
+                        continue;
+                    }
+                    let i = (((value - start) / width).floor().max(0.0) as usize).min(count - 1);
+                    counts[i] += 1;
+                }
+                (edges, counts)
+            }
+
+            // ビンの左端をラベルにした棒グラフに変換する
+            pub fn to_bar_graph(&self) -> VisBarGraph {
+                let (edges, counts) = self.compute_bins(&self.sorted_samples());
+                let y_max = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+                // ビン幅の有効数字に合わせて丸める
+                let digits = edges
+                    .get(1)
+                    .map_or(0, |e| (-(e - edges[0]).log10().floor()).max(0.0) as i32 + 1);
+                let scale = 10f64.powi(digits);
+                let mut bar_graph = VisBarGraph::new(self.title.clone(), self.color, 0.0, y_max);
+                for (edge, count) in edges.iter().zip(&counts) {
+                    let label = ((edge * scale).round() / scale).to_string();
+                    bar_graph = bar_graph.add_item(label, *count as f64);
+                }
+                bar_graph
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+                let sorted = self.sorted_samples();
+                let (edges, counts) = self.compute_bins(&sorted);
+
+                let title_output = if self.title.contains(' ') || self.title.is_empty() {
+                    format!("\"{}\"", self.title)
+                } else {
+                    self.title.clone()
+                };
+                writeln!(
+                    &mut s,
+                    "$v({}) HISTOGRAM {} {}",
+                    mode, title_output, self.color
+                )
+                .unwrap();
+
+                // ビン数 境界 (ビン数 + 1 個) 度数 (ビン数個)
+                write!(&mut s, "{}", counts.len()).unwrap();
+                for edge in &edges {
+                    write!(&mut s, " {edge}").unwrap();
+                }
+                for count in &counts {
+                    write!(&mut s, " {count}").unwrap();
+                }
+                writeln!(&mut s).unwrap();
+
+                // 個数 平均 標準偏差 最小 25% 中央値 75% 95% 最大 (サンプルが無ければ 0 のみ)
+                write!(&mut s, "{}", sorted.len()).unwrap();
+                if !sorted.is_empty() {
+                    let n = sorted.len() as f64;
+                    let mean = sorted.iter().sum::<f64>() / n;
+                    let var = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+                    write!(&mut s, " {} {}", mean, var.sqrt()).unwrap();
+                    for q in [0.0, 0.25, 0.5, 0.75, 0.95, 1.0] {
+                        write!(&mut s, " {}", Self::quantile(&sorted, q)).unwrap();
+                    }
+                }
+                writeln!(&mut s).unwrap();
+
+                s
+            }
+        }
+
+        enum TableValue {
+            Int(i64),
+            Float(f64, usize),
+            Text(String),
+        }
+
+        // 表のセル。数値のセルは右寄せで表示する
+        pub struct TableCell {
+            value: TableValue,
+            color: Option<Color>,
+        }
+
+        impl TableCell {
+            pub fn int(value: i64) -> Self {
+                Self {
+                    value: TableValue::Int(value),
+                    color: None,
+                }
+            }
+
+            // 小数点以下 precision 桁で表示する
+            pub fn float(value: f64, precision: usize) -> Self {
+                Self {
+                    value: TableValue::Float(value, precision),
+                    color: None,
+                }
+            }
+
+            pub fn text(value: String) -> Self {
+                Self {
+                    value: TableValue::Text(value),
+                    color: None,
+                }
+            }
+
+            // セルの背景色 (強調表示用)
+            pub fn with_color(mut self, color: Color) -> Self {
+                self.color = Some(color);
+                self
+            }
+        }
+
+        // ヘッダ行つきの表。key_value で作るとヘッダの無い 2 列の表になる
+        pub struct VisTable {
+            title: String,
+            header: Option<Vec<String>>,
+            columns: usize,
+            rows: Vec<Vec<TableCell>>,
+        }
+
+        impl VisTable {
+            pub fn new(title: String, header: Vec<String>) -> Self {
+                let columns = header.len();
+                Self {
+                    title,
+                    header: Some(header),
+                    columns,
+                    rows: Vec::new(),
+                }
+            }
+
+            pub fn key_value(title: String) -> Self {
+                Self {
+                    title,
+                    header: None,
+                    columns: 2,
+                    rows: Vec::new(),
+                }
+            }
+
+            pub fn add_row(mut self, cells: impl IntoIterator<Item = TableCell>) -> Self {
+                // 列数が合わないときは警告して、空のセルで埋めるか余りを捨てる
+                let mut row: Vec<TableCell> = cells.into_iter().collect();
+                if row.len() != self.columns {
+                    eprintln!(
+                        "warning: VisTable::add_row: expected {} cells, got {}; the row is padded or truncated",
+                        self.columns,
+                        row.len()
+                    );
+                    row.resize_with(self.columns, || TableCell::text(String::new()));
+                }
+                self.rows.push(row);
+                self
+            }
+
+            // key_value の表に 1 行追加する
+            pub fn add_entry(self, key: String, value: TableCell) -> Self {
+                self.add_row([TableCell::text(key), value])
+            }
+
+            pub fn to_vis_string(&self, mode: &str) -> String {
+                let mut s = String::new();
+
+                let quote = |text: &str| {
+                    if text.contains(' ') || text.is_empty() {
+                        format!("\"{text}\"")
+                    } else {
+                        text.to_string()
+                    }
+                };
+                writeln!(
+                    &mut s,
+                    "$v({}) TABLE {} {} {} {}",
+                    mode,
+                    quote(&self.title),
+                    self.rows.len(),
+                    self.columns,
+                    if self.header.is_some() {
+                        "HEADER"
+                    } else {
+                        "NOHEADER"
+                    }
+                )
+                .unwrap();
+
+                if let Some(header) = &self.header {
+                    let header: Vec<String> = header.iter().map(|h| quote(h)).collect();
+                    writeln!(&mut s, "{}", header.join(" ")).unwrap();
+                }
+
+                // セルごとに 型 (I|F|T) 背景色 (無ければ -) 値
+                for row in &self.rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|cell| {
+                            let color = cell.color.map_or("-".to_string(), |c| c.to_string());
+                            match &cell.value {
+                                TableValue::Int(v) => format!("I {color} {v}"),
+                                TableValue::Float(v, precision) => {
+                                    format!("F {color} {v:.precision$}")
+                                }
+                                TableValue::Text(v) => format!("T {color} {}", quote(v)),
+                            }
+                        })
+                        .collect();
+                    writeln!(&mut s, "{}", cells.join(" ")).unwrap();
+                }
+
+                s
+            }
+        }
+    }
+
+    // ============================================================
+    // vis feature が無効な場合の空実装 (Zero-cost)
+    // ============================================================
+    #[cfg(not(feature = "vis"))]
+    mod vis_disabled {
+        use std::fmt::Display;
+        use std::ops::Range;
+        use std::path::PathBuf;
+
+        // Dummy type for API compatibility
+        pub enum OutputDestination {
+            Stderr,
+            File(PathBuf),
+        }
+
+        // VisTextArea - Zero-Sized Type
+        pub struct VisTextArea;
+
+        impl VisTextArea {
+            #[inline(always)]
+            pub fn new(_title: String, _text: String) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn height(self, _height: u32) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn text_color(self, _color: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn fill_color(self, _color: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        // VisRoot - Zero-Sized Type
+        pub struct VisRoot;
+
+        impl Default for VisRoot {
+            #[inline(always)]
+            fn default() -> Self {
+                Self
+            }
+        }
+
+        impl VisRoot {
+            #[inline(always)]
+            pub fn new() -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn new_with_file<P: Into<PathBuf>>(_path: P) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn add_frame(&mut self, _mode: &str, _frame: VisFrame) -> &mut Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_frames(&mut self, _mode: &str, _frames: Vec<VisFrame>) -> &mut Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn get_frames(&self, _mode: &str) -> Option<&Vec<VisFrame>> {
+                None
+            }
+
+            #[inline(always)]
+            pub fn output_all(&self) {}
+        }
+
+        // VisFrame - Zero-Sized Type
+        pub struct VisFrame;
+
+        impl VisFrame {
+            #[inline(always)]
+            pub fn new() -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn new_grid<T: ToString>(_grid: VisGrid, _score: T) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn new_2d_plane<T: ToString>(_plane: Vis2DPlane, _score: T) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn set_canvas(self, _canvas: VisCanvas) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_grid(self, _grid: VisGrid) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_2d_plane(self, _plane: Vis2DPlane) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_hex_grid(self, _grid: VisHexGrid) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_tri_grid(self, _grid: VisTriGrid) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_graph(self, _graph: VisGraph) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_tree(self, _tree: VisTree) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_item(self, _item: VisItem) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_score(self, _score: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_textarea(self, _textarea: VisTextArea) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_bar_graph(self, _bar_graph: VisBarGraph) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_line_chart(self, _line_chart: VisLineChart) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_histogram(self, _histogram: VisHistogram) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_table(self, _table: VisTable) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn enable_debug(self) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn disable_debug(self) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        impl Default for VisFrame {
+            #[inline(always)]
+            fn default() -> Self {
+                Self
+            }
+        }
+
+        // ItemBounds - Zero-Sized Type
+        #[derive(Clone, Copy)]
+        pub struct ItemBounds;
+
+        impl ItemBounds {
+            #[inline(always)]
+            pub fn new(_left: f64, _top: f64, _right: f64, _bottom: f64) -> Self {
+                Self
+            }
+        }
+
+        // VisCanvas - Zero-Sized Type
+        pub struct VisCanvas;
+
+        impl VisCanvas {
+            #[inline(always)]
+            pub fn new(_h: f64, _w: f64) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        impl Default for VisCanvas {
+            #[inline(always)]
+            fn default() -> Self {
+                Self
+            }
+        }
+
+        // VisItem - Zero-Sized Type
+        pub enum VisItem {
+            Grid(VisGrid),
+            Plane(Vis2DPlane),
+            HexGrid(VisHexGrid),
+            TriGrid(VisTriGrid),
+            Graph(VisGraph),
+            Tree(VisTree),
+        }
+
+        impl VisItem {
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum HexOrientation {
+            Pointy,
+            Flat,
+        }
+
+        impl Display for HexOrientation {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    HexOrientation::Pointy => write!(f, "POINTY"),
+                    HexOrientation::Flat => write!(f, "FLAT"),
+                }
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum HexPos {
+            Offset { row: usize, col: usize },
+            Axial { q: isize, r: isize },
+        }
+
+        impl From<GridPos> for HexPos {
+            #[inline(always)]
+            fn from(p: GridPos) -> Self {
+                HexPos::Offset {
+                    row: p.row,
+                    col: p.col,
+                }
+            }
+        }
+
+        impl From<(usize, usize)> for HexPos {
+            #[inline(always)]
+            fn from(p: (usize, usize)) -> Self {
+                GridPos::from(p).into()
+            }
+        }
+
+        pub const HEX_DIRECTIONS: [(isize, isize); 6] =
+            [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub struct HexPosError {
+            pub pos: HexPos,
+            pub h: usize,
+            pub w: usize,
+        }
+
+        impl Display for HexPosError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "position {:?} is out of range for {}x{} grid (H x W)",
+                    self.pos, self.h, self.w
+                )
+            }
+        }
+
+        impl std::error::Error for HexPosError {}
+
+        // VisHexGrid - Zero-Sized Type
+        pub struct VisHexGrid;
+
+        impl VisHexGrid {
+            #[inline(always)]
+            pub fn new(
+                _h: usize,
+                _w: usize,
+                _orientation: HexOrientation,
+                _bounds: Option<ItemBounds>,
+            ) -> Self {
+                VisHexGrid
+            }
+
+            #[inline(always)]
+            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn clip_out_of_range(self, _enabled: bool) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn neighbor(&self, _p: impl Into<HexPos>, _dir: usize) -> Option<GridPos> {
+                None
+            }
+
+            #[inline(always)]
+            pub fn update_cell_color(self, _p: impl Into<HexPos>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_cell_color(
+                self,
+                _p: impl Into<HexPos>,
+                _color: Color,
+            ) -> Result<Self, HexPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn update_text(self, _p: impl Into<HexPos>, _text: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_text(
+                self,
+                _p: impl Into<HexPos>,
+                _text: String,
+            ) -> Result<Self, HexPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn add_line<P: Into<HexPos>>(self, _line: Vec<P>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_add_line<P: Into<HexPos>>(
+                self,
+                _line: Vec<P>,
+                _color: Color,
+            ) -> Result<Self, HexPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn remove_edge(self, _p: impl Into<HexPos>, _dir: usize) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_remove_edge(
+                self,
+                _p: impl Into<HexPos>,
+                _dir: usize,
+            ) -> Result<Self, HexPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn remove_edge_between(self, _a: impl Into<HexPos>, _b: impl Into<HexPos>) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        // VisTriGrid - Zero-Sized Type
+        pub struct VisTriGrid;
+
+        impl VisTriGrid {
+            #[inline(always)]
+            pub fn new(_h: usize, _w: usize, _bounds: Option<ItemBounds>) -> Self {
+                VisTriGrid
+            }
+
+            #[inline(always)]
+            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn clip_out_of_range(self, _enabled: bool) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn is_up(_p: impl Into<GridPos>) -> bool {
+                false
+            }
+
+            #[inline(always)]
+            pub fn neighbor(&self, _p: impl Into<GridPos>, _dir: usize) -> Option<GridPos> {
+                None
+            }
+
+            #[inline(always)]
+            pub fn update_cell_color(self, _p: impl Into<GridPos>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_cell_color(
+                self,
+                _p: impl Into<GridPos>,
+                _color: Color,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn update_text(self, _p: impl Into<GridPos>, _text: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_text(
+                self,
+                _p: impl Into<GridPos>,
+                _text: String,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn add_line<P: Into<GridPos>>(self, _line: Vec<P>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_add_line<P: Into<GridPos>>(
+                self,
+                _line: Vec<P>,
+                _color: Color,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn remove_edge(self, _p: impl Into<GridPos>, _dir: usize) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_remove_edge(
+                self,
+                _p: impl Into<GridPos>,
+                _dir: usize,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn remove_edge_between(
+                self,
+                _a: impl Into<GridPos>,
+                _b: impl Into<GridPos>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        // Vis2DPlane - Zero-Sized Type
+        pub struct Vis2DPlane;
+
+        impl Vis2DPlane {
+            #[inline(always)]
+            pub fn new(_h: f64, _w: f64, _bounds: Option<ItemBounds>) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_bounds(_h: f64, _w: f64, _bounds: ItemBounds) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_layer(self, _z: i32) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_view(self, _min_x: f64, _max_x: f64, _min_y: f64, _max_y: f64) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_y_up(self, _y_up: bool) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_keep_aspect(self, _keep_aspect: bool) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_circle_group<C: Into<Circle>>(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _circles: impl IntoIterator<Item = C>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_styled_circle_group<C: Into<Circle>>(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _circles: impl IntoIterator<Item = C>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_styled_circle(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _x: f64,
+                _y: f64,
+                _r: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_circle(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _x: f64,
+                _y: f64,
+                _r: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_circle_with_tooltip(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _x: f64,
+                _y: f64,
+                _r: f64,
+                _tooltip: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_line_group(
+                self,
+                _color: Color,
+                _width: f64,
+                _points: Vec<(f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_line(
+                self,
+                _color: Color,
+                _width: f64,
+                _ax: f64,
+                _ay: f64,
+                _bx: f64,
+                _by: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_styled_line(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _ax: f64,
+                _ay: f64,
+                _bx: f64,
+                _by: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_polyline(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _points: Vec<(f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_arrow(
+                self,
+                _color: Color,
+                _width: f64,
+                _head_size: f64,
+                _ax: f64,
+                _ay: f64,
+                _bx: f64,
+                _by: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_arrow_group(
+                self,
+                _color: Color,
+                _width: f64,
+                _head_size: f64,
+                _arrows: impl IntoIterator<Item = ((f64, f64), (f64, f64))>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_ellipse(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _cx: f64,
+                _cy: f64,
+                _rx: f64,
+                _ry: f64,
+                _angle: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_ellipse_group(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _ellipses: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_arc(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _cx: f64,
+                _cy: f64,
+                _r: f64,
+                _start: f64,
+                _end: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_arc_group(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _arcs: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_sector(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _cx: f64,
+                _cy: f64,
+                _r: f64,
+                _start: f64,
+                _end: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_sector_group(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _sectors: impl IntoIterator<Item = (f64, f64, f64, f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_quadratic_bezier(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _p0: (f64, f64),
+                _c: (f64, f64),
+                _p1: (f64, f64),
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_cubic_bezier(
+                self,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _p0: (f64, f64),
+                _c0: (f64, f64),
+                _c1: (f64, f64),
+                _p1: (f64, f64),
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_polygon(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _vertices: Vec<(f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_polygon_with_tooltip(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _vertices: Vec<(f64, f64)>,
+                _tooltip: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_styled_polygon(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _stroke_width: f64,
+                _style: LineStyle,
+                _vertices: Vec<(f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_polygon_groups(
+                self,
+                _polygons: impl IntoIterator<Item = PolygonGroup>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_rect(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _x: f64,
+                _y: f64,
+                _w: f64,
+                _h: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            #[allow(clippy::too_many_arguments)]
+            pub fn add_labeled_rect(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _x: f64,
+                _y: f64,
+                _w: f64,
+                _h: f64,
+                _label: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_rect_group<R: Into<Rect>>(
+                self,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _rects: impl IntoIterator<Item = R>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_point(
+                self,
+                _shape: MarkerShape,
+                _color: Color,
+                _size: f64,
+                _x: f64,
+                _y: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_point_group(
+                self,
+                _shape: MarkerShape,
+                _color: Color,
+                _size: f64,
+                _points: impl IntoIterator<Item = (f64, f64)>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_text(
+                self,
+                _color: Color,
+                _font_size: f64,
+                _x: f64,
+                _y: f64,
+                _text: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_text_group<T: Into<TextItem>>(
+                self,
+                _color: Color,
+                _font_size: f64,
+                _texts: impl IntoIterator<Item = T>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_styled_text(
+                self,
+                _color: Color,
+                _font_size: f64,
+                _style: TextStyle,
+                _x: f64,
+                _y: f64,
+                _text: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_styled_text_group<T: Into<TextItem>>(
+                self,
+                _color: Color,
+                _font_size: f64,
+                _style: TextStyle,
+                _texts: impl IntoIterator<Item = T>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        // VisGrid - Zero-Sized Type
+        pub struct VisGrid;
+
+        impl VisGrid {
+            #[inline(always)]
+            pub fn new(_h: usize, _w: usize, _bounds: Option<ItemBounds>) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_bounds(_h: usize, _w: usize, _bounds: ItemBounds) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn clip_out_of_range(self, _enabled: bool) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn show_axis_labels(self, _every: usize) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_row_labels(self, _labels: Vec<String>) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_col_labels(self, _labels: Vec<String>) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_viewport(self, _rows: Range<usize>, _cols: Range<usize>) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn follow(self, _center: impl Into<GridPos>, _rows: usize, _cols: usize) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn update_cell_color(self, _p: impl Into<GridPos>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_cell_color(
+                self,
+                _p: impl Into<GridPos>,
+                _color: Color,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn update_text(self, _p: impl Into<GridPos>, _text: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_text(
+                self,
+                _p: impl Into<GridPos>,
+                _text: String,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn update_text_style(
+                self,
+                _p: impl Into<GridPos>,
+                _color: Color,
+                _font_size: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_update_text_style(
+                self,
+                _p: impl Into<GridPos>,
+                _color: Color,
+                _font_size: f64,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn add_cell_text(
+                self,
+                _p: impl Into<GridPos>,
+                _anchor: TextAnchor,
+                _color: Color,
+                _font_size: f64,
+                _text: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_add_cell_text(
+                self,
+                _p: impl Into<GridPos>,
+                _anchor: TextAnchor,
+                _color: Color,
+                _font_size: f64,
+                _text: String,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn set_tooltip(self, _p: impl Into<GridPos>, _tooltip: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_line<P: Into<GridPos>>(self, _line: Vec<P>, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_add_line<P: Into<GridPos>>(
+                self,
+                _line: Vec<P>,
+                _color: Color,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn add_polyline<P: Into<GridPos>>(
+                self,
+                _points: Vec<P>,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_move_path(
+                self,
+                _start: impl Into<GridPos>,
+                _moves: &str,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_arrow(
+                self,
+                _from: impl Into<GridPos>,
+                _to: impl Into<GridPos>,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_labeled_arrow(
+                self,
+                _from: impl Into<GridPos>,
+                _to: impl Into<GridPos>,
+                _color: Color,
+                _width: f64,
+                _style: LineStyle,
+                _label: String,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_marker(
+                self,
+                _p: impl Into<GridPos>,
+                _shape: MarkerShape,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _size: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn try_add_marker(
+                self,
+                _p: impl Into<GridPos>,
+                _shape: MarkerShape,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _size: f64,
+            ) -> Result<Self, GridPosError> {
+                Ok(self)
+            }
+
+            #[inline(always)]
+            pub fn add_markers<P: Into<GridPos>>(
+                self,
+                _positions: Vec<P>,
+                _shape: MarkerShape,
+                _stroke_color: Color,
+                _fill_color: Color,
+                _size: f64,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn remove_wall(self, _wall: WallPos) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_wall(self, _wall: WallPos) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_wall_style(self, _wall: WallPos, _color: Color, _width: f64) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_vertical_walls(self, _walls: &[Vec<bool>]) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_horizontal_walls(self, _walls: &[Vec<bool>]) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_vertical_walls_from_strs<S: AsRef<str>>(self, _walls: &[S]) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn set_horizontal_walls_from_strs<S: AsRef<str>>(self, _walls: &[S]) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn remove_wall_vertical(self, _p: (usize, usize)) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_wall_vertical(self, _p: (usize, usize)) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn remove_wall_horizontal(self, _p: (usize, usize)) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn add_wall_horizontal(self, _p: (usize, usize)) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn from_cells<T>(_cell_texts: &[Vec<T>]) -> Self
+            where
+                T: ToString,
+            {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode_name: &str) -> String {
+                String::new()
+            }
+        }
+
+        // Helper structs - Zero-Sized Types
+        pub struct CircleGroup;
+        pub struct Circle;
+        pub struct LineGroup;
+        pub struct Line;
+        pub struct PolygonGroup;
+        pub struct TextItem;
+        #[derive(Clone, Copy)]
+        pub struct TextStyle;
+        pub struct Rect;
+        pub struct VisGridConf;
+
+        impl Circle {
+            #[inline(always)]
+            pub fn new(_x: f64, _y: f64, _r: f64) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_tooltip(self, _tooltip: String) -> Self {
+                self
+            }
+        }
+
+        impl From<(f64, f64, f64)> for Circle {
+            #[inline(always)]
+            fn from(_: (f64, f64, f64)) -> Self {
+                Self
+            }
+        }
+
+        impl PolygonGroup {
+            #[inline(always)]
+            pub fn new(
+                _stroke_color: Color,
+                _fill_color: Color,
+                _vertices: Vec<(f64, f64)>,
+            ) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_stroke(self, _width: f64, _style: LineStyle) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_tooltip(self, _tooltip: String) -> Self {
+                self
+            }
+        }
+
+        impl Rect {
+            #[inline(always)]
+            pub fn new(_x: f64, _y: f64, _w: f64, _h: f64) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_label(self, _label: String) -> Self {
+                self
+            }
+        }
+
+        impl From<(f64, f64, f64, f64)> for Rect {
+            #[inline(always)]
+            fn from(_: (f64, f64, f64, f64)) -> Self {
+                Self
+            }
+        }
+
+        impl TextItem {
+            #[inline(always)]
+            pub fn new(_x: f64, _y: f64, _text: String) -> Self {
+                Self
+            }
+        }
+
+        impl From<(f64, f64, String)> for TextItem {
+            #[inline(always)]
+            fn from(_: (f64, f64, String)) -> Self {
+                Self
+            }
+        }
+
+        impl From<(f64, f64, &str)> for TextItem {
+            #[inline(always)]
+            fn from(_: (f64, f64, &str)) -> Self {
+                Self
+            }
+        }
+
+        impl Default for TextStyle {
+            #[inline(always)]
+            fn default() -> Self {
+                Self
+            }
+        }
+
+        impl TextStyle {
+            #[inline(always)]
+            pub fn new() -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_anchor(self, _anchor: TextAnchor) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_rotation(self, _degrees: f64) -> Self {
+                self
             }
-        }
 
-        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
-        pub struct Color {
-            r: u8,
-            g: u8,
-            b: u8,
-        }
+            #[inline(always)]
+            pub fn with_background(self, _color: Color, _padding: f64) -> Self {
+                self
+            }
 
-        impl Color {
-            pub const fn new(r: u8, g: u8, b: u8) -> Self {
-                Self { r, g, b }
+            #[inline(always)]
+            pub fn with_offset(self, _dx: f64, _dy: f64) -> Self {
+                self
             }
         }
 
-        impl Display for Color {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+        impl Default for VisGridConf {
+            #[inline(always)]
+            fn default() -> Self {
+                Self
             }
         }
 
-        impl From<&String> for Color {
-            fn from(s: &String) -> Self {
-                let s = s.trim_start_matches('#');
-                let r = u8::from_str_radix(&s[0..2], 16).unwrap_or(0);
-                let g = u8::from_str_radix(&s[2..4], 16).unwrap_or(0);
-                let b = u8::from_str_radix(&s[4..6], 16).unwrap_or(0);
-                Color::new(r, g, b)
+        impl VisGridConf {
+            #[inline(always)]
+            pub const fn new(
+                _border_color: Color,
+                _text_color: Color,
+                _default_cell_color: Color,
+            ) -> Self {
+                Self
             }
         }
 
-        pub const WHITE: Color = Color::new(255, 255, 255);
-        pub const BLACK: Color = Color::new(0, 0, 0);
-        pub const GRAY: Color = Color::new(128, 128, 128);
-        pub const RED: Color = Color::new(255, 0, 0);
-        pub const BLUE: Color = Color::new(0, 0, 255);
-        pub const GREEN: Color = Color::new(0, 255, 0);
-        pub const YELLOW: Color = Color::new(255, 255, 0);
-        pub const CYAN: Color = Color::new(0, 255, 255);
-        pub const MAGENTA: Color = Color::new(255, 0, 255);
-
-        pub struct VisGridConf {
-            border_color: Color,
-            text_color: Color,
-            default_cell_color: Color,
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum GraphLayout {
+            ForceDirected,
+            Circular,
+            Layered,
         }
 
-        impl VisGridConf {
-            pub const fn new(
-                border_color: Color,
-                text_color: Color,
-                default_cell_color: Color,
-            ) -> Self {
-                Self {
-                    border_color,
-                    text_color,
-                    default_cell_color,
-                }
+        // VisGraph - Zero-Sized Type
+        pub struct VisGraph;
+        pub struct GraphNode;
+        pub struct GraphEdge;
+
+        impl GraphNode {
+            #[inline(always)]
+            pub fn new(_id: usize) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_color(self, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_label(self, _label: String) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_pos(self, _x: f64, _y: f64) -> Self {
+                self
             }
         }
 
-        impl Default for VisGridConf {
-            fn default() -> Self {
-                VisGridConf::new(BLACK, BLACK, WHITE)
+        impl From<usize> for GraphNode {
+            #[inline(always)]
+            fn from(_: usize) -> Self {
+                Self
             }
         }
 
-        pub struct BarGraphItem {
-            pub label: String,
-            pub value: f64,
+        impl GraphEdge {
+            #[inline(always)]
+            pub fn new(_u: usize, _v: usize) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn with_weight(self, _weight: f64) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_color(self, _color: Color) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn with_label(self, _label: String) -> Self {
+                self
+            }
         }
 
-        impl BarGraphItem {
-            pub fn new(label: String, value: f64) -> Self {
-                Self { label, value }
+        impl From<(usize, usize)> for GraphEdge {
+            #[inline(always)]
+            fn from(_: (usize, usize)) -> Self {
+                Self
             }
         }
 
-        pub struct VisBarGraph {
-            title: String,
-            fill_color: Color,
-            y_min: f64,
-            y_max: f64,
-            items: Vec<BarGraphItem>,
+        impl From<(usize, usize, f64)> for GraphEdge {
+            #[inline(always)]
+            fn from(_: (usize, usize, f64)) -> Self {
+                Self
+            }
         }
 
-        impl VisBarGraph {
-            pub fn new(title: String, fill_color: Color, y_min: f64, y_max: f64) -> Self {
-                Self {
-                    title,
-                    fill_color,
-                    y_min,
-                    y_max,
-                    items: Vec::new(),
-                }
+        impl VisGraph {
+            #[inline(always)]
+            pub fn new(_h: f64, _w: f64, _bounds: Option<ItemBounds>) -> Self {
+                VisGraph
             }
 
-            pub fn add_item(mut self, label: String, value: f64) -> Self {
-                self.items.push(BarGraphItem::new(label, value));
+            #[inline(always)]
+            pub fn set_layout(self, _layout: GraphLayout) -> Self {
                 self
             }
 
-            pub fn add_items(mut self, items: Vec<BarGraphItem>) -> Self {
-                self.items.extend(items);
+            #[inline(always)]
+            pub fn set_directed(self, _directed: bool) -> Self {
                 self
             }
 
-            pub fn to_vis_string(&self, mode: &str) -> String {
-                let mut s = String::new();
+            #[inline(always)]
+            pub fn set_node_radius(self, _r: f64) -> Self {
+                self
+            }
 
-                let title_output = if self.title.contains(' ') || self.title.is_empty() {
-                    format!("\"{}\"", self.title)
-                } else {
-                    self.title.clone()
-                };
+            #[inline(always)]
+            pub fn add_node<N: Into<GraphNode>>(self, _node: N) -> Self {
+                self
+            }
 
-                writeln!(
-                    &mut s,
-                    "$v({}) BAR_GRAPH {} {} {} {}",
-                    mode, title_output, self.fill_color, self.y_min, self.y_max
-                )
-                .unwrap();
+            #[inline(always)]
+            pub fn add_nodes<N: Into<GraphNode>>(
+                self,
+                _nodes: impl IntoIterator<Item = N>,
+            ) -> Self {
+                self
+            }
 
-                // Output items count and data
-                write!(&mut s, "{}", self.items.len()).unwrap();
-                for item in &self.items {
-                    write!(&mut s, " {} {}", item.label, item.value).unwrap();
-                }
-                writeln!(&mut s).unwrap();
+            #[inline(always)]
+            pub fn add_edge<E: Into<GraphEdge>>(self, _edge: E) -> Self {
+                self
+            }
 
-                s
+            #[inline(always)]
+            pub fn add_edges<E: Into<GraphEdge>>(
+                self,
+                _edges: impl IntoIterator<Item = E>,
+            ) -> Self {
+                self
+            }
+
+            #[inline(always)]
+            pub fn to_plane(&self) -> Vis2DPlane {
+                Vis2DPlane
+            }
+
+            #[inline(always)]
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
             }
         }
-    }
 
-    // ============================================================
-    // vis feature が無効な場合の空実装 (Zero-cost)
-    // ============================================================
-    #[cfg(not(feature = "vis"))]
-    mod vis_disabled {
-        use std::fmt::Display;
-        use std::path::PathBuf;
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum TreeNodeStatus {
+            Open,
+            Expanded,
+            Pruned,
+        }
 
-        // Dummy type for API compatibility
-        pub enum OutputDestination {
-            Stderr,
-            File(PathBuf),
+        impl Display for TreeNodeStatus {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    TreeNodeStatus::Open => "OPEN",
+                    TreeNodeStatus::Expanded => "EXPANDED",
+                    TreeNodeStatus::Pruned => "PRUNED",
+                };
+                write!(f, "{s}")
+            }
         }
 
-        // VisTextArea - Zero-Sized Type
-        pub struct VisTextArea;
+        // VisTree - Zero-Sized Type
+        pub struct VisTree;
+        pub struct TreeNode;
 
-        impl VisTextArea {
+        impl TreeNode {
             #[inline(always)]
-            pub fn new(_title: String, _text: String) -> Self {
+            pub fn new(_id: usize, _parent: Option<usize>) -> Self {
                 Self
             }
 
             #[inline(always)]
-            pub fn height(self, _height: u32) -> Self {
+            pub fn with_score(self, _score: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn text_color(self, _color: String) -> Self {
+            pub fn with_status(self, _status: TreeNodeStatus) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn fill_color(self, _color: String) -> Self {
+            pub fn with_label(self, _label: String) -> Self {
                 self
             }
         }
 
-        // VisRoot - Zero-Sized Type
-        pub struct VisRoot;
-
-        impl Default for VisRoot {
+        impl From<(usize, Option<usize>)> for TreeNode {
             #[inline(always)]
-            fn default() -> Self {
+            fn from(_: (usize, Option<usize>)) -> Self {
                 Self
             }
         }
 
-        impl VisRoot {
+        impl VisTree {
             #[inline(always)]
-            pub fn new() -> Self {
-                Self
+            pub fn new(_h: f64, _w: f64, _bounds: Option<ItemBounds>) -> Self {
+                VisTree
             }
 
             #[inline(always)]
-            pub fn new_with_file<P: Into<PathBuf>>(_path: P) -> Self {
-                Self
+            pub fn add_node<N: Into<TreeNode>>(self, _node: N) -> Self {
+                self
             }
 
             #[inline(always)]
-            pub fn add_frame(&mut self, _mode: &str, _frame: VisFrame) -> &mut Self {
+            pub fn add_nodes<N: Into<TreeNode>>(self, _nodes: impl IntoIterator<Item = N>) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_frames(&mut self, _mode: &str, _frames: Vec<VisFrame>) -> &mut Self {
+            pub fn highlight_path(self, _leaf: usize) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn get_frames(&self, _mode: &str) -> Option<&Vec<VisFrame>> {
-                None
+            pub fn set_max_depth(self, _max_depth: usize) -> Self {
+                self
             }
 
             #[inline(always)]
-            pub fn output_all(&self) {}
-        }
-
-        // VisFrame - Zero-Sized Type
-        pub struct VisFrame;
+            pub fn set_max_children(self, _max_children: usize) -> Self {
+                self
+            }
 
-        impl VisFrame {
             #[inline(always)]
-            pub fn new() -> Self {
-                Self
+            pub fn to_plane(&self) -> Vis2DPlane {
+                Vis2DPlane
             }
 
             #[inline(always)]
-            pub fn new_grid<T: ToString>(_grid: VisGrid, _score: T) -> Self {
-                Self
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
             }
+        }
 
+        // Color - Keep minimal implementation for API compatibility
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct Color;
+
+        impl Color {
             #[inline(always)]
-            pub fn new_2d_plane<T: ToString>(_plane: Vis2DPlane, _score: T) -> Self {
+            pub const fn new(_r: u8, _g: u8, _b: u8) -> Self {
                 Self
             }
+        }
 
+        impl From<&String> for Color {
             #[inline(always)]
-            pub fn set_canvas(self, _canvas: VisCanvas) -> Self {
-                self
+            fn from(_hex: &String) -> Self {
+                Self
             }
+        }
 
-            #[inline(always)]
-            pub fn add_grid(self, _grid: VisGrid) -> Self {
-                self
+        impl Display for Color {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "")
             }
+        }
 
-            #[inline(always)]
-            pub fn add_2d_plane(self, _plane: Vis2DPlane) -> Self {
-                self
+        // グリッド上のセルの位置。(x, y) と (row, col) の取り違えを防ぐため、
+        // row_col / xy のどちらかで明示的に作成する。タプル (x, y) からも変換できる
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub struct GridPos {
+            pub row: usize,
+            pub col: usize,
+        }
+
+        impl GridPos {
+            pub const fn row_col(row: usize, col: usize) -> Self {
+                Self { row, col }
             }
 
-            #[inline(always)]
-            pub fn add_item(self, _item: VisItem) -> Self {
-                self
+            pub const fn xy(x: usize, y: usize) -> Self {
+                Self { row: y, col: x }
             }
+        }
 
-            #[inline(always)]
-            pub fn set_score(self, _score: String) -> Self {
-                self
+        impl From<(usize, usize)> for GridPos {
+            // 既存の API に合わせて (x, y) として解釈する
+            fn from(p: (usize, usize)) -> Self {
+                Self::xy(p.0, p.1)
             }
+        }
 
-            #[inline(always)]
-            pub fn add_textarea(self, _textarea: VisTextArea) -> Self {
-                self
+        impl Display for GridPos {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "(row {}, col {})", self.row, self.col)
             }
+        }
 
-            #[inline(always)]
-            pub fn add_bar_graph(self, _bar_graph: VisBarGraph) -> Self {
-                self
+        // グリッドの範囲外の位置が指定されたときのエラー
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub struct GridPosError {
+            pub pos: GridPos,
+            pub h: usize,
+            pub w: usize,
+        }
+
+        impl Display for GridPosError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "position {} is out of range for {}x{} grid (H x W)",
+                    self.pos, self.h, self.w
+                )
             }
+        }
 
-            #[inline(always)]
-            pub fn enable_debug(self) -> Self {
-                self
+        impl std::error::Error for GridPosError {}
+
+        // グリッドの壁の位置 (行・列で指定)
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum WallPos {
+            // row 行目のセルの上側 (row == H なら最下段の下側)、col 列目のセルにかかる横向きの壁
+            Horizontal { row: usize, col: usize },
+            // row 行目のセルの、col 列目の左側 (col == W なら最右列の右側) にある縦向きの壁
+            Vertical { row: usize, col: usize },
+        }
+
+        impl Display for WallPos {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    WallPos::Horizontal { row, col } => write!(f, "H {row} {col}"),
+                    WallPos::Vertical { row, col } => write!(f, "V {row} {col}"),
+                }
             }
+        }
 
-            #[inline(always)]
-            pub fn disable_debug(self) -> Self {
-                self
+        impl WallPos {
+            // セル p の上側の壁
+            pub fn above(p: impl Into<GridPos>) -> Self {
+                let p = p.into();
+                WallPos::Horizontal {
+                    row: p.row,
+                    col: p.col,
+                }
             }
 
-            #[inline(always)]
-            pub fn to_vis_string(&self, _mode: &str) -> String {
-                String::new()
+            // セル p の左側の壁
+            pub fn left_of(p: impl Into<GridPos>) -> Self {
+                let p = p.into();
+                WallPos::Vertical {
+                    row: p.row,
+                    col: p.col,
+                }
             }
-        }
 
-        impl Default for VisFrame {
-            #[inline(always)]
-            fn default() -> Self {
-                Self
+            // 隣接する2つのセルの間の壁。隣接していない場合は None
+            // (タプルは VisGrid の他の API と同じく (x, y) として解釈する)
+            pub fn between(a: impl Into<GridPos>, b: impl Into<GridPos>) -> Option<Self> {
+                let (a, b) = (a.into(), b.into());
+                if a.col == b.col && a.row.abs_diff(b.row) == 1 {
+                    Some(WallPos::above(GridPos::row_col(a.row.max(b.row), a.col)))
+                } else if a.row == b.row && a.col.abs_diff(b.col) == 1 {
+                    Some(WallPos::left_of(GridPos::row_col(a.row, a.col.max(b.col))))
+                } else {
+                    None
+                }
             }
         }
 
-        // ItemBounds - Zero-Sized Type
-        pub struct ItemBounds;
+        pub const WHITE: Color = Color;
+        pub const BLACK: Color = Color;
+        pub const GRAY: Color = Color;
+        pub const RED: Color = Color;
+        pub const BLUE: Color = Color;
+        pub const GREEN: Color = Color;
+        pub const YELLOW: Color = Color;
+        pub const CYAN: Color = Color;
+        pub const MAGENTA: Color = Color;
 
-        impl ItemBounds {
-            #[inline(always)]
-            pub fn new(_left: f64, _top: f64, _right: f64, _bottom: f64) -> Self {
-                Self
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum LineStyle {
+            Solid,
+            Dashed,
+            Dotted,
+        }
+
+        impl Display for LineStyle {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    LineStyle::Solid => "SOLID",
+                    LineStyle::Dashed => "DASHED",
+                    LineStyle::Dotted => "DOTTED",
+                };
+                write!(f, "{s}")
             }
         }
 
-        // VisCanvas - Zero-Sized Type
-        pub struct VisCanvas;
+        pub const MOVES_UDLR: [(char, (isize, isize)); 4] =
+            [('U', (0, -1)), ('D', (0, 1)), ('L', (-1, 0)), ('R', (1, 0))];
 
-        impl VisCanvas {
-            #[inline(always)]
-            pub fn new(_h: f64, _w: f64) -> Self {
-                Self
-            }
+        #[inline(always)]
+        pub fn moves_to_path(
+            _start: (usize, usize),
+            _moves: &str,
+            _alphabet: &[(char, (isize, isize))],
+        ) -> Vec<(usize, usize)> {
+            Vec::new()
+        }
 
-            #[inline(always)]
-            pub fn to_vis_string(&self, _mode: &str) -> String {
-                String::new()
-            }
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum MarkerShape {
+            Circle,
+            Square,
+            Diamond,
+            Triangle,
+            Cross,
+            Star,
         }
 
-        impl Default for VisCanvas {
-            #[inline(always)]
-            fn default() -> Self {
-                Self
+        impl Display for MarkerShape {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    MarkerShape::Circle => "CIRCLE",
+                    MarkerShape::Square => "SQUARE",
+                    MarkerShape::Diamond => "DIAMOND",
+                    MarkerShape::Triangle => "TRIANGLE",
+                    MarkerShape::Cross => "CROSS",
+                    MarkerShape::Star => "STAR",
+                };
+                write!(f, "{s}")
             }
         }
 
-        // VisItem - Zero-Sized Type
-        pub enum VisItem {
-            Grid(VisGrid),
-            Plane(Vis2DPlane),
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum TextAnchor {
+            Center,
+            Top,
+            Bottom,
+            Left,
+            Right,
+            TopLeft,
+            TopRight,
+            BottomLeft,
+            BottomRight,
         }
 
-        impl VisItem {
-            #[inline(always)]
-            pub fn to_vis_string(&self, _mode: &str) -> String {
-                String::new()
+        impl Display for TextAnchor {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = match self {
+                    TextAnchor::Center => "C",
+                    TextAnchor::Top => "T",
+                    TextAnchor::Bottom => "B",
+                    TextAnchor::Left => "L",
+                    TextAnchor::Right => "R",
+                    TextAnchor::TopLeft => "TL",
+                    TextAnchor::TopRight => "TR",
+                    TextAnchor::BottomLeft => "BL",
+                    TextAnchor::BottomRight => "BR",
+                };
+                write!(f, "{s}")
             }
         }
 
-        // Vis2DPlane - Zero-Sized Type
-        pub struct Vis2DPlane;
+        // BarGraphItem - Zero-Sized Type
+        pub struct BarGraphItem;
 
-        impl Vis2DPlane {
+        impl BarGraphItem {
             #[inline(always)]
-            pub fn new(_h: f64, _w: f64, _bounds: Option<ItemBounds>) -> Self {
+            pub fn new(_label: String, _value: f64) -> Self {
                 Self
             }
+        }
 
+        // VisBarGraph - Zero-Sized Type
+        pub struct VisBarGraph;
+
+        impl VisBarGraph {
             #[inline(always)]
-            pub fn with_bounds(_h: f64, _w: f64, _bounds: ItemBounds) -> Self {
+            pub fn new(_title: String, _fill_color: Color, _y_min: f64, _y_max: f64) -> Self {
                 Self
             }
 
             #[inline(always)]
-            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
+            pub fn add_item(self, _label: String, _value: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_circle_group(
-                self,
-                _stroke_color: Color,
-                _fill_color: Color,
-                _circles: Vec<Circle>,
-            ) -> Self {
+            pub fn add_items(self, _items: Vec<BarGraphItem>) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_circle(
-                self,
-                _stroke_color: Color,
-                _fill_color: Color,
-                _x: f64,
-                _y: f64,
-                _r: f64,
-            ) -> Self {
+            pub fn to_vis_string(&self, _mode: &str) -> String {
+                String::new()
+            }
+        }
+
+        // VisLineChart - Zero-Sized Type
+        pub struct VisLineChart;
+
+        impl VisLineChart {
+            #[inline(always)]
+            pub fn new(_title: String) -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn set_x_range(self, _min: f64, _max: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_line_group(
-                self,
-                _color: Color,
-                _width: f64,
-                _points: Vec<(f64, f64)>,
-            ) -> Self {
+            pub fn set_y_range(self, _min: f64, _max: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_line(
-                self,
-                _color: Color,
-                _width: f64,
-                _ax: f64,
-                _ay: f64,
-                _bx: f64,
-                _by: f64,
-            ) -> Self {
+            pub fn set_log_scale(self, _log_scale: bool) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_polygon(
-                self,
-                _stroke_color: Color,
-                _fill_color: Color,
-                _vertices: Vec<(f64, f64)>,
-            ) -> Self {
+            pub fn set_marker(self, _x: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_text(
+            pub fn add_series(
                 self,
+                _name: String,
                 _color: Color,
-                _font_size: f64,
-                _x: f64,
-                _y: f64,
-                _text: String,
+                _points: impl IntoIterator<Item = (f64, f64)>,
             ) -> Self {
                 self
             }
@@ -1134,160 +6849,97 @@ pub mod ahc_vdsl {
             }
         }
 
-        // VisGrid - Zero-Sized Type
-        pub struct VisGrid;
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub enum HistogramBins {
+            Count(usize),
+            Width(f64),
+            FreedmanDiaconis,
+        }
 
-        impl VisGrid {
-            #[inline(always)]
-            pub fn new(_h: usize, _w: usize, _bounds: Option<ItemBounds>) -> Self {
-                Self
-            }
+        // VisHistogram - Zero-Sized Type
+        pub struct VisHistogram;
 
+        impl VisHistogram {
             #[inline(always)]
-            pub fn with_bounds(_h: usize, _w: usize, _bounds: ItemBounds) -> Self {
+            pub fn new(_title: String, _color: Color) -> Self {
                 Self
             }
 
             #[inline(always)]
-            pub fn set_bounds(self, _bounds: ItemBounds) -> Self {
-                self
-            }
-
-            #[inline(always)]
-            pub fn update_cell_color(self, _p: (usize, usize), _color: Color) -> Self {
+            pub fn set_bins(self, _bins: HistogramBins) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn update_text(self, _p: (usize, usize), _text: String) -> Self {
+            pub fn set_range(self, _min: f64, _max: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_line(self, _line: Vec<(usize, usize)>, _color: Color) -> Self {
+            pub fn add_sample(self, _value: f64) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn remove_wall_vertical(self, _p: (usize, usize)) -> Self {
-                self
-            }
-
-            #[inline(always)]
-            pub fn add_wall_vertical(self, _p: (usize, usize)) -> Self {
-                self
-            }
-
-            #[inline(always)]
-            pub fn remove_wall_horizontal(self, _p: (usize, usize)) -> Self {
-                self
-            }
-
-            #[inline(always)]
-            pub fn add_wall_horizontal(self, _p: (usize, usize)) -> Self {
+            pub fn add_samples(self, _values: impl IntoIterator<Item = f64>) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn from_cells<T>(_cell_texts: &[Vec<T>]) -> Self
-            where
-                T: ToString,
-            {
-                Self
+            pub fn to_bar_graph(&self) -> VisBarGraph {
+                VisBarGraph
             }
 
             #[inline(always)]
-            pub fn to_vis_string(&self, _mode_name: &str) -> String {
+            pub fn to_vis_string(&self, _mode: &str) -> String {
                 String::new()
             }
         }
 
-        // Helper structs - Zero-Sized Types
-        pub struct CircleGroup;
-        pub struct Circle;
-        pub struct LineGroup;
-        pub struct Line;
-        pub struct PolygonGroup;
-        pub struct VisGridConf;
+        // VisTable - Zero-Sized Type
+        pub struct VisTable;
+        pub struct TableCell;
 
-        impl Default for VisGridConf {
+        impl TableCell {
             #[inline(always)]
-            fn default() -> Self {
+            pub fn int(_value: i64) -> Self {
                 Self
             }
-        }
 
-        impl VisGridConf {
             #[inline(always)]
-            pub const fn new(
-                _border_color: Color,
-                _text_color: Color,
-                _default_cell_color: Color,
-            ) -> Self {
+            pub fn float(_value: f64, _precision: usize) -> Self {
                 Self
             }
-        }
 
-        // Color - Keep minimal implementation for API compatibility
-        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
-        pub struct Color;
-
-        impl Color {
             #[inline(always)]
-            pub const fn new(_r: u8, _g: u8, _b: u8) -> Self {
+            pub fn text(_value: String) -> Self {
                 Self
             }
-        }
 
-        impl From<&String> for Color {
             #[inline(always)]
-            fn from(_hex: &String) -> Self {
-                Self
-            }
-        }
-
-        impl Display for Color {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "")
+            pub fn with_color(self, _color: Color) -> Self {
+                self
             }
         }
 
-        pub const WHITE: Color = Color;
-        pub const BLACK: Color = Color;
-        pub const GRAY: Color = Color;
-        pub const RED: Color = Color;
-        pub const BLUE: Color = Color;
-        pub const GREEN: Color = Color;
-        pub const YELLOW: Color = Color;
-        pub const CYAN: Color = Color;
-        pub const MAGENTA: Color = Color;
-
-        // BarGraphItem - Zero-Sized Type
-        pub struct BarGraphItem;
-
-        impl BarGraphItem {
+        impl VisTable {
             #[inline(always)]
-            pub fn new(_label: String, _value: f64) -> Self {
+            pub fn new(_title: String, _header: Vec<String>) -> Self {
                 Self
             }
-        }
-
-        // VisBarGraph - Zero-Sized Type
-        pub struct VisBarGraph;
 
-        impl VisBarGraph {
             #[inline(always)]
-            pub fn new(_title: String, _fill_color: Color, _y_min: f64, _y_max: f64) -> Self {
+            pub fn key_value(_title: String) -> Self {
                 Self
             }
 
             #[inline(always)]
-            pub fn add_item(self, _label: String, _value: f64) -> Self {
+            pub fn add_row(self, _cells: impl IntoIterator<Item = TableCell>) -> Self {
                 self
             }
 
             #[inline(always)]
-            pub fn add_items(self, _items: Vec<BarGraphItem>) -> Self {
+            pub fn add_entry(self, _key: String, _value: TableCell) -> Self {
                 self
             }
 
@@ -14,6 +14,109 @@ fn test_visgrid_new() {
     assert!(output.contains("LINES"));
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_chooses_dense_colors() {
+    // 全セルに色を付けると CELL_COLORS の方が短くなる
+    let mut grid = VisGrid::new(2, 2, None);
+    for y in 0..2 {
+        for x in 0..2 {
+            grid = grid.update_cell_color((x, y), if (x + y) % 2 == 0 { RED } else { BLUE });
+        }
+    }
+    let output = grid.to_vis_string("test");
+    assert!(!output.contains("CELL_COLORS_POS"));
+    assert!(output.contains("CELL_COLORS\n#FF0000 #0000FF\n#0000FF #FF0000\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_large_sparse() {
+    let output = VisGrid::new(1000, 1000, None)
+        .update_cell_color((999, 0), RED)
+        .update_cell_color((3, 3), GREEN)
+        .update_cell_color((3, 3), WHITE) // 既定色に戻すと出力されない
+        .update_text((2, 1), "a".to_string())
+        .to_vis_string("test");
+    assert!(output.contains("CELL_COLORS_POS\n1\n#FF0000 1 999 0\n"));
+    assert!(output.contains("CELL_TEXT\n\n\"\" \"\" a\n"));
+    assert!(output.len() < 10000);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_viewport() {
+    let output = VisGrid::new(10, 10, None)
+        .update_cell_color(GridPos::row_col(3, 4), RED)
+        .update_cell_color(GridPos::row_col(0, 0), BLUE) // 範囲外
+        .add_marker(GridPos::row_col(4, 5), MarkerShape::Circle, BLACK, RED, 0.5)
+        .add_line(vec![(4, 2), (5, 2), (9, 2), (9, 3)], GREEN)
+        .set_viewport(2..5, 4..7)
+        .to_vis_string("test");
+    assert!(output.contains("$v(test) GRID 3 3"));
+    assert!(output.contains("#FF0000 1 0 1"));
+    assert!(!output.contains("#0000FF"));
+    assert!(output.contains("CIRCLE #000000 #FF0000 0.5 1 1 2"));
+    // 範囲外の点で折れ線が分割され、範囲内の部分だけが残る
+    assert!(output.contains("LINES\n1\n#00FF00 2 0 0 1 0\n"));
+    // 軸ラベルを表示しない設定では切り出してもラベルを出力しない
+    assert!(!output.contains("AXIS_LABELS"));
+    assert!(output.ends_with("OFFSET\n2 4\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_follow() {
+    // 盤面の端では範囲が盤面内に収まるようにずれる
+    let output = VisGrid::new(10, 10, None)
+        .follow(GridPos::row_col(9, 1), 4, 4)
+        .to_vis_string("test");
+    assert!(output.contains("$v(test) GRID 4 4"));
+    assert!(output.ends_with("OFFSET\n6 0\n"));
+
+    let output = VisGrid::new(10, 10, None)
+        .follow(GridPos::row_col(5, 5), 3, 20)
+        .to_vis_string("test");
+    assert!(output.contains("$v(test) GRID 3 10"));
+    assert!(output.ends_with("OFFSET\n4 0\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_empty_viewport_keeps_one_cell() {
+    // 空の範囲や盤面外の範囲でも GRID 0 W を出力せず、1 行 (1 列) 以上を残す
+    let (r0, r1) = (5, 3);
+    let output = VisGrid::new(10, 10, None).set_viewport(r0..r1, 12..15).to_vis_string("test");
+    assert!(output.contains("$v(test) GRID 1 1"));
+    assert!(output.ends_with("OFFSET\n5 9\n"));
+
+    let output = VisGrid::new(10, 10, None)
+        .follow(GridPos::row_col(4, 4), 0, 3)
+        .to_vis_string("test");
+    assert!(output.contains("$v(test) GRID 1 3"));
+    assert!(output.ends_with("OFFSET\n4 3\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_axis_labels() {
+    let output = VisGrid::new(3, 12, None).show_axis_labels(5).to_vis_string("test");
+    assert!(output.contains("AXIS_LABELS\n1 0 0\n3 0 0 5 5 10 10\n"));
+
+    let output = VisGrid::new(2, 2, None)
+        .set_row_labels(vec!["a".to_string(), "row b".to_string()])
+        .set_col_labels(vec![String::new(), "x".to_string()])
+        .to_vis_string("test");
+    assert!(output.contains("AXIS_LABELS\n2 0 a 1 \"row b\"\n1 1 x\n"));
+
+    // 表示範囲を切り出しても元の番号のラベルが付く
+    let output = VisGrid::new(10, 10, None)
+        .show_axis_labels(2)
+        .set_viewport(3..6, 3..5)
+        .to_vis_string("test");
+    assert!(output.contains("AXIS_LABELS\n1 1 4\n1 1 4\n"));
+}
+
 #[cfg(feature = "vis")]
 #[test]
 fn test_visgrid_with_bounds() {
@@ -68,6 +171,48 @@ fn test_visgrid_cell_text_trailing_empty() {
     assert!(!output.contains("hello "));
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_add_cell_text() {
+    // 同じスタイル・位置のテキストは1つのグループにまとめられる
+    let output = VisGrid::new(3, 3, None)
+        .add_cell_text((0, 0), TextAnchor::TopLeft, GRAY, 8.0, "0".to_string())
+        .add_cell_text((1, 0), TextAnchor::TopLeft, GRAY, 8.0, "1".to_string())
+        .add_cell_text((1, 0), TextAnchor::Center, RED, 14.0, "v 12".to_string())
+        .to_vis_string("test");
+    assert!(output.contains("CELL_TEXT_EX\n2\n"));
+    assert!(output.contains("#808080 8 TL 2 0 0 0 1 0 1"));
+    assert!(output.contains("#FF0000 14 C 1 1 0 \"v 12\""));
+    assert!(!output.contains("CELL_TEXT\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_update_text_style() {
+    // スタイル指定のあるセルは CELL_TEXT ではなく CELL_TEXT_EX に出力される
+    let output = VisGrid::new(1, 2, None)
+        .update_text((0, 0), "a".to_string())
+        .update_text((1, 0), "b".to_string())
+        .update_text_style((1, 0), BLUE, 20.0)
+        .to_vis_string("test");
+    assert!(output.contains("CELL_TEXT\na\n"));
+    assert!(output.contains("CELL_TEXT_EX\n1\n#0000FF 20 C 1 1 0 b\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_add_marker() {
+    // 同じ形状・色・大きさのマーカーは追加順を保って1つのグループにまとめられる
+    let output = VisGrid::new(3, 3, None)
+        .add_marker((1, 1), MarkerShape::Square, BLACK, YELLOW, 0.8)
+        .add_marker((1, 1), MarkerShape::Circle, BLACK, RED, 0.4)
+        .add_markers(vec![(0, 2), (2, 0)], MarkerShape::Square, BLACK, YELLOW, 0.8)
+        .to_vis_string("test");
+    assert!(output.contains(
+        "CELL_MARKERS\n2\nSQUARE #000000 #FFFF00 0.8 3 1 1 0 2 2 0\nCIRCLE #000000 #FF0000 0.4 1 1 1\n"
+    ));
+}
+
 #[cfg(feature = "vis")]
 #[test]
 fn test_visgrid_add_line() {
@@ -79,6 +224,79 @@ fn test_visgrid_add_line() {
     assert!(output.contains("#0000FF")); // BLUE color
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_add_polyline() {
+    let output = VisGrid::new(5, 5, None)
+        .add_polyline(vec![(0, 0), (1, 0), (1, 1)], RED, 4.0, LineStyle::Dashed)
+        .to_vis_string("test");
+    assert!(output.contains("POLYLINES\n1\n#FF0000 4 DASHED 3 0 0 1 0 1 1\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_add_arrow() {
+    let output = VisGrid::new(5, 5, None)
+        .add_arrow((0, 0), (2, 0), BLUE, 2.0, LineStyle::Solid)
+        .add_labeled_arrow((2, 0), (2, 3), BLUE, 2.0, LineStyle::Solid, "x 3".to_string())
+        .to_vis_string("test");
+    assert!(output.contains("ARROWS\n1\n#0000FF 2 SOLID 2 0 0 2 0 \"\" 2 0 2 3 \"x 3\"\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_moves_to_path() {
+    assert_eq!(
+        moves_to_path((1, 1), "RRDUL", &MOVES_UDLR),
+        vec![(1, 1), (2, 1), (3, 1), (3, 2), (3, 1), (2, 1)]
+    );
+    // 座標が負になる移動は 0 で止まり、未知の文字は無視される
+    assert_eq!(moves_to_path((0, 0), "LU.D", &MOVES_UDLR), vec![(0, 0), (0, 0), (0, 0), (0, 1)]);
+    // 独自のアルファベット (斜め移動)
+    let diagonal = [('a', (1, 1)), ('b', (-1, 1))];
+    assert_eq!(moves_to_path((1, 0), "ab", &diagonal), vec![(1, 0), (2, 1), (1, 2)]);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_add_move_path() {
+    let output = VisGrid::new(5, 5, None)
+        .add_move_path((0, 0), "RD", GREEN, 3.0, LineStyle::Solid)
+        .to_vis_string("test");
+    assert!(output.contains("#00FF00 3 SOLID 3 0 0 1 0 1 1"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_move_path_stops_at_board_edge() {
+    let output = VisGrid::new(3, 3, None)
+        .add_move_path((1, 1), "RRRDDL", GREEN, 3.0, LineStyle::Solid)
+        .to_vis_string("test");
+    assert!(output.contains("#00FF00 3 SOLID 7 1 1 2 1 2 1 2 1 2 2 2 2 1 2\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisGrid::add_labeled_arrow: position (row 0, col 5)")]
+fn test_visgrid_arrow_out_of_range_panics() {
+    let _ = VisGrid::new(5, 5, None).add_arrow((0, 0), (5, 0), BLUE, 2.0, LineStyle::Solid);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_clip_skips_out_of_range_paths() {
+    let output = VisGrid::new(3, 3, None)
+        .clip_out_of_range(true)
+        .add_polyline(vec![(0, 0), (3, 0), (1, 1)], RED, 2.0, LineStyle::Solid)
+        .add_arrow((0, 0), (0, 3), BLUE, 2.0, LineStyle::Solid)
+        .add_move_path((3, 3), "L", GREEN, 3.0, LineStyle::Solid)
+        .to_vis_string("test");
+    assert!(output.contains("POLYLINES\n1\n#FF0000 2 SOLID 2 0 0 1 1\n"));
+    assert!(!output.contains("ARROWS"));
+    assert!(!output.contains("#00FF00"));
+    assert!(VisGrid::new(3, 3, None).try_add_line(vec![(0, 0), (0, 3)], RED).is_err());
+}
+
 #[cfg(feature = "vis")]
 #[test]
 fn test_color_display() {
@@ -139,6 +357,593 @@ fn test_visgrid_walls() {
     assert!(output.contains("WALL_HORIZONTAL"));
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_gridpos_constructors() {
+    assert_eq!(GridPos::row_col(1, 2), GridPos::xy(2, 1));
+    assert_eq!(GridPos::from((2, 1)), GridPos { row: 1, col: 2 });
+
+    let by_tuple = VisGrid::new(2, 3, None)
+        .update_cell_color((2, 1), RED)
+        .to_vis_string("test");
+    let by_row_col = VisGrid::new(2, 3, None)
+        .update_cell_color(GridPos::row_col(1, 2), RED)
+        .to_vis_string("test");
+    assert_eq!(by_tuple, by_row_col);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_try_update_out_of_range() {
+    let err = VisGrid::new(2, 3, None)
+        .try_update_cell_color(GridPos::row_col(3, 0), RED)
+        .err()
+        .unwrap();
+    assert_eq!(err.pos, GridPos::row_col(3, 0));
+    assert_eq!(err.to_string(), "position (row 3, col 0) is out of range for 2x3 grid (H x W)");
+
+    let grid = VisGrid::new(2, 3, None)
+        .try_update_text(GridPos::row_col(1, 2), "ok".to_string())
+        .unwrap();
+    assert!(grid.to_vis_string("test").contains("ok"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisGrid::update_cell_color: position (row 0, col 3)")]
+fn test_visgrid_update_out_of_range_panics() {
+    let _ = VisGrid::new(2, 3, None).update_cell_color((3, 0), RED);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_clip_out_of_range() {
+    let output = VisGrid::new(2, 3, None)
+        .clip_out_of_range(true)
+        .update_cell_color((3, 0), RED)
+        .update_text(GridPos::row_col(5, 5), "x".to_string())
+        .add_marker((0, 2), MarkerShape::Circle, BLACK, RED, 0.5)
+        .to_vis_string("test");
+    assert!(!output.contains("#FF0000"));
+    assert!(!output.contains("CELL_MARKERS"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_tooltips() {
+    let output = VisGrid::new(3, 3, None)
+        .set_tooltip((2, 1), "score: 10\nC:\\tmp".to_string())
+        .set_tooltip(GridPos::row_col(0, 0), "a b".to_string())
+        .set_tooltip((1, 1), "removed".to_string())
+        .set_tooltip((1, 1), String::new())
+        .to_vis_string("test");
+    assert!(output.contains("TOOLTIPS\n2\n0 0 a b\n2 1 score: 10\\nC:\\\\tmp\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_wallpos_between() {
+    assert_eq!(WallPos::between(GridPos::row_col(1, 2), GridPos::row_col(2, 2)), Some(WallPos::Horizontal { row: 2, col: 2 }));
+    assert_eq!(WallPos::between(GridPos::row_col(1, 3), GridPos::row_col(1, 2)), Some(WallPos::Vertical { row: 1, col: 3 }));
+    // タプルは (x, y)
+    assert_eq!(WallPos::between((3, 1), (2, 1)), Some(WallPos::Vertical { row: 1, col: 3 }));
+    assert_eq!(WallPos::between((1, 1), (2, 2)), None);
+    assert_eq!(WallPos::between((1, 1), (1, 1)), None);
+    assert_eq!(WallPos::above((2, 1)), WallPos::Horizontal { row: 1, col: 2 });
+    assert_eq!(WallPos::left_of(GridPos::row_col(1, 2)), WallPos::Vertical { row: 1, col: 2 });
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_walls_from_strs() {
+    // AHC 形式: v は H 行 x (W-1) 文字、h は (H-1) 行 x W 文字
+    let output = VisGrid::new(2, 3, None)
+        .set_vertical_walls_from_strs(&["10", "01"])
+        .set_horizontal_walls_from_strs(&["100"])
+        .to_vis_string("walls");
+    let horizontal = output.split("WALL_HORIZONTAL\n").nth(1).unwrap();
+    let horizontal: Vec<&str> = horizontal.lines().take(3).collect();
+    assert_eq!(horizontal, vec!["YYY", "YNN", "YYY"]);
+    let vertical = output.split("WALL_VERTICAL\n").nth(1).unwrap();
+    let vertical: Vec<&str> = vertical.lines().take(2).collect();
+    assert_eq!(vertical, vec!["YYNY", "YNYY"]);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgrid_wall_style() {
+    let output = VisGrid::new(2, 2, None)
+        .remove_wall(WallPos::Vertical { row: 0, col: 1 })
+        .set_wall_style(WallPos::Horizontal { row: 1, col: 0 }, RED, 4.0)
+        .set_wall_style(WallPos::Vertical { row: 0, col: 1 }, RED, 4.0)
+        .to_vis_string("walls");
+    // 壁が無い位置のスタイルは出力されない
+    assert!(output.contains("WALL_STYLES\n1\n#FF0000 4 1 H 1 0\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vishexgrid_output() {
+    let output = VisHexGrid::new(3, 4, HexOrientation::Pointy, Some(ItemBounds::new(0.0, 0.0, 400.0, 300.0)))
+        .update_cell_color(GridPos::row_col(1, 2), RED)
+        .update_text((0, 0), "a".to_string())
+        .add_line(vec![(0, 0), (1, 0), (1, 1)], BLUE)
+        .to_vis_string("hex");
+    assert!(output.starts_with("$v(hex) HEX_GRID(0, 0, 400, 300) 3 4 POINTY #000000 #000000 #FFFFFF\n"));
+    assert!(output.contains("CELL_COLORS_POS\n1\n#FF0000 1 2 1\n"));
+    assert!(output.contains("CELL_TEXT\na\n\n\n"));
+    assert!(output.contains("LINES\n1\n#0000FF 3 0 0 1 0 1 1\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vishexgrid_axial_and_neighbors() {
+    // Pointy (奇数行が右にずれる): 行 1 の axial q は col - 0
+    let grid = VisHexGrid::new(4, 4, HexOrientation::Pointy, None);
+    assert_eq!(grid.neighbor(GridPos::row_col(1, 1), 0), Some(GridPos::row_col(1, 2)));
+    assert_eq!(grid.neighbor(GridPos::row_col(1, 1), 1), Some(GridPos::row_col(0, 2)));
+    assert_eq!(grid.neighbor(GridPos::row_col(0, 0), 2), None);
+    assert_eq!(grid.neighbor(HexPos::Axial { q: 0, r: 2 }, 0), Some(GridPos::row_col(2, 2)));
+
+    // Flat (奇数列が下にずれる)
+    let grid = VisHexGrid::new(4, 4, HexOrientation::Flat, None);
+    assert_eq!(grid.neighbor(GridPos::row_col(1, 1), 0), Some(GridPos::row_col(2, 2)));
+    assert_eq!(grid.neighbor(GridPos::row_col(1, 1), 1), Some(GridPos::row_col(1, 2)));
+
+    let output = VisHexGrid::new(4, 4, HexOrientation::Flat, None)
+        .update_cell_color(HexPos::Axial { q: 1, r: 0 }, RED)
+        .remove_edge_between(GridPos::row_col(1, 1), GridPos::row_col(1, 2))
+        .remove_edge(GridPos::row_col(0, 0), 8)
+        .to_vis_string("hex");
+    assert!(output.contains("#FF0000 1 1 0\n"));
+    assert!(output.contains("HIDDEN_EDGES\n2 0 0 2 1 1 1\n"));
+}
+
+// parser.ts の tokenizeQuoted と同じ規則で 1 行をトークンに分ける
+#[cfg(feature = "vis")]
+fn tokenize_quoted(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let (token, next) = match rest.strip_prefix('"').and_then(|r| r.split_once('"')) {
+            Some((quoted, next)) => (quoted, next),
+            None => rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+        };
+        tokens.push(token.to_string());
+        rest = next.trim_start();
+    }
+    tokens
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_lattice_cell_text_round_trip() {
+    let texts = ["a b", "", "c"];
+    let hex = VisHexGrid::new(1, 3, HexOrientation::Pointy, None)
+        .update_text((0, 0), texts[0].to_string())
+        .update_text((2, 0), texts[2].to_string())
+        .to_vis_string("hex");
+    let tri = VisTriGrid::new(1, 3, None)
+        .update_text((0, 0), texts[0].to_string())
+        .update_text((2, 0), texts[2].to_string())
+        .to_vis_string("tri");
+    for output in [hex, tri] {
+        let row = output.split("CELL_TEXT\n").nth(1).unwrap().lines().next().unwrap();
+        assert_eq!(row, "\"a b\" \"\" c");
+        assert_eq!(tokenize_quoted(row), texts);
+    }
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisHexGrid::update_text")]
+fn test_vishexgrid_out_of_range_panics() {
+    let _ = VisHexGrid::new(2, 2, HexOrientation::Flat, None).update_text(HexPos::Axial { q: -1, r: 0 }, "x".to_string());
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vistrigrid_output_and_neighbors() {
+    let grid = VisTriGrid::new(2, 3, Some(ItemBounds::new(0.0, 0.0, 300.0, 200.0)))
+        .update_cell_color((1, 0), RED)
+        .update_text(GridPos::row_col(1, 2), "a".to_string())
+        .add_line(vec![(0, 0), (1, 0), (1, 1)], BLUE)
+        .remove_edge_between((0, 0), (0, 1))
+        .remove_edge((2, 0), 5);
+    // (0, 0) は上向きなので水平の辺は下の (0, 1) と共有する
+    assert!(VisTriGrid::is_up((0, 0)));
+    assert!(!VisTriGrid::is_up((1, 0)));
+    assert_eq!(grid.neighbor((0, 0), 0), Some(GridPos::xy(0, 1)));
+    assert_eq!(grid.neighbor((1, 0), 0), None);
+    assert_eq!(grid.neighbor((1, 0), 1), Some(GridPos::xy(0, 0)));
+    assert_eq!(grid.neighbor((2, 0), 2), None);
+    let output = VisFrame::new().add_tri_grid(grid).to_vis_string("tri");
+    assert!(output.contains("$v(tri) TRI_GRID(0, 0, 300, 200) 2 3 #000000 #000000 #FFFFFF\n"));
+    assert!(output.contains("CELL_COLORS_POS\n1\n#FF0000 1 1 0\n"));
+    assert!(output.contains("CELL_TEXT\n\n\"\" \"\" a\n"));
+    assert!(output.contains("LINES\n1\n#0000FF 3 0 0 1 0 1 1\n"));
+    assert!(output.contains("HIDDEN_EDGES\n2 0 0 0 2 0 2\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vistrigrid_out_of_range() {
+    let err = VisTriGrid::new(2, 2, None).try_update_text((2, 0), "x".to_string()).err().unwrap();
+    assert_eq!(err.pos, GridPos::xy(2, 0));
+    let output = VisTriGrid::new(2, 2, None)
+        .clip_out_of_range(true)
+        .update_cell_color((5, 5), RED)
+        .add_line(vec![(0, 0), (0, 9)], BLUE)
+        .to_vis_string("tri");
+    assert_eq!(output, "$v(tri) TRI_GRID 2 2 #000000 #000000 #FFFFFF\nLINES\n1\n#0000FF 1 0 0\n");
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisTriGrid::remove_edge")]
+fn test_vistrigrid_out_of_range_panics() {
+    let _ = VisTriGrid::new(2, 2, None).remove_edge((0, 2), 0);
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisTriGrid::remove_edge_between: position (row 5, col 0)")]
+fn test_vistrigrid_remove_edge_between_checks_first_cell() {
+    let _ = VisTriGrid::new(2, 2, None).remove_edge_between((0, 5), (0, 1));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+#[should_panic(expected = "VisHexGrid::remove_edge_between")]
+fn test_vishexgrid_remove_edge_between_checks_first_cell() {
+    let _ = VisHexGrid::new(2, 2, HexOrientation::Flat, None).remove_edge_between(GridPos::row_col(5, 0), GridPos::row_col(1, 0));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vishexgrid_try_and_clip_out_of_range() {
+    let err = VisHexGrid::new(2, 2, HexOrientation::Pointy, None)
+        .try_update_cell_color(HexPos::Axial { q: -1, r: 0 }, RED)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "position Axial { q: -1, r: 0 } is out of range for 2x2 grid (H x W)");
+    assert!(VisHexGrid::new(2, 2, HexOrientation::Pointy, None).try_add_line(vec![(0, 0), (2, 0)], RED).is_err());
+
+    let output = VisHexGrid::new(2, 2, HexOrientation::Pointy, None)
+        .clip_out_of_range(true)
+        .update_cell_color((5, 5), RED)
+        .update_text(GridPos::row_col(2, 0), "x".to_string())
+        .add_line(vec![(0, 0), (9, 9), (1, 1)], BLUE)
+        .remove_edge((3, 0), 0)
+        .remove_edge_between((0, 0), (0, 7))
+        .to_vis_string("hex");
+    assert_eq!(output, "$v(hex) HEX_GRID 2 2 POINTY #000000 #000000 #FFFFFF\nLINES\n1\n#0000FF 2 0 0 1 1\n");
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgraph_given_positions() {
+    let graph = VisGraph::new(100.0, 100.0, None)
+        .set_node_radius(5.0)
+        .add_node(GraphNode::new(0).with_pos(10.0, 50.0).with_color(RED))
+        .add_node(GraphNode::new(1).with_pos(90.0, 50.0).with_label("goal".to_string()))
+        .add_edge((0, 1, 3.0));
+    let output = VisFrame::new().add_graph(graph).to_vis_string("test");
+    assert!(output.contains("$v(test) 2D_PLANE 100 100\n"));
+    assert!(output.contains("CIRCLES\n2\n#000000 #FF0000 1 10 50 5\n#000000 #FFFFFF 1 90 50 5\n"));
+    assert!(output.contains("LINES\n1\n#808080 1.5 1 10 50 90 50\n"));
+    assert!(output.contains("#000000 10 1 50 50 3 C 0 #FFFFFF 1 0 0\n"));
+    assert!(output.contains("#000000 12 2 10 50 0 90 50 goal\n"));
+    assert!(output.contains("TOOLTIPS\n2\nC 0 0 node 0\nC 1 0 node 1\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgraph_directed_edges_are_shortened_arrows() {
+    let output = VisGraph::new(100.0, 100.0, None)
+        .set_directed(true)
+        .set_node_radius(5.0)
+        .add_nodes([GraphNode::new(0).with_pos(10.0, 50.0), GraphNode::new(1).with_pos(90.0, 50.0)])
+        .add_edge(GraphEdge::new(0, 1).with_color(BLUE))
+        .add_edge((1, 1))
+        .to_vis_string("test");
+    assert!(output.contains("ARROWS\n1\n#0000FF 1.5 8 1 15 50 85 50\n"));
+    assert!(!output.contains("LINES"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgraph_circular_layout() {
+    let output = VisGraph::new(100.0, 100.0, None)
+        .set_layout(GraphLayout::Circular)
+        .set_node_radius(5.0)
+        .add_edges([(0, 1), (1, 2), (2, 3)])
+        .add_node(GraphNode::new(3).with_pos(0.0, 0.0))
+        .to_vis_string("test");
+    // 上から時計回りに並べ、座標を指定した頂点はその位置に置く
+    assert!(output.contains("#000000 #FFFFFF 4 50 10 5 90 50 5 50 90 5 0 0 5\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgraph_layered_layout() {
+    let output = VisGraph::new(100.0, 100.0, None)
+        .set_layout(GraphLayout::Layered)
+        .set_directed(true)
+        .set_node_radius(5.0)
+        .add_edges([(2, 1), (2, 0)])
+        .to_vis_string("test");
+    // 根 2 が上の段、子 1, 0 が下の段に並ぶ
+    assert!(output.contains("#000000 #FFFFFF 3 50 30 5 30 70 5 70 70 5\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vistree_layout_and_highlight() {
+    let tree = VisTree::new(120.0, 120.0, None)
+        .add_node(TreeNode::new(0, None).with_score(10.0).with_status(TreeNodeStatus::Expanded))
+        .add_node(TreeNode::new(1, Some(0)).with_status(TreeNodeStatus::Expanded))
+        .add_node(TreeNode::new(2, Some(0)).with_status(TreeNodeStatus::Pruned))
+        .add_node((3, Some(1)))
+        .highlight_path(3);
+    let output = VisFrame::new().add_tree(tree).to_vis_string("test");
+    // 葉 3, 2 を等間隔に並べ、親は子の中央に置く
+    assert!(output.contains(
+        "CIRCLES\n3\n#FF0000 #00FFFF 2 60 20 12 30 60 12 2.5 SOLID\n#FF0000 #FFFFFF 1 30 100 12 2.5 SOLID\n#000000 #808080 1 90 60 12 1 SOLID\n"
+    ));
+    assert!(output.contains("LINES\n2\n#FF0000 2.5 2 60 20 30 60 30 60 30 100\n#808080 1 1 60 20 90 60\n"));
+    assert!(output.contains("C 0 0 node 0\\nscore 10\\nEXPANDED\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vistree_collapses_subtrees() {
+    let wide = VisTree::new(100.0, 100.0, None)
+        .add_node((0, None))
+        .add_nodes((1..=5).map(|i| (i, Some(0))))
+        .add_node((6, Some(3)))
+        .set_max_children(2)
+        .highlight_path(5)
+        .to_vis_string("test");
+    // 子 1, 2 と強調した 5 を表示し、3 (と子の 6), 4 をまとめる
+    assert!(wide.contains(" +3\n"));
+    assert!(wide.contains("3 nodes collapsed"));
+
+    let deep = VisTree::new(100.0, 100.0, None)
+        .add_nodes([(0, None), (1, Some(0)), (2, Some(1)), (3, Some(2))])
+        .set_max_depth(1)
+        .to_vis_string("test");
+    assert!(deep.contains(" +2\n"));
+    assert!(deep.contains("2 nodes collapsed"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_visgraph_force_directed_layout_is_deterministic() {
+    let build = || {
+        VisGraph::new(200.0, 200.0, None)
+            .add_edges((0..8).map(|i| (i, (i + 1) % 8)))
+            .add_edge((0, 4))
+            .to_vis_string("test")
+    };
+    let output = build();
+    assert_eq!(output, build());
+    // 頂点はすべて範囲内に置かれる
+    let circles = output.lines().skip_while(|l| *l != "CIRCLES").nth(2).unwrap();
+    let values: Vec<f64> = circles.split(' ').skip(3).map(|v| v.parse().unwrap()).collect();
+    assert_eq!(values.len(), 24);
+    assert!(values.iter().all(|&v| (0.0..=200.0).contains(&v)));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_tooltips() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_circle(BLACK, RED, 10.0, 10.0, 5.0)
+        .add_circle_with_tooltip(BLACK, RED, 20.0, 20.0, 5.0, "id=1\nv=3".to_string())
+        .add_polygon(BLACK, BLUE, vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)])
+        .add_polygon_with_tooltip(BLACK, BLUE, vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)], "big one".to_string())
+        .to_vis_string("test");
+    assert!(output.contains("TOOLTIPS\n2\nC 0 1 id=1\\nv=3\nP 1 big one\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_bulk_items() {
+    let points = [(1.0, 2.0), (3.0, 4.0)];
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_circle_group(RED, BLUE, points.iter().map(|&(x, y)| (x, y, 0.5)))
+        .add_circle_group(RED, BLUE, vec![Circle::new(5.0, 6.0, 1.0).with_tooltip("c".to_string())])
+        .add_text_group(BLACK, 10.0, points.iter().enumerate().map(|(i, &(x, y))| (x, y, i.to_string())))
+        .add_text_group(BLACK, 10.0, [(7.0, 8.0, "a b")])
+        .add_polygon_groups((0..2).map(|i| {
+            let d = i as f64;
+            PolygonGroup::new(GREEN, WHITE, vec![(d, 0.0), (d + 1.0, 0.0), (d, 1.0)])
+        }))
+        .to_vis_string("test");
+    assert!(output.contains("CIRCLES
+1
+#FF0000 #0000FF 3 1 2 0.5 3 4 0.5 5 6 1
+"));
+    assert!(output.contains("TEXT
+1
+#000000 10 3 1 2 0 3 4 1 7 8 \"a b\"\n"));
+    assert!(output.contains("POLYGONS
+2
+#00FF00 #FFFFFF 3 0 0 1 0 0 1
+#00FF00 #FFFFFF 3 1 0 2 0 1 1
+"));
+    assert!(output.contains("TOOLTIPS
+1
+C 0 2 c
+"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_polylines_and_strokes() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_polyline(RED, 1.5, LineStyle::Dashed, vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)])
+        .add_styled_line(BLUE, 1.0, LineStyle::Dotted, 0.0, 0.0, 5.0, 5.0)
+        .add_styled_circle(BLACK, WHITE, 3.0, LineStyle::Solid, 1.0, 2.0, 3.0)
+        .add_styled_polygon(GREEN, WHITE, 0.5, LineStyle::Dashed, vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)])
+        .add_polygon(GREEN, WHITE, vec![(0.0, 0.0), (2.0, 0.0), (0.0, 2.0)])
+        .to_vis_string("test");
+    assert!(output.contains("POLYLINES\n1\n#FF0000 1.5 DASHED 3 0 0 10 0 10 10\n"));
+    assert!(output.contains("LINES\n1\n#0000FF 1 1 0 0 5 5 DOTTED\n"));
+    assert!(output.contains("CIRCLES\n1\n#000000 #FFFFFF 1 1 2 3 3 SOLID\n"));
+    assert!(output.contains("POLYGONS\n2\n#00FF00 #FFFFFF 3 0 0 1 0 0 1 0.5 DASHED\n#00FF00 #FFFFFF 3 0 0 2 0 0 2\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_line_group_odd_points_drops_last() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_line_group(RED, 1.0, vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)])
+        .to_vis_string("test");
+    assert!(output.contains("LINES\n1\n#FF0000 1 1 0 0 1 1\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_arrows() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_arrow(RED, 2.0, 8.0, 0.0, 0.0, 10.0, 5.0)
+        .add_arrow_group(RED, 2.0, 8.0, vec![((1.0, 1.0), (2.0, 2.0))])
+        .add_arrow_group(BLUE, 1.0, 6.0, [((3.0, 3.0), (4.0, 4.0))])
+        .to_vis_string("test");
+    assert!(output.contains("ARROWS\n2\n"));
+    assert!(output.contains("#FF0000 2 8 2 0 0 10 5 1 1 2 2\n"));
+    assert!(output.contains("#0000FF 1 6 1 3 3 4 4\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_rects() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_rect(BLACK, RED, 0.0, 0.0, 10.0, 20.0)
+        .add_labeled_rect(BLACK, RED, 10.0, 0.0, 5.0, 5.0, "id 3".to_string())
+        .add_rect_group(BLACK, RED, vec![Rect::new(20.0, 0.0, 1.0, 1.0).with_label("a".to_string())])
+        .add_rect_group(BLACK, BLUE, [(1.0, 2.0, 3.0, 4.0)])
+        .to_vis_string("test");
+    assert!(output.contains("RECTS\n2\n"));
+    assert!(output.contains("#000000 #FF0000 3 L 0 0 10 20 \"\" 10 0 5 5 \"id 3\" 20 0 1 1 a\n"));
+    assert!(output.contains("#000000 #0000FF 1 N 1 2 3 4\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_groups_keep_insertion_order() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_circle(GREEN, WHITE, 1.0, 1.0, 1.0)
+        .add_circle(RED, WHITE, 2.0, 2.0, 1.0)
+        .add_circle(BLUE, WHITE, 3.0, 3.0, 1.0)
+        .add_circle(GREEN, WHITE, 4.0, 4.0, 1.0)
+        .to_vis_string("test");
+    assert!(output.contains("CIRCLES\n3\n#00FF00 #FFFFFF 2 1 1 1 4 4 1\n#FF0000 #FFFFFF 1 2 2 1\n#0000FF #FFFFFF 1 3 3 1\n"));
+    assert!(!output.contains("LAYER"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_layers() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .set_layer(1)
+        .add_polygon_with_tooltip(RED, WHITE, vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)], "top".to_string())
+        .set_layer(-1)
+        .add_circle_with_tooltip(BLACK, BLUE, 5.0, 5.0, 3.0, "bottom".to_string())
+        .add_polygon(RED, WHITE, vec![(0.0, 0.0), (2.0, 0.0), (0.0, 2.0)])
+        .set_layer(0)
+        .add_circle(BLACK, RED, 5.0, 5.0, 1.0)
+        .to_vis_string("test");
+    let expected = "LAYER -1\nCIRCLES\n1\n#000000 #0000FF 1 5 5 3\nPOLYGONS\n1\n#FF0000 #FFFFFF 3 0 0 2 0 0 2\n\
+                    LAYER 0\nCIRCLES\n1\n#000000 #FF0000 1 5 5 1\n\
+                    LAYER 1\nPOLYGONS\n1\n#FF0000 #FFFFFF 3 0 0 1 0 0 1\n\
+                    TOOLTIPS\n2\nC 0 0 bottom\nP 1 top\n";
+    assert!(output.ends_with(expected));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_view() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .set_view(-10.0, 30.0, -5.0, 5.0)
+        .set_y_up(true)
+        .set_keep_aspect(true)
+        .add_circle(BLACK, RED, -3.0, 2.0, 1.0)
+        .to_vis_string("test");
+    assert!(output.starts_with("$v(test) 2D_PLANE 10 40\nVIEW\n-10 30 -5 5 Y_UP KEEP_ASPECT\nCIRCLES\n"));
+
+    // 既定の座標系では VIEW を出力しない
+    let output = Vis2DPlane::new(10.0, 20.0, None).set_view(0.0, 20.0, 0.0, 10.0).to_vis_string("test");
+    assert!(!output.contains("VIEW"));
+    let output = Vis2DPlane::new(10.0, 20.0, None).set_y_up(true).to_vis_string("test");
+    assert!(output.contains("VIEW\n0 20 0 10 Y_UP STRETCH\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_empty_view_is_ignored() {
+    let output = Vis2DPlane::new(10.0, 10.0, None)
+        .set_view(-1.0, 1.0, -2.0, 2.0)
+        .set_view(1.0, 1.0, 0.0, 1.0)
+        .set_view(0.0, f64::NAN, 0.0, 1.0)
+        .to_vis_string("test");
+    assert!(output.starts_with("$v(test) 2D_PLANE 4 2\nVIEW\n-1 1 -2 2 Y_DOWN STRETCH\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_points() {
+    let customers = [(1.0, 2.0), (3.5, 4.0)];
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_point_group(MarkerShape::Circle, BLUE, 2.0, customers)
+        .add_point(MarkerShape::Cross, RED, 4.0, 50.0, 50.0)
+        .add_point(MarkerShape::Circle, BLUE, 2.0, 5.0, 6.0)
+        .to_vis_string("test");
+    assert!(output.contains("POINTS\n2\nCIRCLE #0000FF 2 3 1 2 3.5 4 5 6\nCROSS #FF0000 4 1 50 50\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_styled_text() {
+    let label = TextStyle::new()
+        .with_anchor(TextAnchor::BottomLeft)
+        .with_rotation(-30.0)
+        .with_background(WHITE, 2.0)
+        .with_offset(4.0, -4.0);
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_text(BLACK, 12.0, 10.0, 10.0, "plain".to_string())
+        .add_styled_text(BLACK, 12.0, label, 20.0, 30.0, "node 1".to_string())
+        .add_styled_text_group(BLACK, 12.0, label, [(40.0, 50.0, "2")])
+        .add_styled_text(RED, 10.0, TextStyle::new().with_anchor(TextAnchor::Top), 5.0, 5.0, "e".to_string())
+        .to_vis_string("test");
+    assert!(output.contains(
+        "TEXT\n3\n#000000 12 1 10 10 plain\n#000000 12 2 20 30 \"node 1\" 40 50 2 BL -30 #FFFFFF 2 4 -4\n#FF0000 10 1 5 5 e T 0 - 0 0 0\n"
+    ));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_vis2dplane_curves() {
+    let output = Vis2DPlane::new(100.0, 100.0, None)
+        .add_ellipse(BLACK, BLUE, 1.0, LineStyle::Solid, 50.0, 50.0, 20.0, 10.0, 30.0)
+        .add_arc(RED, 2.0, LineStyle::Dashed, 0.0, 0.0, 10.0, 0.0, 90.0)
+        .add_sector(BLACK, RED, 1.0, LineStyle::Solid, 0.0, 0.0, 10.0, 90.0, 180.0)
+        .add_arc_group(RED, 2.0, LineStyle::Dashed, [(5.0, 5.0, 1.0, -45.0, 45.0)])
+        .add_quadratic_bezier(BLUE, 1.0, LineStyle::Solid, (0.0, 0.0), (5.0, 10.0), (10.0, 0.0))
+        .add_cubic_bezier(BLUE, 1.0, LineStyle::Solid, (0.0, 0.0), (0.0, 5.0), (10.0, 5.0), (10.0, 0.0))
+        .add_quadratic_bezier(BLUE, 1.0, LineStyle::Solid, (1.0, 1.0), (2.0, 2.0), (3.0, 1.0))
+        .to_vis_string("test");
+    assert!(output.contains("ELLIPSES\n1\n#000000 #0000FF 1 SOLID 1 50 50 20 10 30\n"));
+    assert!(output.contains(
+        "ARCS\n2\nARC #FF0000 2 DASHED 2 0 0 10 0 90 5 5 1 -45 45\nSECTOR #000000 #FF0000 1 SOLID 1 0 0 10 90 180\n"
+    ));
+    assert!(output.contains(
+        "CURVES\n2\nQ #0000FF 1 SOLID 2 0 0 5 10 10 0 1 1 2 2 3 1\nC #0000FF 1 SOLID 1 0 0 0 5 10 5 10 0\n"
+    ));
+}
+
 #[cfg(feature = "vis")]
 #[test]
 fn test_vis2dplane_new() {
@@ -347,6 +1152,27 @@ fn test_textarea_empty_text() {
     assert!(output.contains("$v(test) TEXTAREA EmptyInfo 200 #000000 #ffffff \"\""));
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_textarea_multi_line() {
+    // 改行を含むテキストは TEXTAREA_LINES 形式で出力される
+    let textarea = VisTextArea::new("State".to_string(), "line1\n  line 2\n".to_string());
+    let output = VisFrame::new()
+        .add_textarea(textarea)
+        .to_vis_string("test");
+    assert!(output.contains("$v(test) TEXTAREA_LINES State 200 #000000 #ffffff 3\nline1\n  line 2\n\n"));
+    assert!(!output.contains("$v(test) TEXTAREA State"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_textarea_to_vis_string() {
+    let output = VisTextArea::new("Info".to_string(), "single line".to_string())
+        .height(100)
+        .to_vis_string("test");
+    assert_eq!(output, "$v(test) TEXTAREA Info 100 #000000 #ffffff single line\n");
+}
+
 #[cfg(feature = "vis")]
 #[test]
 fn test_visframe_with_2dplane() {
@@ -574,17 +1400,118 @@ mod vis_disabled_tests {
     fn test_visgrid_operations_compile() {
         // Verify that all operations compile and run without errors
         let output = VisGrid::new(10, 10, None)
+            .clip_out_of_range(true)
+            .set_tooltip((1, 1), "tip".to_string())
+            .show_axis_labels(5)
+            .set_row_labels(vec!["a".to_string()])
+            .set_col_labels(vec!["b".to_string()])
+            .set_viewport(0..5, 0..5)
+            .follow((5, 5), 4, 4)
             .update_cell_color((5, 5), RED)
+            .update_cell_color(GridPos::row_col(5, 5), RED)
             .update_text((5, 5), "test".to_string())
+            .update_text_style((5, 5), RED, 12.0)
+            .add_cell_text((5, 5), TextAnchor::TopLeft, BLACK, 8.0, "55".to_string())
+            .add_marker((5, 5), MarkerShape::Circle, BLACK, RED, 0.5)
+            .add_markers(vec![(1, 1), (2, 2)], MarkerShape::Cross, BLUE, BLUE, 0.3)
+            .add_polyline(vec![(0, 0), (1, 0)], RED, 2.0, LineStyle::Dotted)
+            .add_move_path((0, 0), "RRD", RED, 2.0, LineStyle::Solid)
+            .add_arrow((0, 0), (1, 1), BLUE, 2.0, LineStyle::Dashed)
+            .add_labeled_arrow((1, 1), (2, 2), BLUE, 2.0, LineStyle::Solid, "a".to_string())
             .add_line(vec![(0, 0), (1, 1), (2, 2)], BLUE)
             .remove_wall_vertical((1, 0))
             .remove_wall_horizontal((0, 1))
             .add_wall_vertical((2, 0))
             .add_wall_horizontal((0, 2))
+            .remove_wall(WallPos::Vertical { row: 1, col: 1 })
+            .add_wall(WallPos::Horizontal { row: 1, col: 1 })
+            .set_wall_style(WallPos::Horizontal { row: 1, col: 1 }, RED, 3.0)
+            .set_vertical_walls(&vec![vec![true; 9]; 10])
+            .set_horizontal_walls_from_strs(&["0101010101"])
             .to_vis_string("test");
         assert!(output.is_empty()); // Output should be empty when vis is disabled
     }
 
+    #[test]
+    fn test_visgrid_try_operations_compile() {
+        let grid = VisGrid::new(2, 2, None)
+            .try_update_cell_color(GridPos::xy(5, 5), RED)
+            .and_then(|g| g.try_update_text((0, 0), "a".to_string()))
+            .and_then(|g| g.try_update_text_style((0, 0), RED, 10.0))
+            .and_then(|g| g.try_add_cell_text((0, 0), TextAnchor::Center, RED, 10.0, "b".to_string()))
+            .and_then(|g| g.try_add_marker((0, 0), MarkerShape::Star, RED, RED, 0.5));
+        assert!(grid.is_ok()); // Range checks are skipped when vis is disabled
+    }
+
+    #[test]
+    fn test_vishexgrid_operations_compile() {
+        let grid = VisHexGrid::new(5, 5, HexOrientation::Pointy, None)
+            .set_bounds(ItemBounds::new(0.0, 0.0, 400.0, 400.0))
+            .update_cell_color((1, 1), RED)
+            .update_text(HexPos::Axial { q: 0, r: 0 }, "a".to_string())
+            .add_line(vec![(0, 0), (1, 0)], BLUE)
+            .remove_edge((0, 0), 3)
+            .remove_edge_between((0, 0), (1, 0))
+            .clip_out_of_range(true)
+            .try_update_cell_color((9, 9), RED)
+            .and_then(|g| g.try_update_text((9, 9), "b".to_string()))
+            .and_then(|g| g.try_add_line(vec![(9, 9)], RED))
+            .and_then(|g| g.try_remove_edge((9, 9), 0))
+            .unwrap();
+        assert_eq!(grid.neighbor((0, 0), 0), None);
+        let output = VisFrame::new().add_hex_grid(grid).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_vistrigrid_operations_compile() {
+        let grid = VisTriGrid::new(4, 7, None)
+            .set_bounds(ItemBounds::new(0.0, 0.0, 400.0, 300.0))
+            .update_cell_color((1, 1), RED)
+            .update_text(GridPos::row_col(0, 0), "a".to_string())
+            .add_line(vec![(0, 0), (1, 0)], BLUE)
+            .remove_edge((0, 0), 2)
+            .remove_edge_between((0, 0), (1, 0))
+            .clip_out_of_range(true)
+            .try_update_cell_color((9, 9), RED)
+            .and_then(|g| g.try_update_text((9, 9), "b".to_string()))
+            .and_then(|g| g.try_add_line(vec![(9, 9)], RED))
+            .and_then(|g| g.try_remove_edge((9, 9), 0))
+            .unwrap();
+        assert_eq!(grid.neighbor((0, 0), 0), None);
+        assert!(!VisTriGrid::is_up((0, 0)));
+        let output = VisFrame::new().add_tri_grid(grid).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_visgraph_operations_compile() {
+        let graph = VisGraph::new(100.0, 100.0, None)
+            .set_layout(GraphLayout::Layered)
+            .set_directed(true)
+            .set_node_radius(3.0)
+            .add_node(GraphNode::new(0).with_color(RED).with_label("s".to_string()).with_pos(0.0, 0.0))
+            .add_nodes([1, 2])
+            .add_edge(GraphEdge::new(0, 1).with_weight(2.0).with_color(BLUE).with_label("e".to_string()))
+            .add_edges([(1, 2, 1.5)]);
+        let _plane = graph.to_plane();
+        let output = VisFrame::new().add_graph(graph).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_vistree_operations_compile() {
+        let tree = VisTree::new(100.0, 100.0, None)
+            .add_node(TreeNode::new(0, None).with_score(1.0).with_status(TreeNodeStatus::Expanded).with_label("r".to_string()))
+            .add_nodes([(1, Some(0)), (2, Some(0))])
+            .highlight_path(2)
+            .set_max_depth(3)
+            .set_max_children(2);
+        let _plane = tree.to_plane();
+        let output = VisFrame::new().add_tree(tree).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
     #[test]
     fn test_visgrid_with_bounds_compiles() {
         let bounds = ItemBounds::new(0.0, 0.0, 400.0, 400.0);
@@ -603,6 +1530,41 @@ mod vis_disabled_tests {
                 YELLOW,
                 vec![(10.0, 10.0), (90.0, 10.0), (90.0, 90.0), (10.0, 90.0)],
             )
+            .add_circle_with_tooltip(RED, BLUE, 20.0, 20.0, 5.0, "tip".to_string())
+            .add_polygon_with_tooltip(RED, BLUE, vec![(0.0, 0.0), (1.0, 1.0)], "tip".to_string())
+            .add_circle_group(RED, BLUE, vec![(1.0, 2.0, 3.0)])
+            .add_circle_group(RED, BLUE, [Circle::new(1.0, 2.0, 3.0).with_tooltip("tip".to_string())])
+            .add_text_group(BLACK, 12.0, vec![(1.0, 2.0, "a"), (3.0, 4.0, "b")])
+            .add_text_group(BLACK, 12.0, [TextItem::new(1.0, 2.0, "c".to_string())])
+            .add_polygon_groups([PolygonGroup::new(RED, BLUE, vec![(0.0, 0.0)]).with_tooltip("tip".to_string())])
+            .add_polyline(RED, 1.0, LineStyle::Dotted, vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)])
+            .add_arrow(RED, 2.0, 8.0, 0.0, 0.0, 1.0, 1.0)
+            .set_layer(2)
+            .set_view(-1.0, 1.0, -1.0, 1.0)
+            .set_y_up(true)
+            .set_keep_aspect(true)
+            .add_point(MarkerShape::Square, RED, 3.0, 1.0, 1.0)
+            .add_point_group(MarkerShape::Triangle, RED, 3.0, vec![(1.0, 1.0)])
+            .add_styled_text(RED, 12.0, TextStyle::new().with_anchor(TextAnchor::Left).with_rotation(90.0), 0.0, 0.0, "t".to_string())
+            .add_styled_text_group(RED, 12.0, TextStyle::new().with_background(WHITE, 2.0).with_offset(1.0, 1.0), vec![(0.0, 0.0, "t")])
+            .add_ellipse(RED, BLUE, 1.0, LineStyle::Solid, 0.0, 0.0, 2.0, 1.0, 0.0)
+            .add_ellipse_group(RED, BLUE, 1.0, LineStyle::Solid, vec![(0.0, 0.0, 2.0, 1.0, 45.0)])
+            .add_arc(RED, 1.0, LineStyle::Dotted, 0.0, 0.0, 1.0, 0.0, 90.0)
+            .add_arc_group(RED, 1.0, LineStyle::Dotted, vec![(0.0, 0.0, 1.0, 0.0, 90.0)])
+            .add_sector(RED, BLUE, 1.0, LineStyle::Solid, 0.0, 0.0, 1.0, 0.0, 90.0)
+            .add_sector_group(RED, BLUE, 1.0, LineStyle::Solid, vec![(0.0, 0.0, 1.0, 0.0, 90.0)])
+            .add_quadratic_bezier(RED, 1.0, LineStyle::Solid, (0.0, 0.0), (1.0, 1.0), (2.0, 0.0))
+            .add_cubic_bezier(RED, 1.0, LineStyle::Solid, (0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0))
+            .add_rect(RED, BLUE, 0.0, 0.0, 1.0, 1.0)
+            .add_labeled_rect(RED, BLUE, 0.0, 0.0, 1.0, 1.0, "r".to_string())
+            .add_rect_group(RED, BLUE, [Rect::new(0.0, 0.0, 1.0, 1.0).with_label("r".to_string())])
+            .add_rect_group(RED, BLUE, vec![(0.0, 0.0, 1.0, 1.0)])
+            .add_arrow_group(RED, 2.0, 8.0, vec![((0.0, 0.0), (1.0, 1.0))])
+            .add_styled_line(RED, 1.0, LineStyle::Dashed, 0.0, 0.0, 1.0, 1.0)
+            .add_styled_circle(RED, BLUE, 3.0, LineStyle::Dashed, 1.0, 1.0, 1.0)
+            .add_styled_circle_group(RED, BLUE, 3.0, LineStyle::Solid, vec![(1.0, 2.0, 3.0)])
+            .add_styled_polygon(RED, BLUE, 1.0, LineStyle::Solid, vec![(0.0, 0.0)])
+            .add_polygon_groups([PolygonGroup::new(RED, BLUE, vec![(0.0, 0.0)]).with_stroke(1.0, LineStyle::Dashed)])
             .to_vis_string("test");
         assert!(output.is_empty());
     }
@@ -692,6 +1654,17 @@ mod vis_disabled_tests {
         assert_eq!(RED.to_string(), "");
         assert_eq!(Color::new(128, 128, 128).to_string(), "");
     }
+
+    #[test]
+    fn test_enum_display_matches_enabled() {
+        // 無効時も有効時と同じ型を format できる
+        assert_eq!(HexOrientation::Flat.to_string(), "FLAT");
+        assert_eq!(LineStyle::Dashed.to_string(), "DASHED");
+        assert_eq!(MarkerShape::Star.to_string(), "STAR");
+        assert_eq!(TextAnchor::BottomRight.to_string(), "BR");
+        assert_eq!(TreeNodeStatus::Pruned.to_string(), "PRUNED");
+        assert_eq!(WallPos::Vertical { row: 1, col: 2 }.to_string(), "V 1 2");
+    }
 }
 
 #[cfg(feature = "vis")]
@@ -763,6 +1736,138 @@ fn test_bar_graph_item_new() {
     assert_eq!(item.value, 42.5);
 }
 
+#[cfg(feature = "vis")]
+#[test]
+fn test_line_chart_auto_axes() {
+    let output = VisLineChart::new("Score".to_string())
+        .add_series("best score".to_string(), BLUE, vec![(0.0, 10.0), (1.0, 12.5)])
+        .add_series("temp".to_string(), RED, (0..3).map(|i| (i as f64, 100.0 - i as f64)))
+        .to_vis_string("test");
+    assert_eq!(
+        output,
+        "$v(test) LINE_CHART Score AUTO AUTO AUTO AUTO LINEAR -\n2\n\"best score\" #0000FF 2 0 10 1 12.5\ntemp #FF0000 3 0 100 1 99 2 98\n"
+    );
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_line_chart_fixed_axes_log_scale_and_marker() {
+    let chart = VisLineChart::new("SA state".to_string())
+        .set_x_range(0.0, 1000.0)
+        .set_y_range(0.01, 1.0)
+        .set_log_scale(true)
+        .set_marker(250.0)
+        .add_series("accept".to_string(), GREEN, vec![(0.0, 0.5)]);
+    let output = VisFrame::new().add_line_chart(chart).to_vis_string("test");
+    assert!(output.contains("$v(test) LINE_CHART \"SA state\" 0 1000 0.01 1 LOG 250\n1\naccept #00FF00 1 0 0.5\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_histogram_bin_count_and_summary() {
+    let histogram = VisHistogram::new("Delta".to_string(), RED)
+        .set_bins(HistogramBins::Count(3))
+        .add_samples(vec![1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, f64::NAN])
+        .add_sample(4.0);
+    assert_eq!(
+        histogram.to_vis_string("test"),
+        "$v(test) HISTOGRAM Delta #FF0000\n3 1 2 3 4 1 2 7\n10 3 1 1 2.25 3 4 4 4\n"
+    );
+    assert_eq!(
+        histogram.to_bar_graph().to_vis_string("test"),
+        "$v(test) BAR_GRAPH Delta #FF0000 0 7\n3 1 1 2 2 3 7\n"
+    );
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_histogram_fixed_width_and_range() {
+    // 範囲外のサンプルはビンに入らないが統計量には含まれる
+    let histogram = VisHistogram::new("path length".to_string(), BLUE)
+        .set_bins(HistogramBins::Width(0.5))
+        .set_range(0.0, 1.0)
+        .add_samples(vec![-1.0, 0.2, 0.7, 1.0, 2.0]);
+    let output = VisFrame::new().add_histogram(histogram).to_vis_string("test");
+    assert!(output.contains("$v(test) HISTOGRAM \"path length\" #0000FF\n2 0 0.5 1 1 2\n5 "));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_histogram_invalid_bins_and_range_are_ignored() {
+    // すべて等しいサンプルの min, max を範囲にしても panic せず、自動の範囲になる
+    let output = VisHistogram::new("Delta".to_string(), RED)
+        .set_bins(HistogramBins::Count(2))
+        .set_bins(HistogramBins::Count(0))
+        .set_bins(HistogramBins::Width(-1.0))
+        .set_range(3.0, 3.0)
+        .add_samples(vec![0.0, 1.0, 2.0])
+        .to_vis_string("test");
+    assert_eq!(output.lines().nth(1).unwrap(), "2 0 1 2 1 2");
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_histogram_freedman_diaconis_and_empty() {
+    // IQR = 3.5, n = 8 なので幅は 3.5
+    let output = VisHistogram::new("FD".to_string(), GREEN)
+        .add_samples((0..8).map(|i| i as f64))
+        .to_vis_string("test");
+    let bins = output.lines().nth(1).unwrap();
+    assert_eq!(bins, "2 0 3.5 7 4 4");
+
+    let empty = VisHistogram::new("FD".to_string(), GREEN).to_vis_string("test");
+    assert_eq!(empty, "$v(test) HISTOGRAM FD #00FF00\n0\n0\n");
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_histogram_ignores_infinite_samples() {
+    let output = VisHistogram::new("Delta".to_string(), RED)
+        .set_bins(HistogramBins::Count(2))
+        .add_sample(f64::INFINITY)
+        .add_samples(vec![0.0, f64::NEG_INFINITY, 1.0, 2.0, f64::INFINITY])
+        .to_vis_string("test");
+    assert_eq!(output, "$v(test) HISTOGRAM Delta #FF0000\n2 0 1 2 1 2\n3 1 0.816496580927726 0 0.5 1 1.5 1.9 2\n");
+    assert!(!output.contains("inf") && !output.contains("NaN"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_table_with_header_and_typed_cells() {
+    let table = VisTable::new("Agents".to_string(), vec!["id".to_string(), "pos".to_string(), "load".to_string()])
+        .add_row([TableCell::int(0), TableCell::text("(3, 5)".to_string()), TableCell::float(0.25, 2)])
+        .add_row([TableCell::int(1).with_color(YELLOW), TableCell::text(String::new()), TableCell::float(1.0 / 3.0, 3)]);
+    assert_eq!(
+        table.to_vis_string("test"),
+        "$v(test) TABLE Agents 2 3 HEADER\nid pos load\nI - 0 T - \"(3, 5)\" F - 0.25\nI #FFFF00 1 T - \"\" F - 0.333\n"
+    );
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_table_key_value() {
+    let table = VisTable::key_value("SA params".to_string())
+        .add_entry("iter".to_string(), TableCell::int(12000))
+        .add_entry("temp".to_string(), TableCell::float(3.5, 1).with_color(RED));
+    let output = VisFrame::new().add_table(table).to_vis_string("test");
+    assert!(output.contains("$v(test) TABLE \"SA params\" 2 2 NOHEADER\nT - iter I - 12000\nT - temp F #FF0000 3.5\n"));
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_table_short_row_is_padded() {
+    let table = VisTable::new("T".to_string(), vec!["a".to_string(), "b".to_string()]).add_row([TableCell::int(1)]);
+    assert_eq!(table.to_vis_string("test"), "$v(test) TABLE T 1 2 HEADER\na b\nI - 1 T - \"\"\n");
+}
+
+#[cfg(feature = "vis")]
+#[test]
+fn test_table_long_row_is_truncated() {
+    let table = VisTable::new("T".to_string(), vec!["a".to_string(), "b".to_string()])
+        .add_row([TableCell::int(1), TableCell::int(2), TableCell::int(3)]);
+    assert_eq!(table.to_vis_string("test"), "$v(test) TABLE T 1 2 HEADER\na b\nI - 1 I - 2\n");
+}
+
 #[cfg(not(feature = "vis"))]
 mod bar_graph_disabled_tests {
     use super::super::ahc_vdsl::ahc_vdsl::*;
@@ -784,4 +1889,39 @@ mod bar_graph_disabled_tests {
             .to_vis_string("test");
         assert!(output.is_empty());
     }
+
+    #[test]
+    fn test_line_chart_operations_compile() {
+        let chart = VisLineChart::new("Score".to_string())
+            .set_x_range(0.0, 10.0)
+            .set_y_range(1.0, 100.0)
+            .set_log_scale(true)
+            .set_marker(3.0)
+            .add_series("s".to_string(), RED, vec![(0.0, 1.0)]);
+        let output = VisFrame::new().add_line_chart(chart).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_histogram_operations_compile() {
+        let histogram = VisHistogram::new("Delta".to_string(), RED)
+            .set_bins(HistogramBins::Width(0.5))
+            .set_range(0.0, 10.0)
+            .add_sample(1.0)
+            .add_samples(vec![2.0, 3.0]);
+        assert!(histogram.to_bar_graph().to_vis_string("test").is_empty());
+        let output = VisFrame::new().add_histogram(histogram).to_vis_string("test");
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_table_operations_compile() {
+        let table = VisTable::new("Agents".to_string(), vec!["id".to_string()])
+            .add_row([TableCell::int(0).with_color(RED)]);
+        let params = VisTable::key_value("Params".to_string())
+            .add_entry("temp".to_string(), TableCell::float(1.5, 2))
+            .add_entry("name".to_string(), TableCell::text("sa".to_string()));
+        let output = VisFrame::new().add_table(table).add_table(params).to_vis_string("test");
+        assert!(output.is_empty());
+    }
 }
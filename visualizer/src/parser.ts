@@ -1,4 +1,24 @@
-import { ParsedModes, Frame, GridCommand, Command, GridLine, TwoDPlaneCommand, CircleGroup, LineGroup, PolygonGroup, TextGroup, TextItem, CanvasCommand, ItemBounds, BarGraphCommand, BarGraphItem } from './types';
+import { Point, ParsedModes, Frame, GridCommand, HexGridCommand, TriGridCommand, HexOrientation, HexEdge, Command, GridLine, GridTextGroup, GridTextItem, TextAnchor, GridMarkerGroup, MarkerShape, GridPolyline, GridArrowGroup, GridArrow, LineStyle, GridWallStyle, GridAxisLabel, TwoDPlaneCommand, PlaneLayer, PlaneView, Circle, PolygonGroup, PlaneRect, PlanePointGroup, PlaneArrow, EllipseGroup, ArcGroup, CurveGroup, TextItem, TextGroup, CanvasCommand, ItemBounds, BarGraphCommand, BarGraphItem, LineChartCommand, LineChartSeries, HistogramCommand, TableCommand, TableCell } from './types';
+
+const TEXT_ANCHORS: TextAnchor[] = ['C', 'T', 'B', 'L', 'R', 'TL', 'TR', 'BL', 'BR'];
+const MARKER_SHAPES: MarkerShape[] = ['CIRCLE', 'SQUARE', 'DIAMOND', 'TRIANGLE', 'CROSS', 'STAR'];
+const LINE_STYLES: LineStyle[] = ['SOLID', 'DASHED', 'DOTTED'];
+
+// Split a line into tokens, treating "..." as a single token
+function tokenizeQuoted(line: string): string[] {
+    const regex = /"([^"]*)"|([^\s]+)/g;
+    const tokens: string[] = [];
+    let match;
+    while ((match = regex.exec(line)) !== null) {
+        tokens.push(match[1] !== undefined ? match[1] : match[2]);
+    }
+    return tokens;
+}
+
+// Tooltips are written on a single line with "\n" for newlines and "\\" for backslashes
+function unescapeTooltip(text: string): string {
+    return text.replace(/\\(.)/g, (_, c) => (c === 'n' ? '\n' : c));
+}
 
 interface PendingCommands {
     [mode: string]: Command[];
@@ -63,7 +83,7 @@ export function parseStderr(stderrText: string): ParsedModes {
         if (cmd === 'COMMIT') {
             if (pendingCommands[mode].length > 0) {
                 // Check for overlapping items
-                const items = pendingCommands[mode].filter(c => c.type === 'GRID' || c.type === '2D_PLANE');
+                const items = pendingCommands[mode].filter(c => c.type === 'GRID' || c.type === 'HEX_GRID' || c.type === 'TRI_GRID' || c.type === '2D_PLANE');
                 if (items.length > 1) {
                     // Get canvas size
                     const canvasCmd = pendingCommands[mode].find(c => c.type === 'CANVAS') as CanvasCommand | undefined;
@@ -73,8 +93,8 @@ export function parseStderr(stderrText: string): ParsedModes {
                     // Check for overlaps
                     for (let i = 0; i < items.length; i++) {
                         for (let j = i + 1; j < items.length; j++) {
-                            const item1 = items[i] as GridCommand | TwoDPlaneCommand;
-                            const item2 = items[j] as GridCommand | TwoDPlaneCommand;
+                            const item1 = items[i] as GridCommand | HexGridCommand | TriGridCommand | TwoDPlaneCommand;
+                            const item2 = items[j] as GridCommand | HexGridCommand | TriGridCommand | TwoDPlaneCommand;
 
                             const bounds1 = item1.bounds || { left: 0, top: 0, right: canvasW, bottom: canvasH };
                             const bounds2 = item2.bounds || { left: 0, top: 0, right: canvasW, bottom: canvasH };
@@ -134,6 +154,9 @@ export function parseStderr(stderrText: string): ParsedModes {
                     lineIdx++;
                 }
             }
+        } else if (cmd === 'TEXTAREA_LINES') {
+            const result = parseTextAreaLinesCommand(lines, lineIdx, parts, mode, pendingRawText, pendingCommands, pendingErrors);
+            lineIdx = result.lineIdx;
         } else if (cmd === 'SCORE') {
             const sIndex = remaining.indexOf('SCORE');
             const score = remaining.substring(sIndex + 5).trim();
@@ -142,12 +165,24 @@ export function parseStderr(stderrText: string): ParsedModes {
         } else if (cmd === 'GRID' || cmd.startsWith('GRID(')) {
             const result = parseGridCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
             lineIdx = result.lineIdx;
+        } else if (cmd === 'HEX_GRID' || cmd.startsWith('HEX_GRID(') || cmd === 'TRI_GRID' || cmd.startsWith('TRI_GRID(')) {
+            const result = parseHexGridCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
+            lineIdx = result.lineIdx;
         } else if (cmd === '2D_PLANE' || cmd.startsWith('2D_PLANE(')) {
             const result = parse2DPlaneCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
             lineIdx = result.lineIdx;
         } else if (cmd === 'BAR_GRAPH') {
             const result = parseBarGraphCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
             lineIdx = result.lineIdx;
+        } else if (cmd === 'LINE_CHART') {
+            const result = parseLineChartCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
+            lineIdx = result.lineIdx;
+        } else if (cmd === 'HISTOGRAM') {
+            const result = parseHistogramCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
+            lineIdx = result.lineIdx;
+        } else if (cmd === 'TABLE') {
+            const result = parseTableCommand(lines, lineIdx, remaining, mode, pendingRawText, pendingCommands, pendingErrors);
+            lineIdx = result.lineIdx;
         } else {
             // Unknown command
             if (cmd && cmd.length > 0) {
@@ -206,6 +241,63 @@ function parseCanvasCommand(
     return { lineIdx: lineIdx + 1 };
 }
 
+function parseTextAreaLinesCommand(
+    lines: string[],
+    lineIdx: number,
+    parts: string[],
+    mode: string,
+    pendingRawText: PendingRawText,
+    pendingCommands: PendingCommands,
+    pendingErrors: PendingErrors
+): { lineIdx: number } {
+    // parts: TEXTAREA_LINES TITLE HEIGHT TEXT_COLOR FILL_COLOR N
+    if (parts.length < 6) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TEXTAREA_LINES requires 5 parameters: TITLE HEIGHT TEXT_COLOR FILL_COLOR N`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const title = parts[1];
+    const height = parseInt(parts[2]);
+    const textColor = parts[3];
+    const fillColor = parts[4];
+    const n = parseInt(parts[5]);
+
+    if (isNaN(height)) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TEXTAREA_LINES height must be a number, got '${parts[2]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (!textColor.startsWith('#')) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TEXTAREA_LINES text color must start with #, got '${textColor}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (!fillColor.startsWith('#')) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TEXTAREA_LINES fill color must start with #, got '${fillColor}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (isNaN(n) || n < 0) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TEXTAREA_LINES line count must be a non-negative integer, got '${parts[5]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const startLineIdx = lineIdx;
+    lineIdx++;
+
+    // Following N lines are taken verbatim (no trimming, no $v prefix)
+    const textLines: string[] = [];
+    for (let i = 0; i < n; i++) {
+        if (lineIdx >= lines.length) {
+            pendingErrors[mode].push(`Line ${startLineIdx + 1}: TEXTAREA_LINES expects ${n} lines, but only ${i} lines found`);
+            break;
+        }
+        pendingRawText[mode] += lines[lineIdx] + "\n";
+        textLines.push(lines[lineIdx].replace(/\r$/, ''));
+        lineIdx++;
+    }
+
+    pendingCommands[mode].push({ type: 'TEXTAREA', title, height, textColor, fillColor, text: textLines.join('\n') });
+    return { lineIdx };
+}
+
 function parseBoundsFromCommand(commandStr: string): ItemBounds | undefined {
     // Parse bounds like GRID(min_x, min_y, max_x, max_y) or 2D_PLANE(min_x, min_y, max_x, max_y)
     const match = commandStr.match(/\(([^)]+)\)/);
@@ -280,7 +372,11 @@ function parseGridCommand(
     const gridTexts: string[][] = [];
     for (let r = 0; r < H; r++) gridTexts.push(new Array(W).fill(""));
 
+    const gridTextGroups: GridTextGroup[] = [];
+    const gridMarkerGroups: GridMarkerGroup[] = [];
     const gridLines: GridLine[] = [];
+    const gridPolylines: GridPolyline[] = [];
+    const gridArrowGroups: GridArrowGroup[] = [];
 
     // Initialize walls with default values (all walls exist)
     // wallVertical: H rows, each row has W+1 characters (for W+1 vertical lines)
@@ -297,6 +393,19 @@ function parseGridCommand(
         wallHorizontal.push('Y'.repeat(W));
     }
 
+    // wallStyles["H r c"] / wallStyles["V r c"]: color and width of each wall
+    const wallStyles: { [key: string]: GridWallStyle } = {};
+
+    // Original indices of the top-left cell when the grid is a cropped viewport
+    let rowOffset = 0;
+    let colOffset = 0;
+
+    // Labels drawn outside the cell area (AXIS_LABELS section)
+    const axisLabels: { rows: GridAxisLabel[], cols: GridAxisLabel[] } = { rows: [], cols: [] };
+
+    // Hover text per cell (TOOLTIPS section), keyed by "y,x"
+    const tooltips: { [key: string]: string } = {};
+
     lineIdx++;
 
     while (lineIdx < lines.length) {
@@ -307,7 +416,7 @@ function parseGridCommand(
             header = lines[lineIdx].trim();
         }
 
-        if (header !== 'CELL_COLORS' && header !== 'CELL_COLORS_POS' && header !== 'CELL_TEXT' && header !== 'LINES' && header !== 'WALL_VERTICAL' && header !== 'WALL_HORIZONTAL') {
+        if (header !== 'CELL_COLORS' && header !== 'CELL_COLORS_POS' && header !== 'CELL_TEXT' && header !== 'CELL_TEXT_EX' && header !== 'CELL_MARKERS' && header !== 'LINES' && header !== 'POLYLINES' && header !== 'ARROWS' && header !== 'WALL_VERTICAL' && header !== 'WALL_HORIZONTAL' && header !== 'WALL_STYLES' && header !== 'OFFSET' && header !== 'AXIS_LABELS' && header !== 'TOOLTIPS') {
             break;
         }
 
@@ -368,6 +477,172 @@ function parseGridCommand(
                 }
                 lineIdx++;
             }
+        } else if (header === 'CELL_TEXT_EX') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color fontSize anchor count x0 y0 t0 x1 y1 t1 ...
+                    const tokens = tokenizeQuoted(lines[lineIdx].trim());
+                    if (tokens.length >= 4) {
+                        const color = tokens[0];
+                        const fontSize = parseFloat(tokens[1]);
+                        const anchor = tokens[2] as TextAnchor;
+                        const count = parseInt(tokens[3]);
+                        if (!TEXT_ANCHORS.includes(anchor)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: CELL_TEXT_EX unknown anchor '${tokens[2]}'`);
+                        } else {
+                            const texts: GridTextItem[] = [];
+                            for (let j = 0; j < count; j++) {
+                                const baseIdx = 4 + j * 3;
+                                if (baseIdx + 2 < tokens.length) {
+                                    const x = parseInt(tokens[baseIdx]);
+                                    const y = parseInt(tokens[baseIdx + 1]);
+                                    if (y >= 0 && y < H && x >= 0 && x < W) {
+                                        texts.push({ x, y, text: tokens[baseIdx + 2] });
+                                    }
+                                }
+                            }
+                            if (texts.length > 0) {
+                                gridTextGroups.push({ color, fontSize: isNaN(fontSize) ? 0 : fontSize, anchor, texts });
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'CELL_MARKERS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: shape strokeColor fillColor size count x0 y0 x1 y1 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 5) {
+                        const shape = lParts[0] as MarkerShape;
+                        const strokeColor = lParts[1];
+                        const fillColor = lParts[2];
+                        const size = parseFloat(lParts[3]);
+                        const count = parseInt(lParts[4]);
+                        if (!MARKER_SHAPES.includes(shape)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: CELL_MARKERS unknown shape '${lParts[0]}'`);
+                        } else {
+                            const points = [];
+                            for (let j = 0; j < count; j++) {
+                                const baseIdx = 5 + j * 2;
+                                if (baseIdx + 1 < lParts.length) {
+                                    const x = parseInt(lParts[baseIdx]);
+                                    const y = parseInt(lParts[baseIdx + 1]);
+                                    if (y >= 0 && y < H && x >= 0 && x < W) {
+                                        points.push({ x, y });
+                                    }
+                                }
+                            }
+                            if (points.length > 0) {
+                                gridMarkerGroups.push({ shape, strokeColor, fillColor, size: isNaN(size) ? 0.5 : size, points });
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'TOOLTIPS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: x y text (the rest of the line)
+                    const match = lines[lineIdx].replace(/\r$/, '').match(/^\s*(\d+)\s+(\d+) ?(.*)$/);
+                    if (match) {
+                        const x = parseInt(match[1]);
+                        const y = parseInt(match[2]);
+                        if (y >= 0 && y < H && x >= 0 && x < W) {
+                            tooltips[`${y},${x}`] = unescapeTooltip(match[3]);
+                        }
+                    } else {
+                        pendingErrors[mode].push(`Line ${lineIdx + 1}: TOOLTIPS expects 'x y text'`);
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'AXIS_LABELS') {
+            // Two lines: row labels and column labels, each "count i0 label0 i1 label1 ..."
+            lineIdx++;
+            for (const [target, n] of [[axisLabels.rows, H], [axisLabels.cols, W]] as [GridAxisLabel[], number][]) {
+                if (lineIdx >= lines.length) break;
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const tokens = tokenizeQuoted(lines[lineIdx].trim());
+                const count = parseInt(tokens[0]);
+                for (let j = 0; j < count; j++) {
+                    const baseIdx = 1 + j * 2;
+                    if (baseIdx + 1 < tokens.length) {
+                        const index = parseInt(tokens[baseIdx]);
+                        if (index >= 0 && index < n) {
+                            target.push({ index, text: tokens[baseIdx + 1] });
+                        }
+                    }
+                }
+                lineIdx++;
+            }
+        } else if (header === 'OFFSET') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const oParts = lines[lineIdx].trim().split(/\s+/);
+                const r0 = parseInt(oParts[0]);
+                const c0 = parseInt(oParts[1]);
+                if (isNaN(r0) || isNaN(c0)) {
+                    pendingErrors[mode].push(`Line ${lineIdx + 1}: OFFSET expects 2 integers`);
+                } else {
+                    rowOffset = r0;
+                    colOffset = c0;
+                }
+                lineIdx++;
+            }
+        } else if (header === 'WALL_STYLES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color width count dir0 r0 c0 dir1 r1 c1 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 3) {
+                        const color = lParts[0];
+                        const width = parseFloat(lParts[1]);
+                        const count = parseInt(lParts[2]);
+                        for (let j = 0; j < count; j++) {
+                            const baseIdx = 3 + j * 3;
+                            if (baseIdx + 2 < lParts.length) {
+                                const dir = lParts[baseIdx];
+                                const r = parseInt(lParts[baseIdx + 1]);
+                                const c = parseInt(lParts[baseIdx + 2]);
+                                if (dir !== 'H' && dir !== 'V') {
+                                    pendingErrors[mode].push(`Line ${lineIdx + 1}: WALL_STYLES unknown direction '${dir}'`);
+                                    continue;
+                                }
+                                wallStyles[`${dir} ${r} ${c}`] = { color, width: isNaN(width) ? 2 : width };
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
         } else if (header === 'WALL_VERTICAL') {
             // H rows, each row has W+1 characters
             const startLineIdx = lineIdx;
@@ -436,14 +711,88 @@ function parseGridCommand(
                     lineIdx++;
                 }
             }
+        } else if (header === 'POLYLINES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color width style count x0 y0 x1 y1 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 4) {
+                        const color = lParts[0];
+                        const width = parseFloat(lParts[1]);
+                        const style = lParts[2] as LineStyle;
+                        const numPoints = parseInt(lParts[3]);
+                        if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: POLYLINES unknown line style '${lParts[2]}'`);
+                        } else {
+                            const points = [];
+                            for (let p = 0; p < numPoints; p++) {
+                                if (4 + p * 2 + 1 < lParts.length) {
+                                    const x = parseInt(lParts[4 + p * 2]);
+                                    const y = parseInt(lParts[4 + p * 2 + 1]);
+                                    points.push({ x, y });
+                                }
+                            }
+                            if (points.length > 0) {
+                                gridPolylines.push({ color, width: isNaN(width) ? 3 : width, style, points });
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'ARROWS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color width style count x1 y1 x2 y2 label ...
+                    const tokens = tokenizeQuoted(lines[lineIdx].trim());
+                    if (tokens.length >= 4) {
+                        const color = tokens[0];
+                        const width = parseFloat(tokens[1]);
+                        const style = tokens[2] as LineStyle;
+                        const count = parseInt(tokens[3]);
+                        if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: ARROWS unknown line style '${tokens[2]}'`);
+                        } else {
+                            const arrows: GridArrow[] = [];
+                            for (let j = 0; j < count; j++) {
+                                const baseIdx = 4 + j * 5;
+                                if (baseIdx + 4 < tokens.length) {
+                                    const from = { x: parseInt(tokens[baseIdx]), y: parseInt(tokens[baseIdx + 1]) };
+                                    const to = { x: parseInt(tokens[baseIdx + 2]), y: parseInt(tokens[baseIdx + 3]) };
+                                    if (!isNaN(from.x) && !isNaN(from.y) && !isNaN(to.x) && !isNaN(to.y)) {
+                                        arrows.push({ from, to, label: tokens[baseIdx + 4] });
+                                    }
+                                }
+                            }
+                            if (arrows.length > 0) {
+                                gridArrowGroups.push({ color, width: isNaN(width) ? 2 : width, style, arrows });
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
         }
     }
 
     const gridCommand: GridCommand = {
         type: 'GRID',
         H, W, borderColor, textColor,
-        gridColors, gridTexts, gridLines,
-        wallVertical, wallHorizontal,
+        gridColors, gridTexts, gridTextGroups, gridMarkerGroups, gridLines, gridPolylines, gridArrowGroups,
+        wallVertical, wallHorizontal, wallStyles,
+        rowOffset, colOffset, axisLabels, tooltips,
         bounds
     };
 
@@ -451,6 +800,188 @@ function parseGridCommand(
     return { lineIdx };
 }
 
+function parseHexGridCommand(
+    lines: string[],
+    lineIdx: number,
+    remaining: string,
+    mode: string,
+    pendingRawText: PendingRawText,
+    pendingCommands: PendingCommands,
+    pendingErrors: PendingErrors
+): { lineIdx: number } {
+    // TRI_GRID shares the sections of HEX_GRID but has no orientation and 3 edge directions
+    const tri = remaining.startsWith('TRI_GRID');
+    const name = tri ? 'TRI_GRID' : 'HEX_GRID';
+    const numDirs = tri ? 3 : 6;
+    const bounds = parseBoundsFromCommand(remaining);
+    const paramStr = remaining.replace(/^(HEX|TRI)_GRID(\([^)]+\))?\s*/, '').trim();
+    const parts = paramStr.split(/\s+/);
+    if (tri) {
+        parts.splice(2, 0, 'POINTY');
+    }
+
+    if (parts.length < 6) {
+        const expected = tri ? '5 parameters (H W borderColor textColor defaultCellColor)' : '6 parameters (H W orientation borderColor textColor defaultCellColor)';
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: ${name} command requires ${expected}, got ${parts.length - (tri ? 1 : 0)}`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const H = parseInt(parts[0]);
+    const W = parseInt(parts[1]);
+    const orientation = parts[2] as HexOrientation;
+    const borderColor = parts[3];
+    const textColor = parts[4];
+    const defaultCellColor = parts[5];
+
+    if (isNaN(H) || isNaN(W) || H <= 0 || W <= 0) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: ${name} H and W must be positive integers, got H='${parts[0]}' W='${parts[1]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (orientation !== 'POINTY' && orientation !== 'FLAT') {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: HEX_GRID orientation must be POINTY or FLAT, got '${parts[2]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const cellColors: string[][] = [];
+    for (let r = 0; r < H; r++) cellColors.push(new Array(W).fill(defaultCellColor));
+    const cellTexts: string[][] = [];
+    for (let r = 0; r < H; r++) cellTexts.push(new Array(W).fill(""));
+    const hexLines: GridLine[] = [];
+    const hiddenEdges: HexEdge[] = [];
+
+    lineIdx++;
+
+    while (lineIdx < lines.length) {
+        let header = lines[lineIdx].trim();
+        while (header === '' && lineIdx < lines.length - 1) {
+            pendingRawText[mode] += lines[lineIdx] + "\n";
+            lineIdx++;
+            header = lines[lineIdx].trim();
+        }
+
+        if (header !== 'CELL_COLORS_POS' && header !== 'CELL_TEXT' && header !== 'LINES' && header !== 'HIDDEN_EDGES') {
+            break;
+        }
+
+        pendingRawText[mode] += lines[lineIdx] + "\n";
+        lineIdx++;
+
+        if (header === 'CELL_TEXT') {
+            for (let i = 0; i < H; i++) {
+                if (lineIdx >= lines.length) break;
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const tokens = tokenizeQuoted(lines[lineIdx].trim());
+                for (let c = 0; c < Math.min(W, tokens.length); c++) {
+                    cellTexts[i][c] = tokens[c];
+                }
+                lineIdx++;
+            }
+        } else if (header === 'HIDDEN_EDGES') {
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                // Parse: count x0 y0 dir0 x1 y1 dir1 ...
+                const eParts = lines[lineIdx].trim().split(/\s+/);
+                const count = parseInt(eParts[0]);
+                for (let j = 0; j < count; j++) {
+                    const baseIdx = 1 + j * 3;
+                    if (baseIdx + 2 < eParts.length) {
+                        const x = parseInt(eParts[baseIdx]);
+                        const y = parseInt(eParts[baseIdx + 1]);
+                        const dir = parseInt(eParts[baseIdx + 2]);
+                        if (y >= 0 && y < H && x >= 0 && x < W && dir >= 0 && dir < numDirs) {
+                            hiddenEdges.push({ x, y, dir });
+                        }
+                    }
+                }
+                lineIdx++;
+            }
+        } else if (lineIdx < lines.length) {
+            // CELL_COLORS_POS / LINES: group count, then "color count x0 y0 x1 y1 ..."
+            pendingRawText[mode] += lines[lineIdx] + "\n";
+            const n = parseInt(lines[lineIdx].trim());
+            lineIdx++;
+            for (let k = 0; k < n; k++) {
+                if (lineIdx >= lines.length) break;
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const lParts = lines[lineIdx].trim().split(/\s+/);
+                if (lParts.length >= 2) {
+                    const color = lParts[0];
+                    const count = parseInt(lParts[1]);
+                    const points = [];
+                    for (let j = 0; j < count; j++) {
+                        if (2 + j * 2 + 1 < lParts.length) {
+                            const x = parseInt(lParts[2 + j * 2]);
+                            const y = parseInt(lParts[2 + j * 2 + 1]);
+                            if (y >= 0 && y < H && x >= 0 && x < W) {
+                                points.push({ x, y });
+                            }
+                        }
+                    }
+                    if (header === 'CELL_COLORS_POS') {
+                        for (const pt of points) cellColors[pt.y][pt.x] = color;
+                    } else if (points.length > 0) {
+                        hexLines.push({ color, points });
+                    }
+                }
+                lineIdx++;
+            }
+        }
+    }
+
+    if (tri) {
+        const triGridCommand: TriGridCommand = {
+            type: 'TRI_GRID',
+            H, W, borderColor, textColor,
+            cellColors, cellTexts,
+            lines: hexLines,
+            hiddenEdges,
+            bounds
+        };
+        pendingCommands[mode].push(triGridCommand);
+        return { lineIdx };
+    }
+
+    const hexGridCommand: HexGridCommand = {
+        type: 'HEX_GRID',
+        H, W, orientation, borderColor, textColor,
+        cellColors, cellTexts,
+        lines: hexLines,
+        hiddenEdges,
+        bounds
+    };
+
+    pendingCommands[mode].push(hexGridCommand);
+    return { lineIdx };
+}
+
+// Parse the optional "[width] [style]" suffix that follows the coordinates of a CIRCLES/POLYGONS group
+function parseStrokeSuffix(parts: string[], idx: number): { lineWidth?: number; style?: LineStyle } {
+    const result: { lineWidth?: number; style?: LineStyle } = {};
+    const lineWidth = parseFloat(parts[idx]);
+    if (!isNaN(lineWidth)) result.lineWidth = lineWidth;
+    const style = parts[idx + 1] as LineStyle;
+    if (LINE_STYLES.includes(style)) result.style = style;
+    return result;
+}
+
+// Optional text style suffix: anchor rotation background padding dx dy ("-" means no background)
+function parseTextStyleSuffix(parts: string[], idx: number): Partial<TextGroup> {
+    const result: Partial<TextGroup> = {};
+    if (idx + 5 >= parts.length) return result;
+    const anchor = parts[idx] as TextAnchor;
+    if (TEXT_ANCHORS.includes(anchor)) result.anchor = anchor;
+    const rotation = parseFloat(parts[idx + 1]);
+    if (!isNaN(rotation)) result.rotation = rotation;
+    if (parts[idx + 2] !== '-') result.background = parts[idx + 2];
+    const padding = parseFloat(parts[idx + 3]);
+    if (!isNaN(padding)) result.padding = padding;
+    const offsetX = parseFloat(parts[idx + 4]);
+    if (!isNaN(offsetX)) result.offsetX = offsetX;
+    const offsetY = parseFloat(parts[idx + 5]);
+    if (!isNaN(offsetY)) result.offsetY = offsetY;
+    return result;
+}
+
 function parse2DPlaneCommand(
     lines: string[],
     lineIdx: number,
@@ -491,10 +1022,18 @@ function parse2DPlaneCommand(
         return { lineIdx: lineIdx + 1 };
     }
 
-    const circleGroups: CircleGroup[] = [];
-    const lineGroups: LineGroup[] = [];
-    const polygonGroups: PolygonGroup[] = [];
-    const textGroups: TextGroup[] = [];
+    const newLayer = (): PlaneLayer => ({
+        circleGroups: [], lineGroups: [], polylines: [], arrowGroups: [], polygonGroups: [], rectGroups: [], pointGroups: [], textGroups: [], curveGroups: [], ellipseGroups: [], arcGroups: []
+    });
+    let layer = newLayer();
+    const layers: PlaneLayer[] = [layer];
+    // z of each layer; sections before the first LAYER line belong to layer 0
+    const layerZ: number[] = [0];
+    let view: PlaneView = { minX: 0, maxX: W, minY: 0, maxY: H, yUp: false, keepAspect: false };
+
+    // Shapes in output order, so that TOOLTIPS can refer to them by index
+    const circleRefs: (Circle | undefined)[][] = [];
+    const polygonRefs: PolygonGroup[] = [];
 
     lineIdx++;
 
@@ -506,18 +1045,49 @@ function parse2DPlaneCommand(
             header = lines[lineIdx].trim();
         }
 
-        if (header !== 'CIRCLES' && header !== 'LINES' && header !== 'POLYGONS' && header !== 'TEXT') {
+        const isLayer = /^LAYER(\s|$)/.test(header);
+        if (!isLayer && header !== 'CIRCLES' && header !== 'LINES' && header !== 'POLYLINES' && header !== 'CURVES' && header !== 'ARROWS' && header !== 'ELLIPSES' && header !== 'ARCS' && header !== 'POLYGONS' && header !== 'RECTS' && header !== 'POINTS' && header !== 'TEXT' && header !== 'TOOLTIPS' && header !== 'VIEW') {
             break;
         }
 
         pendingRawText[mode] += lines[lineIdx] + "\n";
 
-        if (header === 'CIRCLES') {
+        if (isLayer) {
+            // "LAYER z": following sections belong to layer z
+            const zStr = header.substring(5).trim();
+            let z = zStr === '' ? 0 : parseInt(zStr);
+            if (isNaN(z)) {
+                pendingErrors[mode].push(`Line ${lineIdx + 1}: LAYER expects an integer z`);
+                z = 0;
+            }
+            if (Object.values(layer).some(groups => groups.length > 0)) {
+                layer = newLayer();
+                layers.push(layer);
+                layerZ.push(z);
+            } else {
+                layerZ[layerZ.length - 1] = z;
+            }
+            lineIdx++;
+        } else if (header === 'VIEW') {
             lineIdx++;
             if (lineIdx < lines.length) {
                 pendingRawText[mode] += lines[lineIdx] + "\n";
-                const cn = parseInt(lines[lineIdx].trim());
-                lineIdx++;
+                // Parse: minX maxX minY maxY Y_UP|Y_DOWN KEEP_ASPECT|STRETCH
+                const vParts = lines[lineIdx].trim().split(/\s+/);
+                const [minX, maxX, minY, maxY] = vParts.slice(0, 4).map(parseFloat);
+                if (vParts.length < 4 || [minX, maxX, minY, maxY].some(isNaN) || minX >= maxX || minY >= maxY) {
+                    pendingErrors[mode].push(`Line ${lineIdx + 1}: VIEW expects 'minX maxX minY maxY' with minX < maxX and minY < maxY`);
+                } else {
+                    view = { minX, maxX, minY, maxY, yUp: vParts[4] === 'Y_UP', keepAspect: vParts[5] === 'KEEP_ASPECT' };
+                }
+                lineIdx++;
+            }
+        } else if (header === 'CIRCLES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const cn = parseInt(lines[lineIdx].trim());
+                lineIdx++;
                 for (let k = 0; k < cn; k++) {
                     if (lineIdx >= lines.length) break;
                     pendingRawText[mode] += lines[lineIdx] + "\n";
@@ -527,20 +1097,25 @@ function parse2DPlaneCommand(
                         const lineColor = lParts[0];
                         const fillColor = lParts[1];
                         const circleCount = parseInt(lParts[2]);
-                        const circles = [];
+                        const circles: Circle[] = [];
+                        const refs: (Circle | undefined)[] = [];
                         for (let j = 0; j < circleCount; j++) {
                             const baseIdx = 3 + j * 3;
+                            let circle: Circle | undefined;
                             if (baseIdx + 2 < lParts.length) {
                                 const x = parseFloat(lParts[baseIdx]);
                                 const y = parseFloat(lParts[baseIdx + 1]);
                                 const r = parseFloat(lParts[baseIdx + 2]);
                                 if (!isNaN(x) && !isNaN(y) && !isNaN(r)) {
-                                    circles.push({ x, y, r });
+                                    circle = { x, y, r };
+                                    circles.push(circle);
                                 }
                             }
+                            refs.push(circle);
                         }
+                        circleRefs.push(refs);
                         if (circles.length > 0) {
-                            circleGroups.push({ lineColor, fillColor, circles });
+                            layer.circleGroups.push({ lineColor, fillColor, ...parseStrokeSuffix(lParts, 3 + circleCount * 3), circles });
                         }
                     }
                     lineIdx++;
@@ -577,12 +1152,247 @@ function parse2DPlaneCommand(
                             }
                         }
                         if (points.length > 0) {
-                            lineGroups.push({ color, width: isNaN(width) ? 2 : width, points });
+                            // An optional line style follows the segments
+                            const style = lParts[3 + lineCount * 4] as LineStyle;
+                            layer.lineGroups.push({ color, width: isNaN(width) ? 2 : width, style: LINE_STYLES.includes(style) ? style : 'SOLID', points });
                         }
                     }
                     lineIdx++;
                 }
             }
+        } else if (header === 'POLYLINES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color width style count x0 y0 x1 y1 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 4) {
+                        const color = lParts[0];
+                        const width = parseFloat(lParts[1]);
+                        const style = lParts[2] as LineStyle;
+                        const numPoints = parseInt(lParts[3]);
+                        if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: POLYLINES unknown line style '${lParts[2]}'`);
+                        } else {
+                            const points = [];
+                            for (let p = 0; p < numPoints; p++) {
+                                if (4 + p * 2 + 1 < lParts.length) {
+                                    const x = parseFloat(lParts[4 + p * 2]);
+                                    const y = parseFloat(lParts[4 + p * 2 + 1]);
+                                    if (!isNaN(x) && !isNaN(y)) {
+                                        points.push({ x, y });
+                                    }
+                                }
+                            }
+                            if (points.length > 0) {
+                                layer.polylines.push({ color, width: isNaN(width) ? 2 : width, style, points });
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'CURVES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: Q|C color width style count x0 y0 ... (3 points per Q curve, 4 per C curve)
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 5) {
+                        const kind = lParts[0];
+                        const color = lParts[1];
+                        const width = parseFloat(lParts[2]);
+                        const style = lParts[3] as LineStyle;
+                        const curveCount = parseInt(lParts[4]);
+                        if (kind !== 'Q' && kind !== 'C') {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: CURVES unknown curve type '${kind}'`);
+                        } else if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: CURVES unknown line style '${lParts[3]}'`);
+                        } else {
+                            const perCurve = kind === 'C' ? 4 : 3;
+                            const curves: Point[][] = [];
+                            for (let j = 0; j < curveCount; j++) {
+                                const curve: Point[] = [];
+                                for (let p = 0; p < perCurve; p++) {
+                                    const baseIdx = 5 + (j * perCurve + p) * 2;
+                                    const x = parseFloat(lParts[baseIdx]);
+                                    const y = parseFloat(lParts[baseIdx + 1]);
+                                    if (!isNaN(x) && !isNaN(y)) {
+                                        curve.push({ x, y });
+                                    }
+                                }
+                                if (curve.length === perCurve) {
+                                    curves.push(curve);
+                                }
+                            }
+                            if (curves.length > 0) {
+                                const group: CurveGroup = { cubic: kind === 'C', color, width: isNaN(width) ? 2 : width, style, curves };
+                                layer.curveGroups.push(group);
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'ELLIPSES') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: lineColor fillColor width style count cx0 cy0 rx0 ry0 angle0 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 5) {
+                        const lineColor = lParts[0];
+                        const fillColor = lParts[1];
+                        const lineWidth = parseFloat(lParts[2]);
+                        const style = lParts[3] as LineStyle;
+                        const ellipseCount = parseInt(lParts[4]);
+                        if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: ELLIPSES unknown line style '${lParts[3]}'`);
+                        } else {
+                            const ellipses = [];
+                            for (let j = 0; j < ellipseCount; j++) {
+                                const baseIdx = 5 + j * 5;
+                                if (baseIdx + 4 < lParts.length) {
+                                    const [cx, cy, rx, ry, angle] = lParts.slice(baseIdx, baseIdx + 5).map(parseFloat);
+                                    if (![cx, cy, rx, ry, angle].some(isNaN)) {
+                                        ellipses.push({ cx, cy, rx, ry, angle });
+                                    }
+                                }
+                            }
+                            if (ellipses.length > 0) {
+                                const group: EllipseGroup = { lineColor, fillColor, lineWidth: isNaN(lineWidth) ? 2 : lineWidth, style, ellipses };
+                                layer.ellipseGroups.push(group);
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'ARCS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: ARC color width style count cx0 cy0 r0 start0 end0 ...
+                    //     or SECTOR lineColor fillColor width style count cx0 cy0 r0 start0 end0 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    const kind = lParts[0];
+                    const base = kind === 'SECTOR' ? 2 : 1;
+                    if (kind !== 'ARC' && kind !== 'SECTOR') {
+                        pendingErrors[mode].push(`Line ${lineIdx + 1}: ARCS unknown arc type '${kind}'`);
+                    } else if (lParts.length >= base + 4) {
+                        const lineColor = lParts[1];
+                        const fillColor = kind === 'SECTOR' ? lParts[2] : undefined;
+                        const lineWidth = parseFloat(lParts[base + 1]);
+                        const style = lParts[base + 2] as LineStyle;
+                        const arcCount = parseInt(lParts[base + 3]);
+                        if (!LINE_STYLES.includes(style)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: ARCS unknown line style '${lParts[base + 2]}'`);
+                        } else {
+                            const arcs = [];
+                            for (let j = 0; j < arcCount; j++) {
+                                const baseIdx = base + 4 + j * 5;
+                                if (baseIdx + 4 < lParts.length) {
+                                    const [cx, cy, r, start, end] = lParts.slice(baseIdx, baseIdx + 5).map(parseFloat);
+                                    if (![cx, cy, r, start, end].some(isNaN)) {
+                                        arcs.push({ cx, cy, r, start, end });
+                                    }
+                                }
+                            }
+                            if (arcs.length > 0) {
+                                const group: ArcGroup = { lineColor, fillColor, lineWidth: isNaN(lineWidth) ? 2 : lineWidth, style, arcs };
+                                layer.arcGroups.push(group);
+                            }
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'ARROWS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: color width headSize count ax0 ay0 bx0 by0 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 4) {
+                        const color = lParts[0];
+                        const width = parseFloat(lParts[1]);
+                        const headSize = parseFloat(lParts[2]);
+                        const arrowCount = parseInt(lParts[3]);
+                        const arrows: PlaneArrow[] = [];
+                        for (let j = 0; j < arrowCount; j++) {
+                            const baseIdx = 4 + j * 4;
+                            if (baseIdx + 3 < lParts.length) {
+                                const ax = parseFloat(lParts[baseIdx]);
+                                const ay = parseFloat(lParts[baseIdx + 1]);
+                                const bx = parseFloat(lParts[baseIdx + 2]);
+                                const by = parseFloat(lParts[baseIdx + 3]);
+                                if (!isNaN(ax) && !isNaN(ay) && !isNaN(bx) && !isNaN(by)) {
+                                    arrows.push({ from: { x: ax, y: ay }, to: { x: bx, y: by } });
+                                }
+                            }
+                        }
+                        if (arrows.length > 0) {
+                            layer.arrowGroups.push({
+                                color,
+                                width: isNaN(width) ? 2 : width,
+                                headSize: isNaN(headSize) ? 8 : headSize,
+                                arrows
+                            });
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'TOOLTIPS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: "C group index text" or "P index text"
+                    const line = lines[lineIdx].replace(/\r$/, '');
+                    const circleMatch = line.match(/^\s*C\s+(\d+)\s+(\d+) ?(.*)$/);
+                    const polygonMatch = line.match(/^\s*P\s+(\d+) ?(.*)$/);
+                    if (circleMatch) {
+                        const circle = circleRefs[parseInt(circleMatch[1])]?.[parseInt(circleMatch[2])];
+                        if (circle) circle.tooltip = unescapeTooltip(circleMatch[3]);
+                    } else if (polygonMatch) {
+                        const polygonGroup = polygonRefs[parseInt(polygonMatch[1])];
+                        if (polygonGroup) polygonGroup.tooltip = unescapeTooltip(polygonMatch[2]);
+                    } else {
+                        pendingErrors[mode].push(`Line ${lineIdx + 1}: TOOLTIPS expects 'C group index text' or 'P index text'`);
+                    }
+                    lineIdx++;
+                }
+            }
         } else if (header === 'POLYGONS') {
             lineIdx++;
             if (lineIdx < lines.length) {
@@ -609,8 +1419,89 @@ function parse2DPlaneCommand(
                                 }
                             }
                         }
+                        const polygonGroup: PolygonGroup = { lineColor, fillColor, ...parseStrokeSuffix(lParts, 3 + vertexCount * 2), polygon: { points } };
+                        polygonRefs.push(polygonGroup);
                         if (points.length > 0) {
-                            polygonGroups.push({ lineColor, fillColor, polygon: { points } });
+                            layer.polygonGroups.push(polygonGroup);
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'RECTS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const rn = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < rn; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: lineColor fillColor count [L|N] x0 y0 w0 h0 [label0] ...
+                    // The L/N flag may be omitted; then every rect has a label
+                    const tokens = tokenizeQuoted(lines[lineIdx].trim());
+                    if (tokens.length >= 3) {
+                        const lineColor = tokens[0];
+                        const fillColor = tokens[1];
+                        const rectCount = parseInt(tokens[2]);
+                        const hasFlag = tokens[3] === 'L' || tokens[3] === 'N';
+                        const labeled = !hasFlag || tokens[3] === 'L';
+                        const start = hasFlag ? 4 : 3;
+                        const stride = labeled ? 5 : 4;
+                        const rects: PlaneRect[] = [];
+                        for (let j = 0; j < rectCount; j++) {
+                            const baseIdx = start + j * stride;
+                            if (baseIdx + stride - 1 < tokens.length) {
+                                const x = parseFloat(tokens[baseIdx]);
+                                const y = parseFloat(tokens[baseIdx + 1]);
+                                const w = parseFloat(tokens[baseIdx + 2]);
+                                const h = parseFloat(tokens[baseIdx + 3]);
+                                if (!isNaN(x) && !isNaN(y) && !isNaN(w) && !isNaN(h)) {
+                                    rects.push({ x, y, w, h, label: labeled ? tokens[baseIdx + 4] : '' });
+                                }
+                            }
+                        }
+                        if (rects.length > 0) {
+                            layer.rectGroups.push({ lineColor, fillColor, rects });
+                        }
+                    }
+                    lineIdx++;
+                }
+            }
+        } else if (header === 'POINTS') {
+            lineIdx++;
+            if (lineIdx < lines.length) {
+                pendingRawText[mode] += lines[lineIdx] + "\n";
+                const n = parseInt(lines[lineIdx].trim());
+                lineIdx++;
+                for (let k = 0; k < n; k++) {
+                    if (lineIdx >= lines.length) break;
+                    pendingRawText[mode] += lines[lineIdx] + "\n";
+                    // Parse: shape color size count x0 y0 x1 y1 ...
+                    const lParts = lines[lineIdx].trim().split(/\s+/);
+                    if (lParts.length >= 4) {
+                        const shape = lParts[0] as MarkerShape;
+                        const color = lParts[1];
+                        const size = parseFloat(lParts[2]);
+                        const pointCount = parseInt(lParts[3]);
+                        if (!MARKER_SHAPES.includes(shape)) {
+                            pendingErrors[mode].push(`Line ${lineIdx + 1}: POINTS unknown shape '${lParts[0]}'`);
+                        } else {
+                            const points = [];
+                            for (let j = 0; j < pointCount; j++) {
+                                const baseIdx = 4 + j * 2;
+                                if (baseIdx + 1 < lParts.length) {
+                                    const x = parseFloat(lParts[baseIdx]);
+                                    const y = parseFloat(lParts[baseIdx + 1]);
+                                    if (!isNaN(x) && !isNaN(y)) {
+                                        points.push({ x, y });
+                                    }
+                                }
+                            }
+                            if (points.length > 0) {
+                                const group: PlanePointGroup = { shape, color, size: isNaN(size) ? 2 : size, points };
+                                layer.pointGroups.push(group);
+                            }
                         }
                     }
                     lineIdx++;
@@ -651,7 +1542,7 @@ function parse2DPlaneCommand(
                             }
                         }
                         if (texts.length > 0) {
-                            textGroups.push({ color, fontSize: isNaN(fontSize) ? 12 : fontSize, texts });
+                            layer.textGroups.push({ color, fontSize: isNaN(fontSize) ? 12 : fontSize, ...parseTextStyleSuffix(tokens, 3 + textCount * 3), texts });
                         }
                     }
                     lineIdx++;
@@ -663,7 +1554,9 @@ function parse2DPlaneCommand(
     const twoDPlaneCommand: TwoDPlaneCommand = {
         type: '2D_PLANE',
         H, W,
-        circleGroups, lineGroups, polygonGroups, textGroups,
+        view,
+        // Stable sort: layers with the same z keep their order of appearance
+        layers: layers.map((l, i) => ({ l, z: layerZ[i] })).sort((a, b) => a.z - b.z).map(({ l }) => l),
         bounds
     };
 
@@ -757,3 +1650,249 @@ function parseBarGraphCommand(
     pendingCommands[mode].push(barGraphCommand);
     return { lineIdx };
 }
+
+function parseLineChartCommand(
+    lines: string[],
+    lineIdx: number,
+    remaining: string,
+    mode: string,
+    pendingRawText: PendingRawText,
+    pendingCommands: PendingCommands,
+    pendingErrors: PendingErrors
+): { lineIdx: number } {
+    const parts = tokenizeQuoted(remaining.replace(/^LINE_CHART\s*/, '').trim());
+
+    if (parts.length < 7) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART requires 7 parameters (title x_min x_max y_min y_max LINEAR|LOG marker), got ${parts.length}`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    // Axis bounds are numbers or AUTO
+    const bounds: (number | undefined)[] = [];
+    for (const token of parts.slice(1, 5)) {
+        const value = parseFloat(token);
+        if (token !== 'AUTO' && isNaN(value)) {
+            pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART axis bound must be a number or AUTO, got '${token}'`);
+            return { lineIdx: lineIdx + 1 };
+        }
+        bounds.push(token === 'AUTO' ? undefined : value);
+    }
+    const [xMin, xMax, yMin, yMax] = bounds;
+    if ((xMin !== undefined && xMax !== undefined && xMin >= xMax) || (yMin !== undefined && yMax !== undefined && yMin >= yMax)) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART min must be less than max`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const scale = parts[5];
+    if (scale !== 'LINEAR' && scale !== 'LOG') {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART scale must be LINEAR or LOG, got '${scale}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (scale === 'LOG' && ((yMin !== undefined && yMin <= 0) || (yMax !== undefined && yMax <= 0))) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART y bounds must be positive with LOG scale`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const marker = parts[6] === '-' ? undefined : parseFloat(parts[6]);
+    if (marker !== undefined && isNaN(marker)) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART marker must be a number or -, got '${parts[6]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    lineIdx++;
+
+    if (lineIdx >= lines.length) {
+        pendingErrors[mode].push(`Line ${lineIdx}: LINE_CHART expected series count but reached end of input`);
+        return { lineIdx };
+    }
+
+    pendingRawText[mode] += lines[lineIdx] + "\n";
+    const n = parseInt(lines[lineIdx].trim());
+    if (isNaN(n) || n < 0) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART series count must be a non-negative integer`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    lineIdx++;
+
+    // Each series: name color count x1 y1 x2 y2 ...
+    const series: LineChartSeries[] = [];
+    for (let k = 0; k < n; k++) {
+        if (lineIdx >= lines.length) {
+            pendingErrors[mode].push(`Line ${lineIdx}: LINE_CHART expected ${n} series but reached end of input`);
+            break;
+        }
+        pendingRawText[mode] += lines[lineIdx] + "\n";
+        const tokens = tokenizeQuoted(lines[lineIdx].trim());
+        if (tokens.length < 3) {
+            pendingErrors[mode].push(`Line ${lineIdx + 1}: LINE_CHART series requires name, color and point count`);
+        } else {
+            const count = parseInt(tokens[2]);
+            const points = [];
+            for (let j = 0; j < count; j++) {
+                const x = parseFloat(tokens[3 + j * 2]);
+                const y = parseFloat(tokens[4 + j * 2]);
+                if (!isNaN(x) && !isNaN(y)) {
+                    points.push({ x, y });
+                }
+            }
+            series.push({ name: tokens[0], color: tokens[1], points });
+        }
+        lineIdx++;
+    }
+
+    const lineChartCommand: LineChartCommand = {
+        type: 'LINE_CHART',
+        title: parts[0],
+        xMin,
+        xMax,
+        yMin,
+        yMax,
+        logScale: scale === 'LOG',
+        marker,
+        series
+    };
+
+    pendingCommands[mode].push(lineChartCommand);
+    return { lineIdx };
+}
+
+function parseHistogramCommand(
+    lines: string[],
+    lineIdx: number,
+    remaining: string,
+    mode: string,
+    pendingRawText: PendingRawText,
+    pendingCommands: PendingCommands,
+    pendingErrors: PendingErrors
+): { lineIdx: number } {
+    const parts = tokenizeQuoted(remaining.replace(/^HISTOGRAM\s*/, '').trim());
+
+    if (parts.length < 2) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: HISTOGRAM requires 2 parameters (title fill_color), got ${parts.length}`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    lineIdx++;
+
+    // Bins: B e0 e1 ... eB c1 ... cB
+    if (lineIdx >= lines.length) {
+        pendingErrors[mode].push(`Line ${lineIdx}: HISTOGRAM expected bins but reached end of input`);
+        return { lineIdx };
+    }
+    pendingRawText[mode] += lines[lineIdx] + "\n";
+    const binTokens = lines[lineIdx].trim().split(/\s+/).map(Number);
+    const b = binTokens[0];
+    const expected = b === 0 ? 1 : 2 * b + 2;
+    if (!Number.isInteger(b) || b < 0 || binTokens.length < expected || binTokens.some(isNaN)) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: HISTOGRAM bins must be 'B e0 ... eB c1 ... cB'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    const edges = b === 0 ? [] : binTokens.slice(1, b + 2);
+    const counts = binTokens.slice(b + 2, 2 * b + 2);
+    lineIdx++;
+
+    // Stats: count mean std min p25 median p75 p95 max (only count when empty)
+    if (lineIdx >= lines.length) {
+        pendingErrors[mode].push(`Line ${lineIdx}: HISTOGRAM expected summary statistics but reached end of input`);
+        return { lineIdx };
+    }
+    pendingRawText[mode] += lines[lineIdx] + "\n";
+    const statTokens = lines[lineIdx].trim().split(/\s+/).map(Number);
+    if (statTokens.some(isNaN) || (statTokens[0] > 0 && statTokens.length < 9)) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: HISTOGRAM summary must be 'count mean std min p25 median p75 p95 max'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    const [count, mean, std, min, p25, median, p75, p95, max] = statTokens;
+    lineIdx++;
+
+    const histogramCommand: HistogramCommand = {
+        type: 'HISTOGRAM',
+        title: parts[0],
+        fillColor: parts[1],
+        edges,
+        counts,
+        stats: count > 0 ? { count, mean, std, min, p25, median, p75, p95, max } : undefined
+    };
+
+    pendingCommands[mode].push(histogramCommand);
+    return { lineIdx };
+}
+
+function parseTableCommand(
+    lines: string[],
+    lineIdx: number,
+    remaining: string,
+    mode: string,
+    pendingRawText: PendingRawText,
+    pendingCommands: PendingCommands,
+    pendingErrors: PendingErrors
+): { lineIdx: number } {
+    const parts = tokenizeQuoted(remaining.replace(/^TABLE\s*/, '').trim());
+
+    if (parts.length < 4) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE requires 4 parameters (title rows columns HEADER|NOHEADER), got ${parts.length}`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    const r = parseInt(parts[1]);
+    const c = parseInt(parts[2]);
+    if (isNaN(r) || r < 0 || isNaN(c) || c <= 0) {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE rows must be non-negative and columns must be positive`);
+        return { lineIdx: lineIdx + 1 };
+    }
+    if (parts[3] !== 'HEADER' && parts[3] !== 'NOHEADER') {
+        pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE expected HEADER or NOHEADER, got '${parts[3]}'`);
+        return { lineIdx: lineIdx + 1 };
+    }
+
+    lineIdx++;
+
+    let header: string[] | undefined;
+    if (parts[3] === 'HEADER') {
+        if (lineIdx >= lines.length) {
+            pendingErrors[mode].push(`Line ${lineIdx}: TABLE expected header but reached end of input`);
+            return { lineIdx };
+        }
+        pendingRawText[mode] += lines[lineIdx] + "\n";
+        header = tokenizeQuoted(lines[lineIdx].trim());
+        if (header.length !== c) {
+            pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE header expected ${c} columns, got ${header.length}`);
+        }
+        lineIdx++;
+    }
+
+    // Each cell: kind (I|F|T) color|- value
+    const rows: TableCell[][] = [];
+    for (let i = 0; i < r; i++) {
+        if (lineIdx >= lines.length) {
+            pendingErrors[mode].push(`Line ${lineIdx}: TABLE expected ${r} rows but reached end of input`);
+            break;
+        }
+        pendingRawText[mode] += lines[lineIdx] + "\n";
+        const tokens = tokenizeQuoted(lines[lineIdx].trim());
+        const row: TableCell[] = [];
+        for (let j = 0; j + 2 < tokens.length && row.length < c; j += 3) {
+            const kind = tokens[j];
+            if (kind !== 'I' && kind !== 'F' && kind !== 'T') {
+                pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE cell kind must be I, F or T, got '${kind}'`);
+                break;
+            }
+            row.push({ kind, color: tokens[j + 1] === '-' ? undefined : tokens[j + 1], value: tokens[j + 2] });
+        }
+        if (row.length !== c) {
+            pendingErrors[mode].push(`Line ${lineIdx + 1}: TABLE row expected ${c} cells, got ${row.length}`);
+        }
+        rows.push(row);
+        lineIdx++;
+    }
+
+    const tableCommand: TableCommand = {
+        type: 'TABLE',
+        title: parts[0],
+        header,
+        rows
+    };
+
+    pendingCommands[mode].push(tableCommand);
+    return { lineIdx };
+}
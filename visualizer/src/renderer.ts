@@ -1,4 +1,4 @@
-import { GridCommand, GridLine, TwoDPlaneCommand, CircleGroup, LineGroup, PolygonGroup, TextGroup, BarGraphCommand } from './types';
+import { Point, GridCommand, HexGridCommand, TriGridCommand, GridLine, GridTextGroup, TextAnchor, GridMarkerGroup, MarkerShape, GridPolyline, GridArrowGroup, GridWallStyle, GridAxisLabels, LineStyle, TwoDPlaneCommand, PlaneLayer, PlaneView, BarGraphCommand, LineChartCommand, HistogramCommand, TableCommand } from './types';
 
 const SVG_NS = "http://www.w3.org/2000/svg";
 
@@ -15,6 +15,154 @@ export function createCanvasSvg(container: HTMLElement, canvasW: number, canvasH
     return svg;
 }
 
+function cellText(gridTexts: string[][], y: number, x: number): string {
+    if (gridTexts && y < gridTexts.length && x < gridTexts[y].length) return gridTexts[y][x];
+    return "";
+}
+
+// Compute the text position and SVG alignment for an anchor inside the box (x, y, w, h)
+function anchorPosition(
+    anchor: TextAnchor,
+    x: number,
+    y: number,
+    w: number,
+    h: number,
+    pad: number
+): { x: number; y: number; textAnchor: string; baseline: string } {
+    let px = x + w / 2;
+    let textAnchor = "middle";
+    if (anchor === 'L' || anchor === 'TL' || anchor === 'BL') {
+        px = x + pad;
+        textAnchor = "start";
+    } else if (anchor === 'R' || anchor === 'TR' || anchor === 'BR') {
+        px = x + w - pad;
+        textAnchor = "end";
+    }
+    let py = y + h / 2;
+    let baseline = "middle";
+    if (anchor === 'T' || anchor === 'TL' || anchor === 'TR') {
+        py = y + pad;
+        baseline = "hanging";
+    } else if (anchor === 'B' || anchor === 'BL' || anchor === 'BR') {
+        py = y + h - pad;
+        baseline = "text-after-edge";
+    }
+    return { x: px, y: py, textAnchor, baseline };
+}
+
+export function applyLineStyle(element: SVGElement, style: LineStyle, width: number): void {
+    if (style === 'DASHED') {
+        element.setAttribute("stroke-dasharray", `${width * 4} ${width * 3}`);
+    } else if (style === 'DOTTED') {
+        element.setAttribute("stroke-dasharray", `0 ${width * 2}`);
+        element.setAttribute("stroke-linecap", "round");
+    }
+}
+
+// Create an arrow from (x1, y1) to (x2, y2). The head is a filled triangle whose tip is at (x2, y2)
+export function createArrow(
+    x1: number,
+    y1: number,
+    x2: number,
+    y2: number,
+    color: string,
+    width: number,
+    headSize: number,
+    style: LineStyle
+): SVGGElement {
+    const group = document.createElementNS(SVG_NS, "g");
+    const len = Math.hypot(x2 - x1, y2 - y1);
+    if (len < 1e-9) return group;
+    const ux = (x2 - x1) / len;
+    const uy = (y2 - y1) / len;
+    const head = Math.min(headSize, len);
+    // The shaft ends at the base of the head so that a thick line does not stick out of the tip
+    const baseX = x2 - ux * head;
+    const baseY = y2 - uy * head;
+
+    const line = document.createElementNS(SVG_NS, "line");
+    line.setAttribute("x1", String(x1));
+    line.setAttribute("y1", String(y1));
+    line.setAttribute("x2", String(baseX));
+    line.setAttribute("y2", String(baseY));
+    line.setAttribute("stroke", color);
+    line.setAttribute("stroke-width", String(width));
+    line.setAttribute("stroke-linecap", "butt");
+    applyLineStyle(line, style, width);
+    group.appendChild(line);
+
+    const halfWidth = head * 0.5;
+    const headElement = document.createElementNS(SVG_NS, "polygon");
+    headElement.setAttribute("points", [
+        `${x2},${y2}`,
+        `${baseX - uy * halfWidth},${baseY + ux * halfWidth}`,
+        `${baseX + uy * halfWidth},${baseY - ux * halfWidth}`,
+    ].join(' '));
+    headElement.setAttribute("fill", color);
+    group.appendChild(headElement);
+    return group;
+}
+
+// Create a marker shape centered at (cx, cy) that fits in a circle of the given radius
+export function createMarker(
+    shape: MarkerShape,
+    cx: number,
+    cy: number,
+    radius: number,
+    strokeColor: string,
+    fillColor: string,
+    strokeWidth: number
+): SVGElement {
+    let element: SVGElement;
+    if (shape === 'CIRCLE') {
+        element = document.createElementNS(SVG_NS, "circle");
+        element.setAttribute("cx", String(cx));
+        element.setAttribute("cy", String(cy));
+        element.setAttribute("r", String(radius));
+    } else if (shape === 'SQUARE') {
+        const half = radius / Math.SQRT2;
+        element = document.createElementNS(SVG_NS, "rect");
+        element.setAttribute("x", String(cx - half));
+        element.setAttribute("y", String(cy - half));
+        element.setAttribute("width", String(half * 2));
+        element.setAttribute("height", String(half * 2));
+    } else if (shape === 'CROSS') {
+        // A cross has no area, so it is drawn with the stroke color only
+        const half = radius / Math.SQRT2;
+        element = document.createElementNS(SVG_NS, "path");
+        element.setAttribute("d", `M ${cx - half} ${cy - half} L ${cx + half} ${cy + half} M ${cx - half} ${cy + half} L ${cx + half} ${cy - half}`);
+        element.setAttribute("stroke", strokeColor);
+        element.setAttribute("stroke-width", String(strokeWidth * 2));
+        element.setAttribute("stroke-linecap", "round");
+        element.setAttribute("fill", "none");
+        return element;
+    } else {
+        let points: [number, number][];
+        if (shape === 'DIAMOND') {
+            points = [[cx, cy - radius], [cx + radius, cy], [cx, cy + radius], [cx - radius, cy]];
+        } else if (shape === 'TRIANGLE') {
+            points = [0, 1, 2].map(i => {
+                const angle = -Math.PI / 2 + i * 2 * Math.PI / 3;
+                return [cx + radius * Math.cos(angle), cy + radius * Math.sin(angle)] as [number, number];
+            });
+        } else {
+            // STAR
+            points = [];
+            for (let i = 0; i < 10; i++) {
+                const angle = -Math.PI / 2 + i * Math.PI / 5;
+                const r = i % 2 === 0 ? radius : radius * 0.45;
+                points.push([cx + r * Math.cos(angle), cy + r * Math.sin(angle)]);
+            }
+        }
+        element = document.createElementNS(SVG_NS, "polygon");
+        element.setAttribute("points", points.map(p => `${p[0]},${p[1]}`).join(' '));
+    }
+    element.setAttribute("fill", fillColor);
+    element.setAttribute("stroke", strokeColor);
+    element.setAttribute("stroke-width", String(strokeWidth));
+    return element;
+}
+
 export function renderGrid(
     svg: SVGSVGElement,
     H: number,
@@ -23,14 +171,32 @@ export function renderGrid(
     textColor: string,
     gridColors: string[][],
     gridTexts: string[][],
+    gridTextGroups: GridTextGroup[],
+    gridMarkerGroups: GridMarkerGroup[],
     gridLines: GridLine[],
+    gridPolylines: GridPolyline[],
+    gridArrowGroups: GridArrowGroup[],
     wallVertical: string[] = [],
     wallHorizontal: string[] = [],
+    wallStyles: { [key: string]: GridWallStyle } = {},
+    rowOffset: number = 0,
+    colOffset: number = 0,
+    axisLabels: GridAxisLabels = { rows: [], cols: [] },
+    tooltips: { [key: string]: string } = {},
     left: number = 0,
     top: number = 0,
     right: number = 800,
     bottom: number = 800
 ): void {
+    // Reserve margins for axis labels (row labels on the left, column labels on the top)
+    const axisFontSize = 11;
+    const tickLength = 3;
+    const maxRowLabelLength = Math.max(0, ...axisLabels.rows.map(l => l.text.length));
+    const axisLeft = axisLabels.rows.length > 0 ? maxRowLabelLength * axisFontSize * 0.6 + tickLength + 4 : 0;
+    const axisTop = axisLabels.cols.length > 0 ? axisFontSize + tickLength + 4 : 0;
+    left += axisLeft;
+    top += axisTop;
+
     // Calculate the actual drawing area based on bounds
     const drawWidth = right - left;
     const drawHeight = bottom - top;
@@ -41,6 +207,40 @@ export function renderGrid(
     const g = document.createElementNS(SVG_NS, "g");
     g.setAttribute("transform", `translate(${left}, ${top})`);
 
+    // Render axis labels and tick marks in the margins
+    const axisLabel = (text: string, x: number, y: number, anchor: string, baseline: string): void => {
+        const textElement = document.createElementNS(SVG_NS, "text");
+        textElement.setAttribute("x", String(x));
+        textElement.setAttribute("y", String(y));
+        textElement.setAttribute("fill", textColor);
+        textElement.setAttribute("font-size", String(axisFontSize));
+        textElement.setAttribute("font-family", "sans-serif");
+        textElement.setAttribute("text-anchor", anchor);
+        textElement.setAttribute("dominant-baseline", baseline);
+        textElement.textContent = text;
+        g.appendChild(textElement);
+    };
+    const axisTick = (x1: number, y1: number, x2: number, y2: number): void => {
+        const tick = document.createElementNS(SVG_NS, "line");
+        tick.setAttribute("x1", String(x1));
+        tick.setAttribute("y1", String(y1));
+        tick.setAttribute("x2", String(x2));
+        tick.setAttribute("y2", String(y2));
+        tick.setAttribute("stroke", borderColor);
+        tick.setAttribute("stroke-width", "1");
+        g.appendChild(tick);
+    };
+    for (const label of axisLabels.rows) {
+        const cy = (label.index + 0.5) * cellHeight;
+        axisTick(-tickLength, cy, 0, cy);
+        axisLabel(label.text, -tickLength - 2, cy, "end", "middle");
+    }
+    for (const label of axisLabels.cols) {
+        const cx = (label.index + 0.5) * cellWidth;
+        axisTick(cx, -tickLength, cx, 0);
+        axisLabel(label.text, cx, -tickLength - 2, "middle", "text-after-edge");
+    }
+
     // Find maximum character count across all cells
     let maxCharCount = 1;
     if (gridTexts) {
@@ -66,6 +266,19 @@ export function renderGrid(
     }
     unifiedFontSize = Math.min(unifiedFontSize, 30);
 
+    // Collect styled texts per cell for tooltips
+    const styledTextsByCell = new Map<string, string[]>();
+    for (const group of gridTextGroups) {
+        for (const item of group.texts) {
+            const key = `${item.y},${item.x}`;
+            if (!styledTextsByCell.has(key)) styledTextsByCell.set(key, []);
+            styledTextsByCell.get(key)!.push(item.text);
+        }
+    }
+
+    // Texts are collected into a separate layer so that markers are drawn below them
+    const textLayer = document.createElementNS(SVG_NS, "g");
+
     // Render cells
     for (let y = 0; y < H; y++) {
         for (let x = 0; x < W; x++) {
@@ -85,7 +298,7 @@ export function renderGrid(
 
             // Add tooltip
             const title = document.createElementNS(SVG_NS, "title");
-            let titleText = `(${y}, ${x})`;
+            let titleText = `(${y + rowOffset}, ${x + colOffset})`;
 
             let textContent = "";
             if (gridTexts && y < gridTexts.length && x < gridTexts[y].length) {
@@ -98,6 +311,14 @@ export function renderGrid(
                     textContent = rawText;
                 }
             }
+            const styledTexts = styledTextsByCell.get(`${y},${x}`);
+            if (styledTexts) {
+                titleText += (cellText(gridTexts, y, x) ? ' / ' : ': ') + styledTexts.join(' / ');
+            }
+            const tooltip = tooltips[`${y},${x}`];
+            if (tooltip) {
+                titleText += `\n${tooltip}`;
+            }
             title.textContent = titleText;
             rect.appendChild(title);
 
@@ -115,13 +336,52 @@ export function renderGrid(
                 textElement.setAttribute("dominant-baseline", "middle");
                 textElement.setAttribute("pointer-events", "none");
                 textElement.textContent = textContent;
-                g.appendChild(textElement);
+                textLayer.appendChild(textElement);
             }
         }
     }
 
+    // Render cell markers
+    for (const group of gridMarkerGroups) {
+        const radius = Math.min(cellWidth, cellHeight) * group.size / 2;
+        for (const pt of group.points) {
+            const cx = pt.x * cellWidth + cellWidth / 2;
+            const cy = pt.y * cellHeight + cellHeight / 2;
+            const marker = createMarker(group.shape, cx, cy, radius, group.strokeColor, group.fillColor, Math.max(1, radius * 0.15));
+            marker.setAttribute("pointer-events", "none");
+            g.appendChild(marker);
+        }
+    }
+
+    // Render styled cell texts
+    for (const group of gridTextGroups) {
+        const fontSize = group.fontSize > 0
+            ? group.fontSize
+            : (group.anchor === 'C' ? unifiedFontSize : Math.min(cellHeight * 0.3, 14));
+        for (const item of group.texts) {
+            const pos = anchorPosition(group.anchor, item.x * cellWidth, item.y * cellHeight, cellWidth, cellHeight, Math.min(cellWidth, cellHeight) * 0.08);
+            const textElement = document.createElementNS(SVG_NS, "text");
+            textElement.setAttribute("x", String(pos.x));
+            textElement.setAttribute("y", String(pos.y));
+            textElement.setAttribute("fill", group.color);
+            textElement.setAttribute("font-size", String(fontSize));
+            textElement.setAttribute("font-family", "sans-serif");
+            textElement.setAttribute("text-anchor", pos.textAnchor);
+            textElement.setAttribute("dominant-baseline", pos.baseline);
+            textElement.setAttribute("pointer-events", "none");
+            textElement.textContent = item.text;
+            textLayer.appendChild(textElement);
+        }
+    }
+    g.appendChild(textLayer);
+
     // Render walls
     const wallWidth = 2;
+    const applyWallStyle = (line: SVGLineElement, key: string): void => {
+        const style = wallStyles[key];
+        line.setAttribute("stroke", style ? style.color : borderColor);
+        line.setAttribute("stroke-width", String(style ? style.width : wallWidth));
+    };
 
     // Render vertical walls
     // wallVertical[i][j] = 'Y' means vertical wall at row i, column j exists
@@ -136,8 +396,7 @@ export function renderGrid(
                         line.setAttribute("y1", String(i * cellHeight));
                         line.setAttribute("x2", String(j * cellWidth));
                         line.setAttribute("y2", String((i + 1) * cellHeight));
-                        line.setAttribute("stroke", borderColor);
-                        line.setAttribute("stroke-width", String(wallWidth));
+                        applyWallStyle(line, `V ${i} ${j}`);
                         line.setAttribute("pointer-events", "none");
                         g.appendChild(line);
                     }
@@ -153,8 +412,7 @@ export function renderGrid(
                 line.setAttribute("y1", String(i * cellHeight));
                 line.setAttribute("x2", String(j * cellWidth));
                 line.setAttribute("y2", String((i + 1) * cellHeight));
-                line.setAttribute("stroke", borderColor);
-                line.setAttribute("stroke-width", String(wallWidth));
+                applyWallStyle(line, `V ${i} ${j}`);
                 line.setAttribute("pointer-events", "none");
                 g.appendChild(line);
             }
@@ -174,8 +432,7 @@ export function renderGrid(
                         line.setAttribute("y1", String(i * cellHeight));
                         line.setAttribute("x2", String((j + 1) * cellWidth));
                         line.setAttribute("y2", String(i * cellHeight));
-                        line.setAttribute("stroke", borderColor);
-                        line.setAttribute("stroke-width", String(wallWidth));
+                        applyWallStyle(line, `H ${i} ${j}`);
                         line.setAttribute("pointer-events", "none");
                         g.appendChild(line);
                     }
@@ -191,8 +448,7 @@ export function renderGrid(
                 line.setAttribute("y1", String(i * cellHeight));
                 line.setAttribute("x2", String((j + 1) * cellWidth));
                 line.setAttribute("y2", String(i * cellHeight));
-                line.setAttribute("stroke", borderColor);
-                line.setAttribute("stroke-width", String(wallWidth));
+                applyWallStyle(line, `H ${i} ${j}`);
                 line.setAttribute("pointer-events", "none");
                 g.appendChild(line);
             }
@@ -240,6 +496,65 @@ export function renderGrid(
         }
     }
 
+    // Render polylines with width and style
+    for (const lineData of gridPolylines) {
+        const points = lineData.points;
+        if (points.length < 2) continue;
+
+        const pointsStr = points
+            .map(pt => `${pt.x * cellWidth + cellWidth / 2},${pt.y * cellHeight + cellHeight / 2}`)
+            .join(' ');
+        const polyline = document.createElementNS(SVG_NS, "polyline");
+        polyline.setAttribute("points", pointsStr);
+        polyline.setAttribute("fill", "none");
+        polyline.setAttribute("stroke", lineData.color);
+        polyline.setAttribute("stroke-width", String(lineData.width));
+        polyline.setAttribute("stroke-linejoin", "round");
+        polyline.setAttribute("stroke-linecap", "round");
+        polyline.setAttribute("pointer-events", "none");
+        applyLineStyle(polyline, lineData.style, lineData.width);
+        g.appendChild(polyline);
+    }
+
+    // Render arrows between cell centers
+    const arrowInset = Math.min(cellWidth, cellHeight) * 0.15;
+    for (const group of gridArrowGroups) {
+        const headSize = Math.max(group.width * 3, Math.min(cellWidth, cellHeight) * 0.25);
+        for (const arrow of group.arrows) {
+            const x1 = arrow.from.x * cellWidth + cellWidth / 2;
+            const y1 = arrow.from.y * cellHeight + cellHeight / 2;
+            const x2 = arrow.to.x * cellWidth + cellWidth / 2;
+            const y2 = arrow.to.y * cellHeight + cellHeight / 2;
+            const len = Math.hypot(x2 - x1, y2 - y1);
+            if (len < 1e-9) continue;
+            // Shorten both ends a little so that adjacent arrows do not overlap at cell centers
+            const inset = Math.min(arrowInset, len / 4);
+            const ux = (x2 - x1) / len;
+            const uy = (y2 - y1) / len;
+            const arrowElement = createArrow(
+                x1 + ux * inset, y1 + uy * inset, x2 - ux * inset, y2 - uy * inset,
+                group.color, group.width, headSize, group.style
+            );
+            arrowElement.setAttribute("pointer-events", "none");
+            g.appendChild(arrowElement);
+
+            if (arrow.label) {
+                const label = document.createElementNS(SVG_NS, "text");
+                // Place the label slightly to the left of the arrow direction
+                label.setAttribute("x", String((x1 + x2) / 2 + uy * headSize * 0.6));
+                label.setAttribute("y", String((y1 + y2) / 2 - ux * headSize * 0.6));
+                label.setAttribute("fill", group.color);
+                label.setAttribute("font-size", String(Math.max(8, Math.min(cellHeight * 0.3, 14))));
+                label.setAttribute("font-family", "sans-serif");
+                label.setAttribute("text-anchor", "middle");
+                label.setAttribute("dominant-baseline", "middle");
+                label.setAttribute("pointer-events", "none");
+                label.textContent = arrow.label;
+                g.appendChild(label);
+            }
+        }
+    }
+
     svg.appendChild(g);
 }
 
@@ -253,9 +568,18 @@ export function renderGridFromCommand(svg: SVGSVGElement, cmd: GridCommand, canv
         cmd.textColor,
         cmd.gridColors,
         cmd.gridTexts,
+        cmd.gridTextGroups,
+        cmd.gridMarkerGroups,
         cmd.gridLines,
+        cmd.gridPolylines,
+        cmd.gridArrowGroups,
         cmd.wallVertical,
         cmd.wallHorizontal,
+        cmd.wallStyles,
+        cmd.rowOffset,
+        cmd.colOffset,
+        cmd.axisLabels,
+        cmd.tooltips,
         bounds.left,
         bounds.top,
         bounds.right,
@@ -263,35 +587,67 @@ export function renderGridFromCommand(svg: SVGSVGElement, cmd: GridCommand, canv
     );
 }
 
-export function render2DPlane(
-    svg: SVGSVGElement,
-    H: number,
-    W: number,
-    circleGroups: CircleGroup[],
-    lineGroups: LineGroup[],
-    polygonGroups: PolygonGroup[],
-    textGroups: TextGroup[],
-    left: number = 0,
-    top: number = 0,
-    right: number = 800,
-    bottom: number = 800
-): void {
-    // Calculate the actual drawing area based on bounds
-    const drawWidth = right - left;
-    const drawHeight = bottom - top;
+// Maps plane coordinates to pixels inside the drawing area
+interface PlaneTransform {
+    x: (v: number) => number;
+    y: (v: number) => number;
+    scaleX: number; // pixels per unit
+    scaleY: number;
+}
 
-    // Create a group for this 2D plane item with offset
-    const g = document.createElementNS(SVG_NS, "g");
-    g.setAttribute("transform", `translate(${left}, ${top})`);
+function createPlaneTransform(view: PlaneView, drawWidth: number, drawHeight: number): PlaneTransform {
+    let scaleX = drawWidth / (view.maxX - view.minX);
+    let scaleY = drawHeight / (view.maxY - view.minY);
+    let offsetX = 0;
+    let offsetY = 0;
+    if (view.keepAspect) {
+        const scale = Math.min(scaleX, scaleY);
+        offsetX = (drawWidth - (view.maxX - view.minX) * scale) / 2;
+        offsetY = (drawHeight - (view.maxY - view.minY) * scale) / 2;
+        scaleX = scale;
+        scaleY = scale;
+    }
+    return {
+        x: (v: number) => offsetX + (v - view.minX) * scaleX,
+        y: view.yUp
+            ? (v: number) => offsetY + (view.maxY - v) * scaleY
+            : (v: number) => offsetY + (v - view.minY) * scaleY,
+        scaleX,
+        scaleY,
+    };
+}
 
-    // Add a white background
-    const background = document.createElementNS(SVG_NS, "rect");
-    background.setAttribute("x", "0");
-    background.setAttribute("y", "0");
-    background.setAttribute("width", String(drawWidth));
-    background.setAttribute("height", String(drawHeight));
-    background.setAttribute("fill", "white");
-    g.appendChild(background);
+// Sample an elliptical arc in plane coordinates and return its pixel points.
+// Sampling (instead of SVG arcs) keeps the shape correct when x and y are scaled differently
+function planeArcPoints(
+    t: PlaneTransform,
+    cx: number,
+    cy: number,
+    rx: number,
+    ry: number,
+    angle: number,
+    start: number,
+    end: number
+): string[] {
+    const rot = angle * Math.PI / 180;
+    const sweep = Math.max(-360, Math.min(360, end - start));
+    const steps = Math.max(4, Math.ceil(Math.abs(sweep) / 5));
+    const points: string[] = [];
+    for (let i = 0; i <= steps; i++) {
+        const theta = (start + sweep * i / steps) * Math.PI / 180;
+        const ex = rx * Math.cos(theta);
+        const ey = ry * Math.sin(theta);
+        const x = cx + ex * Math.cos(rot) - ey * Math.sin(rot);
+        const y = cy + ex * Math.sin(rot) + ey * Math.cos(rot);
+        points.push(`${t.x(x)},${t.y(y)}`);
+    }
+    return points;
+}
+
+// Render the shapes of one layer. Within a layer, polygons and rectangles are drawn first,
+// then lines, curves, arrows, ellipses, arcs, circles, points and text
+function renderPlaneLayer(g: SVGElement, layer: PlaneLayer, t: PlaneTransform): void {
+    const { circleGroups, lineGroups, polylines, curveGroups, arrowGroups, ellipseGroups, arcGroups, polygonGroups, rectGroups, pointGroups, textGroups } = layer;
 
     // Render polygons first (so they appear behind circles and lines)
     for (const polygonGroup of polygonGroups) {
@@ -302,20 +658,70 @@ export function render2DPlane(
 
         let pointsStr = "";
         for (const pt of points) {
-            const px = (pt.x / W) * drawWidth;
-            const py = (pt.y / H) * drawHeight;
+            const px = t.x(pt.x);
+            const py = t.y(pt.y);
             pointsStr += `${px},${py} `;
         }
 
         const polygonElement = document.createElementNS(SVG_NS, "polygon");
         polygonElement.setAttribute("points", pointsStr.trim());
         polygonElement.setAttribute("fill", fillColor);
+        const lineWidth = polygonGroup.lineWidth ?? 2;
         polygonElement.setAttribute("stroke", lineColor);
-        polygonElement.setAttribute("stroke-width", "2");
+        polygonElement.setAttribute("stroke-width", String(lineWidth));
         polygonElement.setAttribute("stroke-linejoin", "miter");
+        applyLineStyle(polygonElement, polygonGroup.style ?? 'SOLID', lineWidth);
+        if (polygonGroup.tooltip) {
+            const title = document.createElementNS(SVG_NS, "title");
+            title.textContent = polygonGroup.tooltip;
+            polygonElement.appendChild(title);
+        }
         g.appendChild(polygonElement);
     }
 
+    // Render axis-aligned rectangles (same layer as polygons)
+    for (const rectGroup of rectGroups) {
+        const { lineColor, fillColor, rects } = rectGroup;
+        for (const rect of rects) {
+            // (x, y) and (x + w, y + h) are opposite corners; with Y_UP (x, y) is the bottom-left one
+            const rx = Math.min(t.x(rect.x), t.x(rect.x + rect.w));
+            const ry = Math.min(t.y(rect.y), t.y(rect.y + rect.h));
+            const rw = Math.abs(rect.w) * t.scaleX;
+            const rh = Math.abs(rect.h) * t.scaleY;
+
+            const rectElement = document.createElementNS(SVG_NS, "rect");
+            rectElement.setAttribute("x", String(rx));
+            rectElement.setAttribute("y", String(ry));
+            rectElement.setAttribute("width", String(Math.max(0, rw)));
+            rectElement.setAttribute("height", String(Math.max(0, rh)));
+            rectElement.setAttribute("fill", fillColor);
+            rectElement.setAttribute("stroke", lineColor);
+            rectElement.setAttribute("stroke-width", "1");
+
+            const title = document.createElementNS(SVG_NS, "title");
+            title.textContent = `Rect at (${rect.x}, ${rect.y}), ${rect.w}x${rect.h}`;
+            if (rect.label) {
+                title.textContent += `\n${rect.label}`;
+            }
+            rectElement.appendChild(title);
+            g.appendChild(rectElement);
+
+            if (rect.label) {
+                const fontSize = Math.max(6, Math.min(12, rh * 0.6));
+                const textElement = document.createElementNS(SVG_NS, "text");
+                textElement.setAttribute("x", String(rx + rw / 2));
+                textElement.setAttribute("y", String(ry + rh / 2));
+                textElement.setAttribute("fill", "black");
+                textElement.setAttribute("font-size", String(fontSize));
+                textElement.setAttribute("text-anchor", "middle");
+                textElement.setAttribute("dominant-baseline", "middle");
+                textElement.setAttribute("pointer-events", "none");
+                textElement.textContent = rect.label;
+                g.appendChild(textElement);
+            }
+        }
+    }
+
     // Render lines
     for (const lineGroup of lineGroups) {
         const { color, width, points } = lineGroup;
@@ -328,10 +734,10 @@ export function render2DPlane(
             const p1 = points[i];
             const p2 = points[i + 1];
 
-            const x1 = (p1.x / W) * drawWidth;
-            const y1 = (p1.y / H) * drawHeight;
-            const x2 = (p2.x / W) * drawWidth;
-            const y2 = (p2.y / H) * drawHeight;
+            const x1 = t.x(p1.x);
+            const y1 = t.y(p1.y);
+            const x2 = t.x(p2.x);
+            const y2 = t.y(p2.y);
 
             const line = document.createElementNS(SVG_NS, "line");
             line.setAttribute("x1", String(x1));
@@ -341,18 +747,106 @@ export function render2DPlane(
             line.setAttribute("stroke", color);
             line.setAttribute("stroke-width", String(width));
             line.setAttribute("stroke-linecap", "round");
+            applyLineStyle(line, lineGroup.style ?? 'SOLID', width);
             g.appendChild(line);
         }
     }
 
+    // Render polylines (connected paths such as routes and tours)
+    for (const lineData of polylines) {
+        const points = lineData.points;
+        if (points.length < 2) continue;
+
+        const pointsStr = points
+            .map(pt => `${t.x(pt.x)},${t.y(pt.y)}`)
+            .join(' ');
+        const polyline = document.createElementNS(SVG_NS, "polyline");
+        polyline.setAttribute("points", pointsStr);
+        polyline.setAttribute("fill", "none");
+        polyline.setAttribute("stroke", lineData.color);
+        polyline.setAttribute("stroke-width", String(lineData.width));
+        polyline.setAttribute("stroke-linejoin", "round");
+        polyline.setAttribute("stroke-linecap", "round");
+        applyLineStyle(polyline, lineData.style, lineData.width);
+        g.appendChild(polyline);
+    }
+
+    // Render quadratic and cubic Bezier curves
+    for (const group of curveGroups) {
+        for (const curve of group.curves) {
+            const [p0, ...rest] = curve.map(pt => `${t.x(pt.x)},${t.y(pt.y)}`);
+            const path = document.createElementNS(SVG_NS, "path");
+            path.setAttribute("d", `M ${p0} ${group.cubic ? 'C' : 'Q'} ${rest.join(' ')}`);
+            path.setAttribute("fill", "none");
+            path.setAttribute("stroke", group.color);
+            path.setAttribute("stroke-width", String(group.width));
+            path.setAttribute("stroke-linecap", "round");
+            applyLineStyle(path, group.style, group.width);
+            g.appendChild(path);
+        }
+    }
+
+    // Render arrows
+    for (const group of arrowGroups) {
+        for (const arrow of group.arrows) {
+            g.appendChild(createArrow(
+                t.x(arrow.from.x),
+                t.y(arrow.from.y),
+                t.x(arrow.to.x),
+                t.y(arrow.to.y),
+                group.color,
+                group.width,
+                group.headSize,
+                'SOLID'
+            ));
+        }
+    }
+
+    // Render ellipses
+    for (const group of ellipseGroups) {
+        for (const e of group.ellipses) {
+            const polygon = document.createElementNS(SVG_NS, "polygon");
+            polygon.setAttribute("points", planeArcPoints(t, e.cx, e.cy, e.rx, e.ry, e.angle, 0, 360).join(' '));
+            polygon.setAttribute("fill", group.fillColor);
+            polygon.setAttribute("stroke", group.lineColor);
+            polygon.setAttribute("stroke-width", String(group.lineWidth));
+            applyLineStyle(polygon, group.style, group.lineWidth);
+            g.appendChild(polygon);
+        }
+    }
+
+    // Render arcs (open) and sectors (closed through the center)
+    for (const group of arcGroups) {
+        for (const arc of group.arcs) {
+            const points = planeArcPoints(t, arc.cx, arc.cy, arc.r, arc.r, 0, arc.start, arc.end);
+            let element: SVGElement;
+            if (group.fillColor !== undefined) {
+                element = document.createElementNS(SVG_NS, "polygon");
+                element.setAttribute("points", [`${t.x(arc.cx)},${t.y(arc.cy)}`, ...points].join(' '));
+                element.setAttribute("fill", group.fillColor);
+                element.setAttribute("stroke-linejoin", "round");
+            } else {
+                element = document.createElementNS(SVG_NS, "polyline");
+                element.setAttribute("points", points.join(' '));
+                element.setAttribute("fill", "none");
+                element.setAttribute("stroke-linecap", "round");
+            }
+            element.setAttribute("stroke", group.lineColor);
+            element.setAttribute("stroke-width", String(group.lineWidth));
+            applyLineStyle(element, group.style, group.lineWidth);
+            g.appendChild(element);
+        }
+    }
+
     // Render circles
     for (const circleGroup of circleGroups) {
         const { lineColor, fillColor, circles } = circleGroup;
+        const lineWidth = circleGroup.lineWidth ?? 2;
 
         for (const circle of circles) {
-            const cx = (circle.x / W) * drawWidth;
-            const cy = (circle.y / H) * drawHeight;
-            const r = (circle.r / W) * drawWidth; // Scale radius based on width
+            const cx = t.x(circle.x);
+            const cy = t.y(circle.y);
+            const r = circle.r * t.scaleX; // Scale radius based on width
 
             const circleElement = document.createElementNS(SVG_NS, "circle");
             circleElement.setAttribute("cx", String(cx));
@@ -360,37 +854,112 @@ export function render2DPlane(
             circleElement.setAttribute("r", String(r));
             circleElement.setAttribute("fill", fillColor);
             circleElement.setAttribute("stroke", lineColor);
-            circleElement.setAttribute("stroke-width", "2");
+            circleElement.setAttribute("stroke-width", String(lineWidth));
+            applyLineStyle(circleElement, circleGroup.style ?? 'SOLID', lineWidth);
 
             // Add tooltip
             const title = document.createElementNS(SVG_NS, "title");
             title.textContent = `Circle at (${circle.x.toFixed(2)}, ${circle.y.toFixed(2)}), r=${circle.r.toFixed(2)}`;
+            if (circle.tooltip) {
+                title.textContent += `\n${circle.tooltip}`;
+            }
             circleElement.appendChild(title);
 
             g.appendChild(circleElement);
         }
     }
 
+    // Render point markers. Their size is in pixels, so it does not depend on the plane scale
+    for (const group of pointGroups) {
+        for (const pt of group.points) {
+            const marker = createMarker(group.shape, t.x(pt.x), t.y(pt.y), group.size, group.color, group.color, 1);
+            marker.setAttribute("pointer-events", "none");
+            g.appendChild(marker);
+        }
+    }
+
     // Render text
     for (const textGroup of textGroups) {
         const { color, fontSize, texts } = textGroup;
+        const anchor = textGroup.anchor ?? 'C';
+        const rotation = textGroup.rotation ?? 0;
+        const padding = textGroup.padding ?? 0;
 
         for (const textItem of texts) {
-            const tx = (textItem.x / W) * drawWidth;
-            const ty = (textItem.y / H) * drawHeight;
+            // Anchor point in pixels, shifted by the offset
+            const tx = t.x(textItem.x) + (textGroup.offsetX ?? 0);
+            const ty = t.y(textItem.y) + (textGroup.offsetY ?? 0);
+            const pos = anchorPosition(anchor, tx, ty, 0, 0, padding);
 
             const textElement = document.createElementNS(SVG_NS, "text");
-            textElement.setAttribute("x", String(tx));
-            textElement.setAttribute("y", String(ty));
+            textElement.setAttribute("x", String(pos.x));
+            textElement.setAttribute("y", String(pos.y));
             textElement.setAttribute("fill", color);
             textElement.setAttribute("font-size", String(fontSize));
-            textElement.setAttribute("text-anchor", "middle");
-            textElement.setAttribute("dominant-baseline", "middle");
+            textElement.setAttribute("text-anchor", pos.textAnchor);
+            textElement.setAttribute("dominant-baseline", pos.baseline);
             textElement.textContent = textItem.text;
 
-            g.appendChild(textElement);
+            if (rotation === 0 && textGroup.background === undefined) {
+                g.appendChild(textElement);
+                continue;
+            }
+
+            const item = document.createElementNS(SVG_NS, "g");
+            if (rotation !== 0) {
+                item.setAttribute("transform", `rotate(${rotation} ${tx} ${ty})`);
+            }
+            if (textGroup.background !== undefined) {
+                // The text is not laid out yet, so estimate its box from the font size
+                const w = textItem.text.length * fontSize * 0.6;
+                const h = fontSize;
+                const left = pos.textAnchor === "start" ? pos.x : pos.textAnchor === "end" ? pos.x - w : pos.x - w / 2;
+                const top = pos.baseline === "hanging" ? pos.y : pos.baseline === "middle" ? pos.y - h / 2 : pos.y - h;
+                const rect = document.createElementNS(SVG_NS, "rect");
+                rect.setAttribute("x", String(left - padding));
+                rect.setAttribute("y", String(top - padding));
+                rect.setAttribute("width", String(w + padding * 2));
+                rect.setAttribute("height", String(h + padding * 2));
+                rect.setAttribute("fill", textGroup.background);
+                item.appendChild(rect);
+            }
+            item.appendChild(textElement);
+            g.appendChild(item);
         }
     }
+}
+
+export function render2DPlane(
+    svg: SVGSVGElement,
+    view: PlaneView,
+    layers: PlaneLayer[],
+    left: number = 0,
+    top: number = 0,
+    right: number = 800,
+    bottom: number = 800
+): void {
+    // Calculate the actual drawing area based on bounds
+    const drawWidth = right - left;
+    const drawHeight = bottom - top;
+
+    // Create a group for this 2D plane item with offset
+    const g = document.createElementNS(SVG_NS, "g");
+    g.setAttribute("transform", `translate(${left}, ${top})`);
+
+    // Add a white background
+    const background = document.createElementNS(SVG_NS, "rect");
+    background.setAttribute("x", "0");
+    background.setAttribute("y", "0");
+    background.setAttribute("width", String(drawWidth));
+    background.setAttribute("height", String(drawHeight));
+    background.setAttribute("fill", "white");
+    g.appendChild(background);
+
+    // Draw layers in order so that later layers appear on top
+    const t = createPlaneTransform(view, drawWidth, drawHeight);
+    for (const layer of layers) {
+        renderPlaneLayer(g, layer, t);
+    }
 
     svg.appendChild(g);
 }
@@ -399,12 +968,8 @@ export function render2DPlaneFromCommand(svg: SVGSVGElement, cmd: TwoDPlaneComma
     const bounds = cmd.bounds || { left: 0, top: 0, right: canvasW, bottom: canvasH };
     render2DPlane(
         svg,
-        cmd.H,
-        cmd.W,
-        cmd.circleGroups,
-        cmd.lineGroups,
-        cmd.polygonGroups,
-        cmd.textGroups,
+        cmd.view,
+        cmd.layers,
         bounds.left,
         bounds.top,
         bounds.right,
@@ -412,6 +977,245 @@ export function render2DPlaneFromCommand(svg: SVGSVGElement, cmd: TwoDPlaneComma
     );
 }
 
+// Axial neighbor directions, shared with the Rust library (HEX_DIRECTIONS)
+const HEX_DIRECTIONS: [number, number][] = [[1, 0], [1, -1], [0, -1], [-1, 0], [-1, 1], [0, 1]];
+
+export function renderHexGridFromCommand(svg: SVGSVGElement, cmd: HexGridCommand, canvasW: number = 800, canvasH: number = 800): void {
+    const bounds = cmd.bounds || { left: 0, top: 0, right: canvasW, bottom: canvasH };
+    const { H, W, orientation } = cmd;
+    const pointy = orientation === 'POINTY';
+    const sqrt3 = Math.sqrt(3);
+
+    // Fit the lattice into the bounds (size = circumradius of a cell)
+    const drawWidth = bounds.right - bounds.left;
+    const drawHeight = bounds.bottom - bounds.top;
+    const unitWidth = pointy ? sqrt3 * (W + (H > 1 ? 0.5 : 0)) : 1.5 * (W - 1) + 2;
+    const unitHeight = pointy ? 1.5 * (H - 1) + 2 : sqrt3 * (H + (W > 1 ? 0.5 : 0));
+    const size = Math.min(drawWidth / unitWidth, drawHeight / unitHeight);
+    const offsetX = (drawWidth - unitWidth * size) / 2;
+    const offsetY = (drawHeight - unitHeight * size) / 2;
+
+    const center = (x: number, y: number): Point => {
+        if (pointy) {
+            return { x: size * sqrt3 * (x + 0.5 * (y & 1) + 0.5), y: size * (1.5 * y + 1) };
+        }
+        return { x: size * (1.5 * x + 1), y: size * sqrt3 * (y + 0.5 * (x & 1) + 0.5) };
+    };
+    // Screen angle (degrees, y-down) of the neighbor in direction dir
+    const dirAngle = (dir: number): number => (pointy ? 0 : 30) - 60 * dir;
+    const corner = (c: Point, angle: number): Point => ({
+        x: c.x + size * Math.cos(angle * Math.PI / 180),
+        y: c.y + size * Math.sin(angle * Math.PI / 180),
+    });
+    const neighbor = (x: number, y: number, dir: number): [number, number] => {
+        const q = pointy ? x - (y - (y & 1)) / 2 : x;
+        const r = pointy ? y : y - (x - (x & 1)) / 2;
+        const nq = q + HEX_DIRECTIONS[dir][0];
+        const nr = r + HEX_DIRECTIONS[dir][1];
+        return pointy ? [nq + (nr - (nr & 1)) / 2, nr] : [nq, nr + (nq - (nq & 1)) / 2];
+    };
+
+    // An edge hidden from one side is hidden from the neighbor's side too
+    const hidden = new Set<string>();
+    for (const e of cmd.hiddenEdges) {
+        hidden.add(`${e.x},${e.y},${e.dir}`);
+        const [nx, ny] = neighbor(e.x, e.y, e.dir);
+        hidden.add(`${nx},${ny},${(e.dir + 3) % 6}`);
+    }
+
+    const g = document.createElementNS(SVG_NS, "g");
+    g.setAttribute("transform", `translate(${bounds.left + offsetX}, ${bounds.top + offsetY})`);
+
+    const fontSize = Math.min(size * 0.8, 30);
+    for (let y = 0; y < H; y++) {
+        for (let x = 0; x < W; x++) {
+            const c = center(x, y);
+            const corners = [0, 1, 2, 3, 4, 5].map(k => corner(c, dirAngle(0) + 30 - 60 * k));
+            const polygon = document.createElementNS(SVG_NS, "polygon");
+            polygon.setAttribute("points", corners.map(p => `${p.x},${p.y}`).join(" "));
+            polygon.setAttribute("fill", cmd.cellColors[y][x]);
+            polygon.setAttribute("stroke", "none");
+            const title = document.createElementNS(SVG_NS, "title");
+            const text = cmd.cellTexts[y][x];
+            title.textContent = `(${y}, ${x})` + (text ? `: ${text}` : "");
+            polygon.appendChild(title);
+            g.appendChild(polygon);
+
+            if (text) {
+                const textElement = document.createElementNS(SVG_NS, "text");
+                textElement.setAttribute("x", String(c.x));
+                textElement.setAttribute("y", String(c.y));
+                textElement.setAttribute("fill", cmd.textColor);
+                textElement.setAttribute("font-size", String(fontSize / Math.max(1, text.length * 0.6)));
+                textElement.setAttribute("font-family", "sans-serif");
+                textElement.setAttribute("text-anchor", "middle");
+                textElement.setAttribute("dominant-baseline", "middle");
+                textElement.setAttribute("pointer-events", "none");
+                textElement.textContent = text;
+                g.appendChild(textElement);
+            }
+        }
+    }
+
+    // Edges (shared edges are drawn from both cells)
+    for (let y = 0; y < H; y++) {
+        for (let x = 0; x < W; x++) {
+            const c = center(x, y);
+            for (let dir = 0; dir < 6; dir++) {
+                if (hidden.has(`${x},${y},${dir}`)) continue;
+                const a = corner(c, dirAngle(dir) - 30);
+                const b = corner(c, dirAngle(dir) + 30);
+                const line = document.createElementNS(SVG_NS, "line");
+                line.setAttribute("x1", String(a.x));
+                line.setAttribute("y1", String(a.y));
+                line.setAttribute("x2", String(b.x));
+                line.setAttribute("y2", String(b.y));
+                line.setAttribute("stroke", cmd.borderColor);
+                line.setAttribute("stroke-width", "2");
+                line.setAttribute("stroke-linecap", "round");
+                line.setAttribute("pointer-events", "none");
+                g.appendChild(line);
+            }
+        }
+    }
+
+    // Lines between cell centers
+    for (const lineData of cmd.lines) {
+        if (lineData.points.length < 2) continue;
+        const polyline = document.createElementNS(SVG_NS, "polyline");
+        polyline.setAttribute("points", lineData.points.map(p => {
+            const c = center(p.x, p.y);
+            return `${c.x},${c.y}`;
+        }).join(" "));
+        polyline.setAttribute("fill", "none");
+        polyline.setAttribute("stroke", lineData.color);
+        polyline.setAttribute("stroke-width", String(Math.max(2, size * 0.15)));
+        polyline.setAttribute("pointer-events", "none");
+        g.appendChild(polyline);
+    }
+
+    svg.appendChild(g);
+}
+
+export function renderTriGridFromCommand(svg: SVGSVGElement, cmd: TriGridCommand, canvasW: number = 800, canvasH: number = 800): void {
+    const bounds = cmd.bounds || { left: 0, top: 0, right: canvasW, bottom: canvasH };
+    const { H, W } = cmd;
+    const sqrt3 = Math.sqrt(3);
+
+    // Fit the lattice into the bounds (size = side length of a triangle)
+    const drawWidth = bounds.right - bounds.left;
+    const drawHeight = bounds.bottom - bounds.top;
+    const unitWidth = (W + 1) / 2;
+    const unitHeight = H * sqrt3 / 2;
+    const size = Math.min(drawWidth / unitWidth, drawHeight / unitHeight);
+    const rowHeight = size * sqrt3 / 2;
+    const offsetX = (drawWidth - unitWidth * size) / 2;
+    const offsetY = (drawHeight - unitHeight * size) / 2;
+
+    const isUp = (x: number, y: number): boolean => (x + y) % 2 === 0;
+    // Corners in the order: horizontal edge (2 corners), then the apex
+    const corners = (x: number, y: number): Point[] => {
+        const left = x * size / 2;
+        const top = y * rowHeight;
+        const bottom = top + rowHeight;
+        if (isUp(x, y)) {
+            return [{ x: left, y: bottom }, { x: left + size, y: bottom }, { x: left + size / 2, y: top }];
+        }
+        return [{ x: left, y: top }, { x: left + size, y: top }, { x: left + size / 2, y: bottom }];
+    };
+    const center = (x: number, y: number): Point => {
+        const c = corners(x, y);
+        return { x: (c[0].x + c[1].x + c[2].x) / 3, y: (c[0].y + c[1].y + c[2].y) / 3 };
+    };
+    // Edge endpoints for dir 0 (horizontal), 1 (left) and 2 (right)
+    const edge = (x: number, y: number, dir: number): [Point, Point] => {
+        const c = corners(x, y);
+        return dir === 0 ? [c[0], c[1]] : dir === 1 ? [c[0], c[2]] : [c[1], c[2]];
+    };
+    const neighbor = (x: number, y: number, dir: number): [number, number] => {
+        if (dir === 0) return [x, isUp(x, y) ? y + 1 : y - 1];
+        return [dir === 1 ? x - 1 : x + 1, y];
+    };
+
+    // An edge hidden from one side is hidden from the neighbor's side too (left and right swap)
+    const hidden = new Set<string>();
+    for (const e of cmd.hiddenEdges) {
+        hidden.add(`${e.x},${e.y},${e.dir}`);
+        const [nx, ny] = neighbor(e.x, e.y, e.dir);
+        hidden.add(`${nx},${ny},${e.dir === 0 ? 0 : 3 - e.dir}`);
+    }
+
+    const g = document.createElementNS(SVG_NS, "g");
+    g.setAttribute("transform", `translate(${bounds.left + offsetX}, ${bounds.top + offsetY})`);
+
+    const fontSize = Math.min(size * 0.35, 30);
+    for (let y = 0; y < H; y++) {
+        for (let x = 0; x < W; x++) {
+            const polygon = document.createElementNS(SVG_NS, "polygon");
+            polygon.setAttribute("points", corners(x, y).map(p => `${p.x},${p.y}`).join(" "));
+            polygon.setAttribute("fill", cmd.cellColors[y][x]);
+            polygon.setAttribute("stroke", "none");
+            const title = document.createElementNS(SVG_NS, "title");
+            const text = cmd.cellTexts[y][x];
+            title.textContent = `(${y}, ${x})` + (text ? `: ${text}` : "");
+            polygon.appendChild(title);
+            g.appendChild(polygon);
+
+            if (text) {
+                const c = center(x, y);
+                const textElement = document.createElementNS(SVG_NS, "text");
+                textElement.setAttribute("x", String(c.x));
+                textElement.setAttribute("y", String(c.y));
+                textElement.setAttribute("fill", cmd.textColor);
+                textElement.setAttribute("font-size", String(fontSize / Math.max(1, text.length * 0.6)));
+                textElement.setAttribute("font-family", "sans-serif");
+                textElement.setAttribute("text-anchor", "middle");
+                textElement.setAttribute("dominant-baseline", "middle");
+                textElement.setAttribute("pointer-events", "none");
+                textElement.textContent = text;
+                g.appendChild(textElement);
+            }
+        }
+    }
+
+    // Edges (shared edges are drawn from both cells)
+    for (let y = 0; y < H; y++) {
+        for (let x = 0; x < W; x++) {
+            for (let dir = 0; dir < 3; dir++) {
+                if (hidden.has(`${x},${y},${dir}`)) continue;
+                const [a, b] = edge(x, y, dir);
+                const line = document.createElementNS(SVG_NS, "line");
+                line.setAttribute("x1", String(a.x));
+                line.setAttribute("y1", String(a.y));
+                line.setAttribute("x2", String(b.x));
+                line.setAttribute("y2", String(b.y));
+                line.setAttribute("stroke", cmd.borderColor);
+                line.setAttribute("stroke-width", "2");
+                line.setAttribute("stroke-linecap", "round");
+                line.setAttribute("pointer-events", "none");
+                g.appendChild(line);
+            }
+        }
+    }
+
+    // Lines between cell centroids
+    for (const lineData of cmd.lines) {
+        if (lineData.points.length < 2) continue;
+        const polyline = document.createElementNS(SVG_NS, "polyline");
+        polyline.setAttribute("points", lineData.points.map(p => {
+            const c = center(p.x, p.y);
+            return `${c.x},${c.y}`;
+        }).join(" "));
+        polyline.setAttribute("fill", "none");
+        polyline.setAttribute("stroke", lineData.color);
+        polyline.setAttribute("stroke-width", String(Math.max(2, size * 0.08)));
+        polyline.setAttribute("pointer-events", "none");
+        g.appendChild(polyline);
+    }
+
+    svg.appendChild(g);
+}
+
 function formatNumber(value: number): string {
     if (Math.abs(value - Math.round(value)) < 1e-9) return String(Math.round(value));
     return parseFloat(value.toFixed(2)).toString();
@@ -536,3 +1340,394 @@ export function renderBarGraph(container: HTMLElement, cmd: BarGraphCommand): vo
 
     container.appendChild(svg);
 }
+
+// Axis labels for values that do not fit formatNumber, e.g. small values on a log scale
+function formatAxisValue(value: number): string {
+    if (value !== 0 && (Math.abs(value) >= 1e5 || Math.abs(value) < 1e-2)) {
+        return value.toExponential(1);
+    }
+    return formatNumber(value);
+}
+
+let lineChartCount = 0;
+
+export function renderLineChart(container: HTMLElement, cmd: LineChartCommand): void {
+    const { logScale, marker, series } = cmd;
+
+    const totalWidth = 330;
+    const paddingLeft = 55;
+    const paddingRight = 10;
+    const paddingTop = 25;
+    const paddingBottom = 25;
+
+    const chartWidth = totalWidth - paddingLeft - paddingRight;
+    const chartHeight = 135;
+    const totalHeight = paddingTop + chartHeight + paddingBottom;
+
+    // Values that cannot be drawn on a log scale are skipped
+    const visible = series.map(s => s.points.filter(pt => !logScale || pt.y > 0));
+    const allPoints = visible.flat();
+    if (allPoints.length === 0) return;
+
+    // Auto bounds come from the data. Degenerate ranges are widened so the chart stays drawable
+    const toY = (v: number): number => logScale ? Math.log10(v) : v;
+    let xMin = cmd.xMin ?? Math.min(...allPoints.map(pt => pt.x));
+    let xMax = cmd.xMax ?? Math.max(...allPoints.map(pt => pt.x));
+    let yMin = cmd.yMin !== undefined ? toY(cmd.yMin) : Math.min(...allPoints.map(pt => toY(pt.y)));
+    let yMax = cmd.yMax !== undefined ? toY(cmd.yMax) : Math.max(...allPoints.map(pt => toY(pt.y)));
+    if (xMin >= xMax) {
+        xMin -= 1;
+        xMax += 1;
+    }
+    if (yMin >= yMax) {
+        yMin -= 1;
+        yMax += 1;
+    }
+
+    const sx = (x: number): number => paddingLeft + chartWidth * (x - xMin) / (xMax - xMin);
+    const sy = (y: number): number => paddingTop + chartHeight * (1 - (toY(y) - yMin) / (yMax - yMin));
+
+    const svg = document.createElementNS(SVG_NS, "svg");
+    svg.setAttribute("width", String(totalWidth));
+    svg.setAttribute("height", String(totalHeight));
+    svg.setAttribute("viewBox", `0 0 ${totalWidth} ${totalHeight}`);
+    svg.style.display = "block";
+
+    // Background
+    const bg = document.createElementNS(SVG_NS, "rect");
+    bg.setAttribute("x", "0");
+    bg.setAttribute("y", "0");
+    bg.setAttribute("width", String(totalWidth));
+    bg.setAttribute("height", String(totalHeight));
+    bg.setAttribute("fill", "#fafafa");
+    bg.setAttribute("stroke", "#ddd");
+    bg.setAttribute("stroke-width", "1");
+    bg.setAttribute("rx", "4");
+    svg.appendChild(bg);
+
+    // Y-axis gridlines and labels (evenly spaced in log space for LOG)
+    const numTicks = 5;
+    for (let i = 0; i <= numTicks; i++) {
+        const t = yMin + (yMax - yMin) * (i / numTicks);
+        const y = paddingTop + chartHeight * (1 - i / numTicks);
+
+        const line = document.createElementNS(SVG_NS, "line");
+        line.setAttribute("x1", String(paddingLeft));
+        line.setAttribute("y1", String(y));
+        line.setAttribute("x2", String(totalWidth - paddingRight));
+        line.setAttribute("y2", String(y));
+        line.setAttribute("stroke", (i === 0 || i === numTicks) ? "#bbb" : "#eee");
+        line.setAttribute("stroke-width", "1");
+        svg.appendChild(line);
+
+        const text = document.createElementNS(SVG_NS, "text");
+        text.setAttribute("x", String(paddingLeft - 5));
+        text.setAttribute("y", String(y));
+        text.setAttribute("text-anchor", "end");
+        text.setAttribute("dominant-baseline", "middle");
+        text.setAttribute("font-size", "11");
+        text.setAttribute("font-family", "sans-serif");
+        text.setAttribute("fill", "#666");
+        text.textContent = formatAxisValue(logScale ? Math.pow(10, t) : t);
+        svg.appendChild(text);
+    }
+
+    // X-axis labels at both ends and the middle
+    for (let i = 0; i <= 2; i++) {
+        const value = xMin + (xMax - xMin) * (i / 2);
+        const text = document.createElementNS(SVG_NS, "text");
+        text.setAttribute("x", String(sx(value)));
+        text.setAttribute("y", String(paddingTop + chartHeight + 15));
+        text.setAttribute("text-anchor", i === 0 ? "start" : i === 2 ? "end" : "middle");
+        text.setAttribute("font-size", "11");
+        text.setAttribute("font-family", "sans-serif");
+        text.setAttribute("fill", "#666");
+        text.textContent = formatAxisValue(value);
+        svg.appendChild(text);
+    }
+
+    // Clip the series to the chart area so fixed axes can cut off outliers
+    const clipId = `line-chart-clip-${lineChartCount++}`;
+    const clipPath = document.createElementNS(SVG_NS, "clipPath");
+    clipPath.setAttribute("id", clipId);
+    const clipRect = document.createElementNS(SVG_NS, "rect");
+    clipRect.setAttribute("x", String(paddingLeft));
+    clipRect.setAttribute("y", String(paddingTop));
+    clipRect.setAttribute("width", String(chartWidth));
+    clipRect.setAttribute("height", String(chartHeight));
+    clipPath.appendChild(clipRect);
+    svg.appendChild(clipPath);
+
+    const plot = document.createElementNS(SVG_NS, "g");
+    plot.setAttribute("clip-path", `url(#${clipId})`);
+    for (let k = 0; k < series.length; k++) {
+        const points = visible[k];
+        if (points.length === 0) continue;
+        const polyline = document.createElementNS(SVG_NS, "polyline");
+        polyline.setAttribute("points", points.map(pt => `${sx(pt.x)},${sy(pt.y)}`).join(' '));
+        polyline.setAttribute("fill", "none");
+        polyline.setAttribute("stroke", series[k].color);
+        polyline.setAttribute("stroke-width", "1.5");
+        polyline.setAttribute("stroke-linejoin", "round");
+        const title = document.createElementNS(SVG_NS, "title");
+        title.textContent = series[k].name;
+        polyline.appendChild(title);
+        plot.appendChild(polyline);
+    }
+    svg.appendChild(plot);
+
+    // Current frame marker, with a dot on the latest point of each series at or before it
+    if (marker !== undefined && marker >= xMin && marker <= xMax) {
+        const line = document.createElementNS(SVG_NS, "line");
+        line.setAttribute("x1", String(sx(marker)));
+        line.setAttribute("y1", String(paddingTop));
+        line.setAttribute("x2", String(sx(marker)));
+        line.setAttribute("y2", String(paddingTop + chartHeight));
+        line.setAttribute("stroke", "#d00");
+        line.setAttribute("stroke-width", "1");
+        line.setAttribute("stroke-dasharray", "4 3");
+        svg.appendChild(line);
+
+        for (let k = 0; k < series.length; k++) {
+            let current: Point | undefined;
+            for (const pt of visible[k]) {
+                if (pt.x <= marker && (current === undefined || pt.x >= current.x)) current = pt;
+            }
+            if (current === undefined) continue;
+            const dot = document.createElementNS(SVG_NS, "circle");
+            dot.setAttribute("cx", String(sx(current.x)));
+            dot.setAttribute("cy", String(sy(current.y)));
+            dot.setAttribute("r", "3");
+            dot.setAttribute("fill", series[k].color);
+            dot.setAttribute("clip-path", `url(#${clipId})`);
+            const title = document.createElementNS(SVG_NS, "title");
+            title.textContent = `${series[k].name}: ${current.y} (x = ${current.x})`;
+            dot.appendChild(title);
+            svg.appendChild(dot);
+        }
+    }
+
+    // Legend along the top edge
+    let legendX = paddingLeft;
+    for (const s of series) {
+        const swatch = document.createElementNS(SVG_NS, "line");
+        swatch.setAttribute("x1", String(legendX));
+        swatch.setAttribute("y1", String(paddingTop / 2));
+        swatch.setAttribute("x2", String(legendX + 12));
+        swatch.setAttribute("y2", String(paddingTop / 2));
+        swatch.setAttribute("stroke", s.color);
+        swatch.setAttribute("stroke-width", "2");
+        svg.appendChild(swatch);
+
+        const text = document.createElementNS(SVG_NS, "text");
+        text.setAttribute("x", String(legendX + 16));
+        text.setAttribute("y", String(paddingTop / 2));
+        text.setAttribute("dominant-baseline", "middle");
+        text.setAttribute("font-size", "10");
+        text.setAttribute("font-family", "sans-serif");
+        text.setAttribute("fill", "#333");
+        text.textContent = s.name;
+        svg.appendChild(text);
+        legendX += 16 + s.name.length * 6 + 10;
+    }
+
+    container.appendChild(svg);
+}
+
+export function renderHistogram(container: HTMLElement, cmd: HistogramCommand): void {
+    const { fillColor, edges, counts, stats } = cmd;
+
+    const totalWidth = 330;
+    const paddingLeft = 55;
+    const paddingRight = 10;
+    const paddingTop = 10;
+    const paddingBottom = 25;
+
+    const chartWidth = totalWidth - paddingLeft - paddingRight;
+    const chartHeight = 135;
+    const totalHeight = paddingTop + chartHeight + paddingBottom;
+
+    if (counts.length > 0) {
+        const xMin = edges[0];
+        const xMax = edges[edges.length - 1];
+        const yMax = Math.max(1, ...counts);
+        const sx = (x: number): number => paddingLeft + chartWidth * (x - xMin) / (xMax - xMin);
+        const sy = (c: number): number => paddingTop + chartHeight * (1 - c / yMax);
+
+        const svg = document.createElementNS(SVG_NS, "svg");
+        svg.setAttribute("width", String(totalWidth));
+        svg.setAttribute("height", String(totalHeight));
+        svg.setAttribute("viewBox", `0 0 ${totalWidth} ${totalHeight}`);
+        svg.style.display = "block";
+
+        // Background
+        const bg = document.createElementNS(SVG_NS, "rect");
+        bg.setAttribute("x", "0");
+        bg.setAttribute("y", "0");
+        bg.setAttribute("width", String(totalWidth));
+        bg.setAttribute("height", String(totalHeight));
+        bg.setAttribute("fill", "#fafafa");
+        bg.setAttribute("stroke", "#ddd");
+        bg.setAttribute("stroke-width", "1");
+        bg.setAttribute("rx", "4");
+        svg.appendChild(bg);
+
+        // Y-axis gridlines and labels
+        const numTicks = 5;
+        for (let i = 0; i <= numTicks; i++) {
+            const value = yMax * (i / numTicks);
+            const y = sy(value);
+
+            const line = document.createElementNS(SVG_NS, "line");
+            line.setAttribute("x1", String(paddingLeft));
+            line.setAttribute("y1", String(y));
+            line.setAttribute("x2", String(totalWidth - paddingRight));
+            line.setAttribute("y2", String(y));
+            line.setAttribute("stroke", (i === 0 || i === numTicks) ? "#bbb" : "#eee");
+            line.setAttribute("stroke-width", "1");
+            svg.appendChild(line);
+
+            const text = document.createElementNS(SVG_NS, "text");
+            text.setAttribute("x", String(paddingLeft - 5));
+            text.setAttribute("y", String(y));
+            text.setAttribute("text-anchor", "end");
+            text.setAttribute("dominant-baseline", "middle");
+            text.setAttribute("font-size", "11");
+            text.setAttribute("font-family", "sans-serif");
+            text.setAttribute("fill", "#666");
+            text.textContent = formatNumber(value);
+            svg.appendChild(text);
+        }
+
+        // Bins are drawn edge to edge, each with its range and count as a tooltip
+        for (let i = 0; i < counts.length; i++) {
+            const x = sx(edges[i]);
+            const w = Math.max(0, sx(edges[i + 1]) - x);
+            const rect = document.createElementNS(SVG_NS, "rect");
+            rect.setAttribute("x", String(x));
+            rect.setAttribute("y", String(sy(counts[i])));
+            rect.setAttribute("width", String(w));
+            rect.setAttribute("height", String(chartHeight * counts[i] / yMax));
+            rect.setAttribute("fill", fillColor);
+            rect.setAttribute("stroke", "#fff");
+            rect.setAttribute("stroke-width", w > 3 ? "1" : "0");
+            const title = document.createElementNS(SVG_NS, "title");
+            const close = i === counts.length - 1 ? ']' : ')';
+            title.textContent = `[${formatAxisValue(edges[i])}, ${formatAxisValue(edges[i + 1])}${close}: ${counts[i]}`;
+            rect.appendChild(title);
+            svg.appendChild(rect);
+        }
+
+        // X-axis labels at both ends and the middle
+        for (let i = 0; i <= 2; i++) {
+            const value = xMin + (xMax - xMin) * (i / 2);
+            const text = document.createElementNS(SVG_NS, "text");
+            text.setAttribute("x", String(sx(value)));
+            text.setAttribute("y", String(paddingTop + chartHeight + 15));
+            text.setAttribute("text-anchor", i === 0 ? "start" : i === 2 ? "end" : "middle");
+            text.setAttribute("font-size", "11");
+            text.setAttribute("font-family", "sans-serif");
+            text.setAttribute("fill", "#666");
+            text.textContent = formatAxisValue(value);
+            svg.appendChild(text);
+        }
+
+        // Mean as a dashed line when it falls inside the bins
+        if (stats && stats.mean >= xMin && stats.mean <= xMax) {
+            const line = document.createElementNS(SVG_NS, "line");
+            line.setAttribute("x1", String(sx(stats.mean)));
+            line.setAttribute("y1", String(paddingTop));
+            line.setAttribute("x2", String(sx(stats.mean)));
+            line.setAttribute("y2", String(paddingTop + chartHeight));
+            line.setAttribute("stroke", "#d00");
+            line.setAttribute("stroke-width", "1");
+            line.setAttribute("stroke-dasharray", "4 3");
+            const title = document.createElementNS(SVG_NS, "title");
+            title.textContent = `mean: ${stats.mean}`;
+            line.appendChild(title);
+            svg.appendChild(line);
+        }
+
+        container.appendChild(svg);
+    }
+
+    // Summary statistics over all samples, including those outside the bins
+    const summary = document.createElement('div');
+    summary.style.fontFamily = 'monospace';
+    summary.style.fontSize = '11px';
+    summary.style.color = '#333';
+    summary.style.marginTop = '4px';
+    summary.style.whiteSpace = 'pre';
+    if (stats) {
+        const f = formatAxisValue;
+        summary.textContent =
+            `n=${stats.count}  mean=${f(stats.mean)}  sd=${f(stats.std)}\n` +
+            `min=${f(stats.min)}  p25=${f(stats.p25)}  med=${f(stats.median)}\n` +
+            `p75=${f(stats.p75)}  p95=${f(stats.p95)}  max=${f(stats.max)}`;
+    } else {
+        summary.textContent = 'n=0';
+    }
+    container.appendChild(summary);
+}
+
+export function renderTable(container: HTMLElement, cmd: TableCommand): void {
+    // Long tables scroll inside the panel
+    const wrapper = document.createElement('div');
+    wrapper.style.maxHeight = '300px';
+    wrapper.style.overflow = 'auto';
+    wrapper.style.border = '1px solid #ddd';
+    wrapper.style.borderRadius = '4px';
+
+    const table = document.createElement('table');
+    table.style.borderCollapse = 'collapse';
+    table.style.fontSize = '12px';
+    table.style.fontFamily = 'monospace';
+    table.style.width = '100%';
+
+    const cellStyle = (cell: HTMLElement): void => {
+        cell.style.padding = '2px 6px';
+        cell.style.borderBottom = '1px solid #eee';
+        cell.style.whiteSpace = 'nowrap';
+    };
+
+    if (cmd.header) {
+        const thead = document.createElement('thead');
+        const tr = document.createElement('tr');
+        for (const name of cmd.header) {
+            const th = document.createElement('th');
+            cellStyle(th);
+            th.textContent = name;
+            th.style.textAlign = 'left';
+            th.style.backgroundColor = '#f0f0f0';
+            th.style.position = 'sticky';
+            th.style.top = '0';
+            tr.appendChild(th);
+        }
+        thead.appendChild(tr);
+        table.appendChild(thead);
+    }
+
+    const tbody = document.createElement('tbody');
+    for (const row of cmd.rows) {
+        const tr = document.createElement('tr');
+        for (let j = 0; j < row.length; j++) {
+            const cell = row[j];
+            const td = document.createElement('td');
+            cellStyle(td);
+            td.textContent = cell.value;
+            td.style.textAlign = cell.kind === 'T' ? 'left' : 'right';
+            if (cell.color) td.style.backgroundColor = cell.color;
+            // Keys of a key-value table are shown like a header column
+            if (!cmd.header && j === 0) {
+                td.style.fontWeight = 'bold';
+                td.style.color = '#555';
+            }
+            tr.appendChild(td);
+        }
+        tbody.appendChild(tr);
+    }
+    table.appendChild(tbody);
+
+    wrapper.appendChild(table);
+    container.appendChild(wrapper);
+}
@@ -1,7 +1,7 @@
 import './styles.css';
-import { ParsedModes, Frame, GridCommand, TwoDPlaneCommand, CanvasCommand, BarGraphCommand } from './types';
+import { ParsedModes, Frame, GridCommand, HexGridCommand, TriGridCommand, TwoDPlaneCommand, CanvasCommand, BarGraphCommand, LineChartCommand, HistogramCommand, TableCommand } from './types';
 import { parseStderr } from './parser';
-import { createCanvasSvg, renderGridFromCommand, render2DPlaneFromCommand, renderBarGraph } from './renderer';
+import { createCanvasSvg, renderGridFromCommand, renderHexGridFromCommand, renderTriGridFromCommand, render2DPlaneFromCommand, renderBarGraph, renderLineChart, renderHistogram, renderTable } from './renderer';
 import { buildUrl } from './utils';
 
 export interface Sample {
@@ -544,6 +544,10 @@ function renderCurrentFrame(): void {
     for (const cmd of commands) {
         if (cmd.type === 'GRID') {
             renderGridFromCommand(svg, cmd as GridCommand, canvasW, canvasH);
+        } else if (cmd.type === 'HEX_GRID') {
+            renderHexGridFromCommand(svg, cmd as HexGridCommand, canvasW, canvasH);
+        } else if (cmd.type === 'TRI_GRID') {
+            renderTriGridFromCommand(svg, cmd as TriGridCommand, canvasW, canvasH);
         } else if (cmd.type === '2D_PLANE') {
             render2DPlaneFromCommand(svg, cmd as TwoDPlaneCommand, canvasW, canvasH);
         } else if (cmd.type === 'CANVAS') {
@@ -582,6 +586,45 @@ function renderCurrentFrame(): void {
 
             renderBarGraph(container, cmd as BarGraphCommand);
             infoDiv.appendChild(container);
+        } else if (cmd.type === 'LINE_CHART') {
+            const container = document.createElement('div');
+            container.style.marginBottom = '10px';
+
+            const titleLabel = document.createElement('div');
+            titleLabel.textContent = cmd.title;
+            titleLabel.style.fontWeight = 'bold';
+            titleLabel.style.marginBottom = '4px';
+            titleLabel.style.fontSize = '14px';
+            container.appendChild(titleLabel);
+
+            renderLineChart(container, cmd as LineChartCommand);
+            infoDiv.appendChild(container);
+        } else if (cmd.type === 'HISTOGRAM') {
+            const container = document.createElement('div');
+            container.style.marginBottom = '10px';
+
+            const titleLabel = document.createElement('div');
+            titleLabel.textContent = cmd.title;
+            titleLabel.style.fontWeight = 'bold';
+            titleLabel.style.marginBottom = '4px';
+            titleLabel.style.fontSize = '14px';
+            container.appendChild(titleLabel);
+
+            renderHistogram(container, cmd as HistogramCommand);
+            infoDiv.appendChild(container);
+        } else if (cmd.type === 'TABLE') {
+            const container = document.createElement('div');
+            container.style.marginBottom = '10px';
+
+            const titleLabel = document.createElement('div');
+            titleLabel.textContent = cmd.title;
+            titleLabel.style.fontWeight = 'bold';
+            titleLabel.style.marginBottom = '4px';
+            titleLabel.style.fontSize = '14px';
+            container.appendChild(titleLabel);
+
+            renderTable(container, cmd as TableCommand);
+            infoDiv.appendChild(container);
         } else if (cmd.type === 'SCORE') {
             scoreDisplay.textContent = `Score = ${cmd.score}`;
         }
@@ -43,6 +43,69 @@ export interface GridLine {
     points: Point[];
 }
 
+// C: center, T/B/L/R: edges, TL/TR/BL/BR: corners
+export type TextAnchor = 'C' | 'T' | 'B' | 'L' | 'R' | 'TL' | 'TR' | 'BL' | 'BR';
+
+export interface GridTextItem {
+    x: number;
+    y: number;
+    text: string;
+}
+
+export interface GridTextGroup {
+    color: string;
+    fontSize: number; // 0 means auto
+    anchor: TextAnchor;
+    texts: GridTextItem[];
+}
+
+export type MarkerShape = 'CIRCLE' | 'SQUARE' | 'DIAMOND' | 'TRIANGLE' | 'CROSS' | 'STAR';
+
+export interface GridMarkerGroup {
+    shape: MarkerShape;
+    strokeColor: string;
+    fillColor: string;
+    size: number; // ratio to the cell size
+    points: Point[];
+}
+
+export type LineStyle = 'SOLID' | 'DASHED' | 'DOTTED';
+
+export interface GridPolyline {
+    color: string;
+    width: number;
+    style: LineStyle;
+    points: Point[];
+}
+
+export interface GridArrow {
+    from: Point;
+    to: Point;
+    label: string;
+}
+
+export interface GridArrowGroup {
+    color: string;
+    width: number;
+    style: LineStyle;
+    arrows: GridArrow[];
+}
+
+export interface GridWallStyle {
+    color: string;
+    width: number;
+}
+
+export interface GridAxisLabel {
+    index: number;
+    text: string;
+}
+
+export interface GridAxisLabels {
+    rows: GridAxisLabel[];
+    cols: GridAxisLabel[];
+}
+
 export interface ItemBounds {
     left: number;
     top: number;
@@ -58,9 +121,54 @@ export interface GridCommand {
     textColor: string;
     gridColors: string[][];
     gridTexts: string[][];
+    gridTextGroups: GridTextGroup[];
+    gridMarkerGroups: GridMarkerGroup[];
     gridLines: GridLine[];
+    gridPolylines: GridPolyline[];
+    gridArrowGroups: GridArrowGroup[];
     wallVertical: string[];
     wallHorizontal: string[];
+    wallStyles: { [key: string]: GridWallStyle }; // key: "H r c" or "V r c"
+    rowOffset: number; // original index of the first row (OFFSET section)
+    colOffset: number; // original index of the first column (OFFSET section)
+    axisLabels: GridAxisLabels;
+    tooltips: { [key: string]: string }; // key: "y,x"
+    bounds?: ItemBounds; // Optional bounds within canvas
+}
+
+export type HexOrientation = 'POINTY' | 'FLAT';
+
+export interface HexEdge {
+    x: number;
+    y: number;
+    dir: number; // 0-5, index into the axial neighbor directions
+}
+
+export interface HexGridCommand {
+    type: 'HEX_GRID';
+    H: number;
+    W: number;
+    orientation: HexOrientation;
+    borderColor: string;
+    textColor: string;
+    cellColors: string[][];
+    cellTexts: string[][];
+    lines: GridLine[];
+    hiddenEdges: HexEdge[];
+    bounds?: ItemBounds; // Optional bounds within canvas
+}
+
+// Rows of alternating up/down triangles; cell (x, y) points up when x + y is even
+export interface TriGridCommand {
+    type: 'TRI_GRID';
+    H: number;
+    W: number;
+    borderColor: string;
+    textColor: string;
+    cellColors: string[][];
+    cellTexts: string[][];
+    lines: GridLine[];
+    hiddenEdges: HexEdge[]; // dir 0: horizontal, 1: left, 2: right
     bounds?: ItemBounds; // Optional bounds within canvas
 }
 
@@ -86,6 +194,7 @@ export interface Circle {
     x: number;
     y: number;
     r: number;
+    tooltip?: string;
 }
 
 export interface Polygon {
@@ -95,19 +204,106 @@ export interface Polygon {
 export interface CircleGroup {
     lineColor: string;
     fillColor: string;
+    lineWidth?: number; // stroke width (default 2)
+    style?: LineStyle;
     circles: Circle[];
 }
 
 export interface LineGroup {
     color: string;
     width: number;
+    style?: LineStyle;
     points: Point[];
 }
 
+export interface PlanePolyline {
+    color: string;
+    width: number;
+    style: LineStyle;
+    points: Point[];
+}
+
+export interface PlaneEllipse {
+    cx: number;
+    cy: number;
+    rx: number;
+    ry: number;
+    angle: number; // degrees from the x axis toward the y axis
+}
+
+export interface EllipseGroup {
+    lineColor: string;
+    fillColor: string;
+    lineWidth: number;
+    style: LineStyle;
+    ellipses: PlaneEllipse[];
+}
+
+export interface PlaneArc {
+    cx: number;
+    cy: number;
+    r: number;
+    start: number; // degrees from the x axis toward the y axis
+    end: number;
+}
+
+export interface ArcGroup {
+    lineColor: string;
+    fillColor?: string; // set for sectors, which are closed through the center
+    lineWidth: number;
+    style: LineStyle;
+    arcs: PlaneArc[];
+}
+
+// Quadratic curves have 3 control points, cubic curves 4
+export interface CurveGroup {
+    cubic: boolean;
+    color: string;
+    width: number;
+    style: LineStyle;
+    curves: Point[][];
+}
+
+export interface PlaneArrow {
+    from: Point;
+    to: Point;
+}
+
+export interface PlaneArrowGroup {
+    color: string;
+    width: number;
+    headSize: number; // in pixels
+    arrows: PlaneArrow[];
+}
+
 export interface PolygonGroup {
     lineColor: string;
     fillColor: string;
+    lineWidth?: number; // stroke width (default 2)
+    style?: LineStyle;
     polygon: Polygon;
+    tooltip?: string;
+}
+
+export interface PlanePointGroup {
+    shape: MarkerShape;
+    color: string;
+    size: number; // radius in pixels, independent of the plane scale
+    points: Point[];
+}
+
+export interface PlaneRect {
+    x: number; // left
+    y: number; // top
+    w: number;
+    h: number;
+    label: string;
+}
+
+export interface RectGroup {
+    lineColor: string;
+    fillColor: string;
+    rects: PlaneRect[];
 }
 
 export interface TextItem {
@@ -119,17 +315,46 @@ export interface TextItem {
 export interface TextGroup {
     color: string;
     fontSize: number;
+    anchor?: TextAnchor; // which part of the text is placed at (x, y), default center
+    rotation?: number; // degrees clockwise around (x, y)
+    background?: string;
+    padding?: number; // pixels around the text
+    offsetX?: number; // pixels
+    offsetY?: number; // pixels
     texts: TextItem[];
 }
 
-export interface TwoDPlaneCommand {
-    type: '2D_PLANE';
-    H: number;
-    W: number;
+// Shapes of one z-layer. Layers are drawn in order, later layers on top
+export interface PlaneLayer {
     circleGroups: CircleGroup[];
     lineGroups: LineGroup[];
+    polylines: PlanePolyline[];
+    curveGroups: CurveGroup[];
+    arrowGroups: PlaneArrowGroup[];
+    ellipseGroups: EllipseGroup[];
+    arcGroups: ArcGroup[];
     polygonGroups: PolygonGroup[];
+    rectGroups: RectGroup[];
+    pointGroups: PlanePointGroup[];
     textGroups: TextGroup[];
+}
+
+// Coordinate range drawn by a 2D plane
+export interface PlaneView {
+    minX: number;
+    maxX: number;
+    minY: number;
+    maxY: number;
+    yUp: boolean;
+    keepAspect: boolean; // same scale for x and y, centered in the bounds
+}
+
+export interface TwoDPlaneCommand {
+    type: '2D_PLANE';
+    H: number;
+    W: number;
+    view: PlaneView;
+    layers: PlaneLayer[];
     bounds?: ItemBounds; // Optional bounds within canvas
 }
 
@@ -153,7 +378,62 @@ export interface BarGraphCommand {
     items: BarGraphItem[];
 }
 
-export type Command = CanvasCommand | GridCommand | TextAreaCommand | ScoreCommand | DebugCommand | TwoDPlaneCommand | BarGraphCommand;
+export interface LineChartSeries {
+    name: string;
+    color: string;
+    points: Point[];
+}
+
+export interface LineChartCommand {
+    type: 'LINE_CHART';
+    title: string;
+    // undefined means the bound is computed from the data
+    xMin?: number;
+    xMax?: number;
+    yMin?: number;
+    yMax?: number;
+    logScale: boolean; // logarithmic y axis
+    marker?: number; // x position of the current frame
+    series: LineChartSeries[];
+}
+
+export interface HistogramStats {
+    count: number;
+    mean: number;
+    std: number;
+    min: number;
+    p25: number;
+    median: number;
+    p75: number;
+    p95: number;
+    max: number;
+}
+
+export interface HistogramCommand {
+    type: 'HISTOGRAM';
+    title: string;
+    fillColor: string;
+    edges: number[]; // bins.length + 1 boundaries
+    counts: number[];
+    stats?: HistogramStats; // undefined when there are no samples
+}
+
+export type TableCellKind = 'I' | 'F' | 'T';
+
+export interface TableCell {
+    kind: TableCellKind; // I and F are right-aligned
+    color?: string; // background color
+    value: string;
+}
+
+export interface TableCommand {
+    type: 'TABLE';
+    title: string;
+    header?: string[]; // undefined for key-value tables
+    rows: TableCell[][];
+}
+
+export type Command = CanvasCommand | GridCommand | HexGridCommand | TriGridCommand | TextAreaCommand | ScoreCommand | DebugCommand | TwoDPlaneCommand | BarGraphCommand | LineChartCommand | HistogramCommand | TableCommand;
 
 export interface Frame {
     commands: Command[];